@@ -0,0 +1,90 @@
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use crunch::{Collectable, Gc, Heap, OptionBuilder};
+
+/// Every operation should take the same time no matter how many objects are allocated,
+/// so the time per object should stay flat across these sizes
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// Creates a GC large enough to allocate `objects` integers without collecting
+fn gc(objects: usize) -> Gc {
+    Gc::new(
+        &OptionBuilder::new("./gc_bench")
+            .heap_size(objects * std::mem::size_of::<usize>() * 2)
+            .build(),
+    )
+}
+
+/// Creates a GC with `objects` integers allocated in it
+fn allocated(objects: usize) -> (Gc, Vec<Heap<usize>>) {
+    let mut gc = gc(objects);
+    let stubs = (0..objects)
+        .map(|int| int.alloc(&mut gc).unwrap())
+        .collect();
+
+    (gc, stubs)
+}
+
+fn gc_operations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("GC");
+
+    for &objects in &SIZES {
+        group.throughput(Throughput::Elements(objects as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("Allocate", objects),
+            &objects,
+            |b, &objects| {
+                b.iter_batched(
+                    || gc(objects),
+                    |mut gc| {
+                        for int in 0..objects {
+                            int.alloc(&mut gc).unwrap();
+                        }
+
+                        gc
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("Fetch", objects),
+            &objects,
+            |b, &objects| {
+                let (gc, stubs) = allocated(objects);
+
+                b.iter(|| {
+                    for stub in &stubs {
+                        black_box(stub.fetch(&gc).unwrap());
+                    }
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("Add Child", objects),
+            &objects,
+            |b, &objects| {
+                b.iter_batched(
+                    || allocated(objects),
+                    |(mut gc, stubs)| {
+                        for child in &stubs[1..] {
+                            stubs[0].add_child(child, &mut gc).unwrap();
+                        }
+
+                        (gc, stubs)
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, gc_operations);
+criterion_main!(benches);
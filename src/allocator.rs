@@ -0,0 +1,349 @@
+use crate::{
+    code_builder::VirtualRegister, Instruction, Register, NUMBER_CALLER_REGISTERS, NUMBER_REGISTERS,
+};
+use std::collections::{BTreeSet, HashMap};
+
+/// The registers that spilled values are loaded into for an instruction, one for each register an
+/// instruction can use
+const SCRATCH_REGISTERS: [u8; 2] = [
+    NUMBER_CALLER_REGISTERS as u8,
+    NUMBER_CALLER_REGISTERS as u8 + 1,
+];
+
+/// The first register that can be given to a virtual register
+const FIRST_ALLOCATABLE_REGISTER: usize = NUMBER_CALLER_REGISTERS + SCRATCH_REGISTERS.len();
+
+/// Where a virtual register lives for its entire lifetime
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Location {
+    Register(u8),
+    Slot(u32),
+}
+
+/// The instructions that a virtual register is live for, inclusive on both ends
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Interval {
+    register: VirtualRegister,
+    start: usize,
+    end: usize,
+}
+
+/// Allocates the virtual registers of a function with a linear scan, returning the function's final
+/// instructions along with the index of the instruction in `block` that each one came from
+///
+/// Every virtual register is given a single location for its entire lifetime, either a callee-saved
+/// register or, once those run out, a stack slot of the function. Spilled values are reloaded into a
+/// scratch register before every instruction that reads them and spilled again after every
+/// instruction that writes them, so allocation can never fail
+pub fn allocate(block: Vec<(Instruction, Vec<VirtualRegister>)>) -> (Vec<Instruction>, Vec<usize>) {
+    let locations = assign_locations(live_intervals(&block));
+
+    let mut instructions = Vec::with_capacity(block.len());
+    let mut origins = Vec::with_capacity(block.len());
+    for (origin, (mut instruction, registers)) in block.into_iter().enumerate() {
+        let writes = instruction.writes_register();
+        // Dropping a spilled value has to empty its slot too, otherwise the slot keeps it rooted
+        let clears = matches!(instruction, Instruction::Drop(_));
+        let (mut reloads, mut spills) = (Vec::new(), Vec::new());
+
+        let operands = instruction.registers_mut();
+        debug_assert_eq!(
+            operands.len(),
+            registers.len(),
+            "Every register of an instruction must be given a virtual register",
+        );
+
+        for (index, (operand, register)) in operands.into_iter().zip(registers).enumerate() {
+            *operand = if register.is_caller() {
+                Register(register.0 as u8)
+            } else {
+                match locations[&register] {
+                    Location::Register(reg) => Register(reg),
+                    Location::Slot(slot) => {
+                        let scratch = Register(SCRATCH_REGISTERS[index]);
+
+                        if index == 0 && writes {
+                            spills.push(Instruction::Spill(scratch, slot));
+                        } else {
+                            reloads.push(Instruction::Reload(slot, scratch));
+
+                            if clears {
+                                spills.push(Instruction::Spill(scratch, slot));
+                            }
+                        }
+
+                        scratch
+                    }
+                }
+            };
+        }
+
+        let added = reloads.len() + 1 + spills.len();
+        instructions.append(&mut reloads);
+        instructions.push(instruction);
+        instructions.append(&mut spills);
+        origins.resize(origins.len() + added, origin);
+    }
+
+    (instructions, origins)
+}
+
+/// Finds the live interval of every virtual register, sorted by where they start
+fn live_intervals(block: &[(Instruction, Vec<VirtualRegister>)]) -> Vec<Interval> {
+    let mut intervals: HashMap<VirtualRegister, Interval> = HashMap::new();
+    let mut jump_points = HashMap::new();
+
+    for (index, (instruction, registers)) in block.iter().enumerate() {
+        if let Instruction::JumpPoint(id) = instruction {
+            jump_points.insert(*id, index);
+        }
+
+        for &register in registers.iter().filter(|reg| !reg.is_caller()) {
+            intervals
+                .entry(register)
+                .and_modify(|interval| interval.end = index)
+                .or_insert(Interval {
+                    register,
+                    start: index,
+                    end: index,
+                });
+        }
+    }
+
+    // Every backwards jump forms a loop, given as the loop's start and the jump's index
+    let loops = block
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (instruction, _registers))| match instruction {
+            Instruction::Jump(id) | Instruction::JumpComp(id) => jump_points
+                .get(&(*id as u32))
+                .filter(|&&target| target < index)
+                .map(|&target| (target, index)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    // Anything that's live going into a loop has to stay live until the loop jumps back, extending
+    // one interval can make it enter another loop, so this is done until nothing changes
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for interval in intervals.values_mut() {
+            for &(loop_start, loop_end) in &loops {
+                if interval.start < loop_start
+                    && interval.end >= loop_start
+                    && interval.end < loop_end
+                {
+                    interval.end = loop_end;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    let mut intervals = intervals.values().copied().collect::<Vec<_>>();
+    intervals.sort_by_key(|interval| (interval.start, interval.register));
+
+    intervals
+}
+
+/// Gives every interval a location, preferring the highest free register
+fn assign_locations(intervals: Vec<Interval>) -> HashMap<VirtualRegister, Location> {
+    let mut locations = HashMap::with_capacity(intervals.len());
+    let mut free = (FIRST_ALLOCATABLE_REGISTER..NUMBER_REGISTERS)
+        .map(|reg| reg as u8)
+        .collect::<BTreeSet<u8>>();
+    // The intervals that currently hold a register
+    let mut active: Vec<Interval> = Vec::new();
+    // The last instruction that uses each stack slot
+    let mut slots: Vec<usize> = Vec::new();
+
+    for interval in intervals {
+        // Registers whose intervals have ended can be reused
+        active.retain(|active| {
+            if active.end < interval.start {
+                if let Location::Register(reg) = locations[&active.register] {
+                    free.insert(reg);
+                }
+
+                false
+            } else {
+                true
+            }
+        });
+
+        if let Some(&reg) = free.iter().next_back() {
+            free.remove(&reg);
+            locations.insert(interval.register, Location::Register(reg));
+            active.push(interval);
+
+            continue;
+        }
+
+        // Out of registers, so spill whichever interval ends last
+        let (index, furthest) = active
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by_key(|(_index, active)| active.end)
+            .expect("Some interval must hold a register if none are free");
+
+        if furthest.end > interval.end {
+            locations.insert(interval.register, locations[&furthest.register]);
+            locations.insert(
+                furthest.register,
+                Location::Slot(stack_slot(&mut slots, furthest)),
+            );
+            active[index] = interval;
+        } else {
+            locations.insert(
+                interval.register,
+                Location::Slot(stack_slot(&mut slots, interval)),
+            );
+        }
+    }
+
+    locations
+}
+
+/// Finds a stack slot that's unused for the entire interval
+fn stack_slot(slots: &mut Vec<usize>, interval: Interval) -> u32 {
+    // Slots are handed out in order, so a slot is free if its last user ended before the interval
+    if let Some(slot) = slots.iter().position(|&end| end < interval.start) {
+        slots[slot] = interval.end;
+
+        slot as u32
+    } else {
+        slots.push(interval.end);
+
+        (slots.len() - 1) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RuntimeValue;
+
+    fn load(register: u32, value: i32) -> (Instruction, Vec<VirtualRegister>) {
+        (
+            Instruction::Load(RuntimeValue::I32(value), Register(0)),
+            vec![VirtualRegister(register)],
+        )
+    }
+
+    fn print(register: u32) -> (Instruction, Vec<VirtualRegister>) {
+        (
+            Instruction::Print(Register(0)),
+            vec![VirtualRegister(register)],
+        )
+    }
+
+    #[test]
+    fn reuses_dead_registers() {
+        let base = NUMBER_CALLER_REGISTERS as u32;
+        let (instructions, _origins) = allocate(vec![
+            load(base, 1),
+            print(base),
+            load(base + 1, 2),
+            print(base + 1),
+        ]);
+
+        assert_eq!(
+            instructions[0],
+            Instruction::Load(RuntimeValue::I32(1), Register(31))
+        );
+        assert_eq!(
+            instructions[2],
+            Instruction::Load(RuntimeValue::I32(2), Register(31))
+        );
+    }
+
+    #[test]
+    fn spills_under_pressure() {
+        let base = NUMBER_CALLER_REGISTERS as u32;
+        let live = (NUMBER_REGISTERS * 2) as u32;
+
+        let mut block = Vec::new();
+        for register in base..base + live {
+            block.push(load(register, register as i32));
+        }
+        for register in base..base + live {
+            block.push(print(register));
+        }
+
+        let (instructions, origins) = allocate(block);
+
+        let spills = instructions
+            .iter()
+            .filter(|inst| matches!(inst, Instruction::Spill(_, _)))
+            .count();
+        let reloads = instructions
+            .iter()
+            .filter(|inst| matches!(inst, Instruction::Reload(_, _)))
+            .count();
+
+        let registers = NUMBER_REGISTERS - FIRST_ALLOCATABLE_REGISTER;
+        assert_eq!(spills, live as usize - registers);
+        assert_eq!(reloads, live as usize - registers);
+
+        // Reloads belong to the print that needed them
+        assert_eq!(origins.len(), instructions.len());
+        for (instruction, &origin) in instructions.iter().zip(&origins) {
+            if let Instruction::Reload(_, _) = instruction {
+                assert!(origin >= live as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn drops_clear_spill_slots() {
+        let base = NUMBER_CALLER_REGISTERS as u32;
+        let live = (NUMBER_REGISTERS * 2) as u32;
+
+        let mut block = Vec::new();
+        for register in base..base + live {
+            block.push(load(register, register as i32));
+        }
+        for register in base..base + live {
+            block.push((
+                Instruction::Drop(Register(0)),
+                vec![VirtualRegister(register)],
+            ));
+        }
+
+        let (instructions, _origins) = allocate(block);
+
+        // Every spilled value is reloaded, dropped and then spilled again, emptying its slot
+        let mut cleared = 0;
+        for (index, instruction) in instructions.iter().enumerate() {
+            if let Instruction::Reload(slot, scratch) = *instruction {
+                assert_eq!(instructions[index + 1], Instruction::Drop(scratch));
+                assert_eq!(instructions[index + 2], Instruction::Spill(scratch, slot));
+                cleared += 1;
+            }
+        }
+
+        let registers = NUMBER_REGISTERS - FIRST_ALLOCATABLE_REGISTER;
+        assert_eq!(cleared, live as usize - registers);
+    }
+
+    #[test]
+    fn loops_extend_intervals() {
+        let base = NUMBER_CALLER_REGISTERS as u32;
+        let intervals = live_intervals(&[
+            load(base, 0),
+            (Instruction::JumpPoint(1), Vec::new()),
+            print(base),
+            load(base + 1, 1),
+            print(base + 1),
+            (Instruction::Jump(1), Vec::new()),
+        ]);
+
+        // Loaded before the loop, so it must survive until the loop jumps back
+        assert_eq!(intervals[0].end, 5);
+        // Only used within a single iteration
+        assert_eq!(intervals[1].end, 4);
+    }
+}
@@ -1,9 +1,690 @@
-#![allow(dead_code)]
+use crate::{
+    AllocId, Instruction, Register, RuntimeError, RuntimeErrorTy, RuntimeValue, NUMBER_REGISTERS,
+};
+use std::{collections::HashMap, fmt};
 
-use crate::Instruction;
+// Assembly Format
+//
+// Comments start with `;` and go until the end of the line
+//
+// >       Constants       <
+// .constants
+//     #<name> <type> <literal>
+// Types are the names given by `RuntimeValue::name`, floats, chars and strings are written the
+// same way that Rust's `Debug` writes them, `null` and `NoneType` have no literal
+//
+// >       Functions       <
+// .function <name>
+//     <label>:
+//     <mnemonic> <operands, separated by commas>
+// The first function is the main function, and functions named with a number must be named with
+// their index. Numeric labels such as the `0004:` that the disassembler writes are ignored
+//
+// >       Operands        <
+// Registers: r<index>
+// Stack slots: s<index>
+// Constants: #<name>, or an inline `<type> <literal>`
+// Jump targets: a label or the index of an instruction within the function
+// Functions: a function name or the index of a function
+// Object members and member counts: a plain number
 
-pub fn assemble(input: &str) -> Vec<Instruction> {
-    for _line in input.lines() {}
+/// An error that occurred while assembling, along with the line that it occurred on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyError {
+    /// The line the error occurred on, starting from `1`
+    pub line: usize,
+    /// The error message
+    pub message: String,
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Error on line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AssemblyError {}
+
+impl From<AssemblyError> for RuntimeError {
+    fn from(err: AssemblyError) -> Self {
+        Self {
+            ty: RuntimeErrorTy::CompilationError,
+            message: err.to_string(),
+            backtrace: None,
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, String>;
+
+/// A reference that can only be resolved once everything has been assembled
+#[derive(Debug)]
+enum Fixup<'a> {
+    Constant(&'a str),
+    Jump(&'a str),
+    Function(&'a str),
+}
+
+#[derive(Debug, Default)]
+struct Function<'a> {
+    instructions: Vec<Instruction>,
+    labels: HashMap<&'a str, usize>,
+    /// The line, instruction index and reference of every unresolved operand
+    fixups: Vec<(usize, usize, Fixup<'a>)>,
+}
+
+/// Assembles text assembly into a Function Table, where the main function is at index `0`
+pub fn assemble(input: &str) -> std::result::Result<Vec<Vec<Instruction>>, AssemblyError> {
+    let mut constants: HashMap<&str, RuntimeValue> = HashMap::new();
+    let mut functions: Vec<Function<'_>> = Vec::new();
+    let mut function_names: HashMap<&str, usize> = HashMap::new();
+    let mut in_constants = false;
+
+    for (line_index, line) in input.lines().enumerate() {
+        let error = |message: String| AssemblyError {
+            line: line_index + 1,
+            message,
+        };
+        let mut line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(directive) = line.strip_prefix('.') {
+            let mut parts = directive.split_whitespace();
+
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("constants"), None, None) => in_constants = true,
+                (Some("function"), Some(name), None) => {
+                    in_constants = false;
+
+                    if name
+                        .parse::<usize>()
+                        .map_or(false, |index| index != functions.len())
+                    {
+                        return Err(error(format!(
+                            "The function '{}' must be named with its index, {}",
+                            name,
+                            functions.len()
+                        )));
+                    }
+                    if function_names.insert(name, functions.len()).is_some() {
+                        return Err(error(format!("The function '{}' already exists", name)));
+                    }
+
+                    functions.push(Function::default());
+                }
+                _ => return Err(error(format!("Invalid directive '{}'", line))),
+            }
+
+            continue;
+        }
+
+        if in_constants {
+            let (name, value) = line
+                .strip_prefix('#')
+                .and_then(|line| split_once(line, char::is_whitespace))
+                .ok_or_else(|| error(format!("Invalid constant '{}'", line)))?;
+            let value = constant(value.trim()).map_err(error)?;
+
+            if constants.insert(name, value).is_some() {
+                return Err(error(format!("The constant '#{}' already exists", name)));
+            }
+
+            continue;
+        }
+
+        let function = functions
+            .last_mut()
+            .ok_or_else(|| error("Instructions must be inside of a function".to_string()))?;
+
+        // Labels may share a line with an instruction
+        while let Some((label, rest)) = split_once(line, |c| c == ':') {
+            let label = label.trim();
+            if !is_label(label) {
+                break;
+            }
+
+            if !label.chars().all(|c| c.is_ascii_digit())
+                && function
+                    .labels
+                    .insert(label, function.instructions.len())
+                    .is_some()
+            {
+                return Err(error(format!("The label '{}' already exists", label)));
+            }
+
+            line = rest.trim();
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let index = function.instructions.len();
+        let (instruction, fixup) = instruction(line).map_err(error)?;
+
+        function.instructions.push(instruction);
+        if let Some(fixup) = fixup {
+            function.fixups.push((line_index + 1, index, fixup));
+        }
+    }
+
+    let number_functions = functions.len();
+    functions
+        .into_iter()
+        .map(|mut function| {
+            for (line, index, fixup) in function.fixups {
+                let error = |message: String| AssemblyError { line, message };
+
+                match (&mut function.instructions[index], fixup) {
+                    (Instruction::Load(value, _), Fixup::Constant(name)) => {
+                        *value = constants.get(name).cloned().ok_or_else(|| {
+                            error(format!("The constant '#{}' doesn't exist", name))
+                        })?;
+                    }
+
+                    (
+                        Instruction::Jump(offset) | Instruction::JumpComp(offset),
+                        Fixup::Jump(target),
+                    ) => {
+                        let target = match function.labels.get(target) {
+                            Some(target) => *target,
+                            None => target.parse::<usize>().map_err(|_| {
+                                error(format!("The label '{}' doesn't exist", target))
+                            })?,
+                        };
+
+                        // Jumps land on the instruction after their target
+                        *offset = target as i32 - index as i32 - 1;
+                    }
+
+                    (Instruction::Func(func), Fixup::Function(name)) => {
+                        *func = match function_names.get(name) {
+                            Some(func) => *func as u32,
+                            None => match name.parse::<usize>() {
+                                Ok(func) if func < number_functions => func as u32,
+                                _ => {
+                                    return Err(error(format!(
+                                        "The function '{}' doesn't exist",
+                                        name
+                                    )))
+                                }
+                            },
+                        };
+                    }
+
+                    _ => unreachable!("Fixups are only created for their instructions"),
+                }
+            }
+
+            Ok(function.instructions)
+        })
+        .collect()
+}
+
+/// Assembles a single instruction, along with any operand that has to be resolved later
+fn instruction(line: &str) -> Result<(Instruction, Option<Fixup<'_>>)> {
+    let (mnemonic, operands) = split_once(line, char::is_whitespace).unwrap_or((line, ""));
+    let operands = operands.trim();
+
+    // Inline constants may contain commas, so loads are split separately
+    if mnemonic == "ld" {
+        let (reg, value) = split_once(operands, |c| c == ',')
+            .ok_or_else(|| format!("'ld' takes 2 operands, found '{}'", operands))?;
+        let (reg, value) = (register(reg.trim())?, value.trim());
+
+        return Ok(if let Some(name) = value.strip_prefix('#') {
+            (
+                Instruction::Load(RuntimeValue::None, reg),
+                Some(Fixup::Constant(name)),
+            )
+        } else {
+            (Instruction::Load(constant(value)?, reg), None)
+        });
+    }
+
+    let operands = if operands.is_empty() {
+        Vec::new()
+    } else {
+        operands.split(',').map(str::trim).collect::<Vec<_>>()
+    };
+
+    macro_rules! operands {
+        ($($operand:ident),*) => {
+            match operands.as_slice() {
+                [$($operand),*] => ($(*$operand),*),
+                _ => {
+                    return Err(format!(
+                        "'{}' takes {} operands, found {}",
+                        mnemonic,
+                        0 $(+ { stringify!($operand); 1 })*,
+                        operands.len()
+                    ))
+                }
+            }
+        };
+    }
+
+    macro_rules! reg {
+        ($variant:ident) => {{
+            let reg = operands!(reg);
+            Instruction::$variant(register(reg)?)
+        }};
+    }
+
+    macro_rules! regs {
+        ($variant:ident) => {{
+            let (left, right) = operands!(left, right);
+            Instruction::$variant(register(left)?, register(right)?)
+        }};
+    }
+
+    macro_rules! none {
+        ($variant:ident) => {{
+            operands!();
+            Instruction::$variant
+        }};
+    }
+
+    let instruction = match mnemonic {
+        "cmp" => reg!(CompToReg),
+        "opr" => reg!(OpToReg),
+        "drop" => reg!(Drop),
+        "mov" => regs!(Move),
+        "push" => reg!(Push),
+        "pop" => reg!(Pop),
+        "spill" => {
+            let (reg, slot) = operands!(reg, slot);
+            Instruction::Spill(register(reg)?, stack_slot(slot)?)
+        }
+        "reload" => {
+            let (slot, reg) = operands!(slot, reg);
+            Instruction::Reload(stack_slot(slot)?, register(reg)?)
+        }
+        "newobj" => {
+            let (reg, members) = operands!(reg, members);
+            Instruction::NewObject(register(reg)?, member(members)?)
+        }
+        "getmem" | "setmem" => {
+            let (left, right, index) = operands!(left, right, index);
+            let (left, right, index) = (register(left)?, register(right)?, member(index)?);
+
+            if mnemonic == "getmem" {
+                Instruction::GetMember(left, right, index)
+            } else {
+                Instruction::SetMember(left, right, index)
+            }
+        }
+
+        "add" => regs!(Add),
+        "sub" => regs!(Sub),
+        "mul" => regs!(Mult),
+        "div" => regs!(Div),
+        "add?" => regs!(FallibleAdd),
+        "sub?" => regs!(FallibleSub),
+        "mul?" => regs!(FallibleMult),
+        "div?" => regs!(FallibleDiv),
+        "add!" => regs!(PanickingAdd),
+        "sub!" => regs!(PanickingSub),
+        "mul!" => regs!(PanickingMult),
+        "div!" => regs!(PanickingDiv),
+
+        "print" => reg!(Print),
+
+        "jmp" | "jmpcmp" => {
+            let target = operands!(target);
+            let instruction = if mnemonic == "jmp" {
+                Instruction::Jump(0)
+            } else {
+                Instruction::JumpComp(0)
+            };
+
+            return Ok((instruction, Some(Fixup::Jump(target))));
+        }
+        "jmppt" => {
+            let id = operands!(id);
+            Instruction::JumpPoint(
+                id.parse()
+                    .map_err(|_| format!("Invalid jump point '{}'", id))?,
+            )
+        }
+
+        "and" => regs!(And),
+        "or" => regs!(Or),
+        "xor" => regs!(Xor),
+        "not" => reg!(Not),
+
+        "eq" => regs!(Eq),
+        "neq" => regs!(NotEq),
+        "grt" => regs!(GreaterThan),
+        "let" => regs!(LessThan),
+        "greq" => regs!(GreaterEq),
+        "leeq" => regs!(LessEq),
+
+        "call" => {
+            let function = operands!(function);
+            return Ok((Instruction::Func(0), Some(Fixup::Function(function))));
+        }
+        "yield" => none!(Yield),
+        "ret" => none!(Return),
+
+        "coll" => none!(Collect),
+        "halt" => none!(Halt),
+
+        "illegal" => none!(Illegal),
+        "nop" => none!(NoOp),
+
+        _ => return Err(format!("Unknown instruction '{}'", mnemonic)),
+    };
+
+    Ok((instruction, None))
+}
+
+fn register(operand: &str) -> Result<Register> {
+    match operand.strip_prefix('r').map(str::parse::<u8>) {
+        Some(Ok(reg)) if (reg as usize) < NUMBER_REGISTERS => Ok(reg.into()),
+        _ => Err(format!("Invalid register '{}'", operand)),
+    }
+}
+
+fn stack_slot(operand: &str) -> Result<u32> {
+    operand
+        .strip_prefix('s')
+        .and_then(|slot| slot.parse().ok())
+        .ok_or_else(|| format!("Invalid stack slot '{}'", operand))
+}
+
+fn member(operand: &str) -> Result<u32> {
+    operand
+        .parse()
+        .map_err(|_| format!("Invalid member '{}'", operand))
+}
+
+/// Parses a constant in the form of `<type> <literal>`
+fn constant(constant: &str) -> Result<RuntimeValue> {
+    let (ty, literal) = split_once(constant, char::is_whitespace).unwrap_or((constant, ""));
+    let literal = literal.trim();
+
+    macro_rules! parse {
+        ($variant:ident) => {
+            RuntimeValue::$variant(
+                literal
+                    .parse()
+                    .map_err(|_| format!("Invalid {} '{}'", ty, literal))?,
+            )
+        };
+    }
+
+    let value = match ty {
+        "byte" => parse!(Byte),
+        "uint16" => parse!(U16),
+        "uint" => parse!(U32),
+        "uint64" => parse!(U64),
+        "uint128" => parse!(U128),
+        "ibyte" => parse!(IByte),
+        "int16" => parse!(I16),
+        "int" => parse!(I32),
+        "int64" => parse!(I64),
+        "int128" => parse!(I128),
+        "float" => parse!(F32),
+        "float64" => parse!(F64),
+        "bool" => parse!(Bool),
+        "ptr" => RuntimeValue::Pointer(AllocId(
+            literal
+                .parse()
+                .map_err(|_| format!("Invalid ptr '{}'", literal))?,
+        )),
+
+        "char" => {
+            let string = unescape(literal, '\'')?;
+            let mut chars = string.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => RuntimeValue::Char(c),
+                _ => return Err(format!("Invalid char '{}'", literal)),
+            }
+        }
+        "str" => RuntimeValue::Str(Box::leak(unescape(literal, '"')?.into_boxed_str())),
+
+        "null" | "NoneType" => {
+            if !literal.is_empty() {
+                return Err(format!("'{}' takes no literal, found '{}'", ty, literal));
+            }
+
+            if ty == "null" {
+                RuntimeValue::Null
+            } else {
+                RuntimeValue::None
+            }
+        }
+
+        _ => return Err(format!("Unknown type '{}'", ty)),
+    };
+
+    Ok(value)
+}
+
+/// Unescapes a quoted literal that was escaped in the same way as Rust's `Debug` escapes them
+fn unescape(literal: &str, quote: char) -> Result<String> {
+    let invalid = || format!("Invalid literal {}", literal);
+
+    let inner = literal
+        .strip_prefix(quote)
+        .and_then(|literal| literal.strip_suffix(quote))
+        .ok_or_else(invalid)?;
+
+    let mut string = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == quote {
+            return Err(invalid());
+        } else if c != '\\' {
+            string.push(c);
+            continue;
+        }
+
+        let escaped = match chars.next().ok_or_else(invalid)? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            '\\' => '\\',
+            '\'' => '\'',
+            '"' => '"',
+            'u' => {
+                if chars.next() != Some('{') {
+                    return Err(invalid());
+                }
+
+                let hex = chars.by_ref().take_while(|c| *c != '}').collect::<String>();
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(std::char::from_u32)
+                    .ok_or_else(invalid)?
+            }
+            _ => return Err(invalid()),
+        };
+
+        string.push(escaped);
+    }
+
+    Ok(string)
+}
+
+/// Removes a trailing comment, ignoring any semicolons within char or string literals
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if q == c => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, ';') => return &line[..index],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+fn is_label(label: &str) -> bool {
+    !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn split_once(string: &str, pattern: impl Fn(char) -> bool) -> Option<(&str, &str)> {
+    let index = string.find(pattern)?;
+    let (left, right) = string.split_at(index);
+
+    Some((left, &right[right.chars().next()?.len_utf8()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{disassemble_functions, Instruction::*, OptionBuilder, Vm};
+
+    #[test]
+    fn assembly() {
+        const CODE: &str = r#"
+.constants
+    #zero int 0
+    #ten int 10
+
+; Count up to ten
+.function main
+    ld r0, #zero
+    ld r1, int 1
+    ld r2, #ten
+    ld r3, str "a; b, c"  ; Semicolons and commas in strings are kept
+loop:
+    add r0, r1
+    opr r0
+    eq r0, r2
+    jmpcmp done
+    jmp loop
+done: call noop
+    halt
+
+.function noop
+    ret
+"#;
+
+        let functions = assemble(CODE).unwrap();
+        assert_eq!(
+            functions,
+            vec![
+                vec![
+                    Load(RuntimeValue::I32(0), 0.into()),
+                    Load(RuntimeValue::I32(1), 1.into()),
+                    Load(RuntimeValue::I32(10), 2.into()),
+                    Load(RuntimeValue::Str("a; b, c"), 3.into()),
+                    Add(0.into(), 1.into()),
+                    OpToReg(0.into()),
+                    Eq(0.into(), 2.into()),
+                    JumpComp(1),
+                    Jump(-5),
+                    Func(1),
+                    Halt,
+                ],
+                vec![Return],
+            ]
+        );
+
+        let options = OptionBuilder::new("./assembler_test").build();
+        let mut vm = Vm::new(&options, Box::new(Vec::<u8>::new()));
+        vm.execute(functions).unwrap();
+
+        assert!(vm.registers[0]
+            .clone()
+            .is_equal(RuntimeValue::I32(10), &vm.gc)
+            .unwrap());
+    }
+
+    #[test]
+    fn disassembly_round_trip() {
+        let functions = vec![
+            vec![
+                Load(RuntimeValue::I32(10), 0.into()),
+                Load(RuntimeValue::Str("hello\n"), 1.into()),
+                Load(RuntimeValue::Null, 2.into()),
+                Func(1),
+                GreaterEq(0.into(), 1.into()),
+                JumpComp(2),
+                Print(1.into()),
+                JumpPoint(0),
+                Halt,
+            ],
+            vec![
+                Load(RuntimeValue::F64(1.5), 2.into()),
+                Spill(2.into(), 0),
+                Reload(0, 3.into()),
+                Push(3.into()),
+                Pop(4.into()),
+                NewObject(5.into(), 2),
+                SetMember(5.into(), 4.into(), 1),
+                GetMember(6.into(), 5.into(), 1),
+                Jump(-9),
+                Return,
+            ],
+        ];
+
+        let disassembly = disassemble_functions(&functions);
+        let assembled = assemble(&disassembly).unwrap();
+
+        assert_eq!(functions, assembled);
+        assert_eq!(disassembly, disassemble_functions(&assembled));
+    }
+
+    #[test]
+    fn assembly_errors() {
+        fn error(code: &str) -> AssemblyError {
+            assemble(code).unwrap_err()
+        }
+
+        assert_eq!(error("ret").line, 1);
+        assert_eq!(error(".function main\n    frob r0").line, 2);
+        assert_eq!(error(".function main\n    print r32").line, 2);
+        assert_eq!(error(".function main\n    add r0").line, 2);
+        assert_eq!(
+            error(".function main\n    ld r0, #missing\n    ret").line,
+            2
+        );
+        assert_eq!(error(".function main\n    ret\n    jmp nowhere").line, 3);
+        assert_eq!(error(".function main\n    call nothing").line, 2);
+        assert_eq!(error(".function main\n.function main").line, 2);
+        assert_eq!(error(".function main\n.function 2").line, 2);
+        assert_eq!(error(".constants\n    #x int 1\n    #x int 2").line, 3);
+        assert_eq!(error(".constants\n    #x uint -1").line, 2);
+        assert_eq!(error(".constants\n    #x str \"unterminated").line, 2);
+    }
+
+    mod property_tests {
+        use super::super::*;
+        use crate::{disassemble_functions, Instruction};
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn constants_round_trip(string in any::<String>(), c in any::<char>(), float in any::<f32>(), double in any::<f64>()) {
+                let functions = vec![vec![
+                    Instruction::Load(RuntimeValue::Str(Box::leak(string.into_boxed_str())), 0.into()),
+                    Instruction::Load(RuntimeValue::Char(c), 1.into()),
+                    Instruction::Load(RuntimeValue::F32(float), 2.into()),
+                    Instruction::Load(RuntimeValue::F64(double), 3.into()),
+                    Instruction::Halt,
+                ]];
+
+                let disassembly = disassemble_functions(&functions);
+                let assembled = assemble(&disassembly).unwrap();
 
-    todo!("Work on assembler")
+                // Compare the disassembly, since that also compares floats bit for bit
+                prop_assert_eq!(disassembly, disassemble_functions(&assembled));
+            }
+        }
+    }
 }
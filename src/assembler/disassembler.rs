@@ -1 +1,201 @@
+use crate::{Decoder, Instruction, Result, RuntimeValue};
+use std::fmt::Write;
 
+/// Disassembles bytecode into a human-readable format, verifying it first
+pub fn disassemble(bytes: &[u8]) -> Result<String> {
+    let (main, mut functions) = Decoder::new(bytes).decode()?;
+    functions.insert(0, main);
+
+    Ok(disassemble_functions(&functions))
+}
+
+/// Disassembles a Function Table into a human-readable format.
+///
+/// Constants are numbered in the order that their `Load`s appear, which is the same order that
+/// the [`Encoder`](crate::Encoder) encodes them in
+#[must_use]
+pub fn disassemble_functions(functions: &[Vec<Instruction>]) -> String {
+    // Writing to a `String` can't fail
+    let mut output = String::new();
+
+    writeln!(&mut output, ".constants").unwrap();
+    let constants = functions.iter().flatten().filter_map(|instruction| {
+        if let Instruction::Load(value, _) = instruction {
+            Some(value)
+        } else {
+            None
+        }
+    });
+    for (index, value) in constants.enumerate() {
+        writeln!(&mut output, "    #{} {}", index, constant(value)).unwrap();
+    }
+
+    let mut constant = 0;
+    for (function_index, function) in functions.iter().enumerate() {
+        if function_index == 0 {
+            writeln!(&mut output, "\n.function 0 ; main").unwrap();
+        } else {
+            writeln!(&mut output, "\n.function {}", function_index).unwrap();
+        }
+
+        for (index, instruction) in function.iter().enumerate() {
+            let operands = match instruction {
+                Instruction::Load(_, reg) => {
+                    constant += 1;
+                    format!("r{}, #{}", **reg, constant - 1)
+                }
+
+                Instruction::CompToReg(reg)
+                | Instruction::OpToReg(reg)
+                | Instruction::Drop(reg)
+                | Instruction::Push(reg)
+                | Instruction::Pop(reg)
+                | Instruction::Print(reg)
+                | Instruction::Not(reg) => format!("r{}", **reg),
+
+                Instruction::Move(left, right)
+                | Instruction::Add(left, right)
+                | Instruction::Sub(left, right)
+                | Instruction::Mult(left, right)
+                | Instruction::Div(left, right)
+                | Instruction::FallibleAdd(left, right)
+                | Instruction::FallibleSub(left, right)
+                | Instruction::FallibleMult(left, right)
+                | Instruction::FallibleDiv(left, right)
+                | Instruction::PanickingAdd(left, right)
+                | Instruction::PanickingSub(left, right)
+                | Instruction::PanickingMult(left, right)
+                | Instruction::PanickingDiv(left, right)
+                | Instruction::And(left, right)
+                | Instruction::Or(left, right)
+                | Instruction::Xor(left, right)
+                | Instruction::Eq(left, right)
+                | Instruction::NotEq(left, right)
+                | Instruction::GreaterThan(left, right)
+                | Instruction::LessThan(left, right)
+                | Instruction::GreaterEq(left, right)
+                | Instruction::LessEq(left, right) => format!("r{}, r{}", **left, **right),
+
+                Instruction::Spill(reg, slot) => format!("r{}, s{}", **reg, slot),
+                Instruction::Reload(slot, reg) => format!("s{}, r{}", slot, **reg),
+                Instruction::NewObject(reg, members) => format!("r{}, {}", **reg, members),
+                Instruction::GetMember(left, right, member)
+                | Instruction::SetMember(left, right, member) => {
+                    format!("r{}, r{}, {}", **left, **right, member)
+                }
+
+                // Jumps land on the instruction after their target, show where that is
+                Instruction::Jump(offset) | Instruction::JumpComp(offset) => format!(
+                    "{:04} ; {:+}",
+                    index as i64 + i64::from(*offset) + 1,
+                    offset
+                ),
+                Instruction::JumpPoint(id) => id.to_string(),
+                Instruction::Func(func) => func.to_string(),
+
+                Instruction::Yield
+                | Instruction::Return
+                | Instruction::Collect
+                | Instruction::Halt
+                | Instruction::Illegal
+                | Instruction::NoOp => String::new(),
+            };
+
+            let line = format!("    {:04}: {:<7} {}", index, instruction.to_str(), operands);
+            writeln!(&mut output, "{}", line.trim_end()).unwrap();
+        }
+    }
+
+    output
+}
+
+/// Formats a constant as its type name followed by its literal value
+fn constant(value: &RuntimeValue) -> String {
+    let literal = match value {
+        RuntimeValue::Byte(int) => int.to_string(),
+        RuntimeValue::U16(int) => int.to_string(),
+        RuntimeValue::U32(int) => int.to_string(),
+        RuntimeValue::U64(int) => int.to_string(),
+        RuntimeValue::U128(int) => int.to_string(),
+        RuntimeValue::IByte(int) => int.to_string(),
+        RuntimeValue::I16(int) => int.to_string(),
+        RuntimeValue::I32(int) => int.to_string(),
+        RuntimeValue::I64(int) => int.to_string(),
+        RuntimeValue::I128(int) => int.to_string(),
+
+        // Debug formatting is used since it round-trips floats and escapes strings
+        RuntimeValue::F32(float) => format!("{:?}", float),
+        RuntimeValue::F64(float) => format!("{:?}", float),
+        RuntimeValue::Char(c) => format!("{:?}", c),
+        RuntimeValue::Str(string) => format!("{:?}", string),
+
+        RuntimeValue::Bool(boolean) => boolean.to_string(),
+        RuntimeValue::Pointer(ptr) => ptr.0.to_string(),
+
+        // Heap values live in the Gc and can't be shown without it
+        RuntimeValue::GcUint(_)
+        | RuntimeValue::GcInt(_)
+        | RuntimeValue::GcString(_)
+        | RuntimeValue::GcObject(_) => "<heap>".to_string(),
+        RuntimeValue::Null | RuntimeValue::None => return value.name().to_string(),
+    };
+
+    format!("{} {}", value.name(), literal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Encoder, Instruction::*};
+
+    #[test]
+    fn disassembly() {
+        let functions = vec![
+            vec![
+                Load(RuntimeValue::I32(10), 0.into()),
+                Load(RuntimeValue::Str("hello\n"), 1.into()),
+                Func(1),
+                Eq(0.into(), 1.into()),
+                JumpComp(1),
+                Print(1.into()),
+                Halt,
+            ],
+            vec![
+                Load(RuntimeValue::F64(1.5), 2.into()),
+                Spill(2.into(), 0),
+                Reload(0, 3.into()),
+                Jump(-4),
+                Return,
+            ],
+        ];
+
+        let expected = "\
+.constants
+    #0 int 10
+    #1 str \"hello\\n\"
+    #2 float64 1.5
+
+.function 0 ; main
+    0000: ld      r0, #0
+    0001: ld      r1, #1
+    0002: call    1
+    0003: eq      r0, r1
+    0004: jmpcmp  0006 ; +1
+    0005: print   r1
+    0006: halt
+
+.function 1
+    0000: ld      r2, #2
+    0001: spill   r2, s0
+    0002: reload  s0, r3
+    0003: jmp     0000 ; -4
+    0004: ret
+";
+
+        assert_eq!(disassemble_functions(&functions), expected);
+
+        let encoded = Encoder::new(functions).encode().unwrap();
+        assert_eq!(disassemble(&encoded).unwrap(), expected);
+        assert!(disassemble(&encoded[..encoded.len() - 1]).is_err());
+    }
+}
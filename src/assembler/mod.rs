@@ -1,2 +1,5 @@
 mod assembler;
 mod disassembler;
+
+pub use assembler::{assemble, AssemblyError};
+pub use disassembler::{disassemble, disassemble_functions};
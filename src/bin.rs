@@ -21,7 +21,11 @@ fn main() {
             match options.file.as_path().extension() {
                 Some(ext) => match ext.to_os_string() {
                     ref a if &*a == "crunch" => Crunch::run_source_file(options),
-                    ref b if &*b == "crunched" => Crunch::run_byte_file(options).unwrap(),
+                    ref b if &*b == "crunched" => {
+                        if let Err(err) = Crunch::run_byte_file(options) {
+                            err.emit();
+                        }
+                    }
                     _ => {
                         println!("Please choose a valid .crunch or .crunched file");
                     }
@@ -33,10 +37,19 @@ fn main() {
             }
         }
 
-        Opt::Build { options } => {
+        Opt::Build { output, options } => {
             if options.debug_log {
                 set_debug_hooks();
             }
+
+            match options.file.as_path().extension() {
+                Some(ext) if &*ext.to_os_string() == "crunch" => {
+                    Crunch::build_source_file(options, output)
+                }
+                _ => {
+                    println!("Please choose a valid .crunch file");
+                }
+            }
         }
 
         Opt::Verify { options } => {
@@ -44,38 +57,34 @@ fn main() {
                 set_debug_hooks();
             }
 
-            if let Some(ext) = options.file.as_path().extension() {
-                if &*ext.to_os_string() == "crunched" {
-                    let source = {
-                        use std::{fs::File, io::Read};
-
-                        let mut buf = Vec::new();
-
-                        let mut file = match File::open(&options.file) {
-                            Ok(file) => file,
-                            Err(err) => {
-                                println!("Error Opening File: {:?}", err);
-                                return;
-                            }
-                        };
-
-                        if let Err(err) = file.read_to_end(&mut buf) {
-                            println!("Error Reading File: {:?}", err);
-                            return;
-                        }
+            if let Some(source) = read_bytecode_file(&options) {
+                if let Err(err) = Crunch::validate(&source) {
+                    println!("Error Verifying Bytecode: {}", err);
+                } else {
+                    println!("Bytecode is valid");
+                }
+            }
+        }
 
-                        buf
-                    };
+        Opt::Disasm { options } => {
+            if options.debug_log {
+                set_debug_hooks();
+            }
 
-                    if let Err(err) = Crunch::validate(&source) {
-                        println!("Error Verifying Bytecode: {}", err);
-                    } else {
-                        println!("Bytecode is valid");
-                    }
+            if let Some(source) = read_bytecode_file(&options) {
+                match Crunch::validate(&source).map(Crunch::disassemble) {
+                    Ok(Ok(disassembly)) => print!("{}", disassembly),
+                    Ok(Err(err)) => err.emit(),
+                    Err(err) => println!("Error Verifying Bytecode: {}", err),
                 }
             }
         }
 
+        Opt::Explain { code } => match ErrorCode::from_code(&code) {
+            Some(error) => print!("{}", error.explain()),
+            None => println!("`{}` is not a Crunch error code", code),
+        },
+
         Opt::Repl {
             repl_options,
             options,
@@ -89,6 +98,36 @@ fn main() {
     }
 }
 
+/// Reads the `.crunched` file given in `options`, printing any errors that occur
+fn read_bytecode_file(options: &Options) -> Option<Vec<u8>> {
+    use std::{fs::File, io::Read};
+
+    match options.file.as_path().extension() {
+        Some(ext) if &*ext.to_os_string() == "crunched" => {}
+        _ => {
+            println!("Please choose a valid .crunched file");
+            return None;
+        }
+    }
+
+    let mut buf = Vec::new();
+
+    let mut file = match File::open(&options.file) {
+        Ok(file) => file,
+        Err(err) => {
+            println!("Error Opening File: {:?}", err);
+            return None;
+        }
+    };
+
+    if let Err(err) = file.read_to_end(&mut buf) {
+        println!("Error Reading File: {:?}", err);
+        return None;
+    }
+
+    Some(buf)
+}
+
 fn set_debug_hooks() {
     simple_logger::init().unwrap();
     #[cfg(not(miri))]
@@ -110,6 +149,9 @@ enum Opt {
 
     /// Builds a source file into it's compiled version
     Build {
+        /// The file to write the compiled bytecode to, defaults to the source file with a `.crunched` extension
+        #[structopt(short = "o", long = "--output", parse(from_os_str))]
+        output: Option<std::path::PathBuf>,
         #[structopt(flatten)]
         options: Options,
     },
@@ -120,6 +162,18 @@ enum Opt {
         options: Options,
     },
 
+    /// Disassembles compiled bytecode
+    Disasm {
+        #[structopt(flatten)]
+        options: Options,
+    },
+
+    /// Explains an error code, such as `E0201`
+    Explain {
+        /// The error code to explain
+        code: String,
+    },
+
     /// Runs a repl
     Repl {
         #[structopt(long = "--output", parse(from_str))]
@@ -1,33 +1,27 @@
-use crate::{Instruction, Result, RuntimeError, RuntimeErrorTy, RuntimeValue, INSTRUCTION_LENGTH};
-use std::{collections::VecDeque, convert::TryInto, mem::size_of};
+use super::{
+    verify::{verify_functions, Location, Section, VerifyError},
+    BYTECODE_VERSION, INSTRUCTION_BYTES, INSTRUCTION_LENGTH, MAGIC_NUMBER,
+};
+use crate::{Instruction, Result, RuntimeValue};
+use std::{collections::VecDeque, mem::size_of};
 
 // TODO: Document & Test all functions
 
 macro_rules! take {
-    ($self:tt, $ty:tt) => {{
-        let int = $ty::from_be_bytes(
-            $self.bytes[$self.index..$self.index + size_of::<$ty>()]
-                .try_into()
-                .unwrap_or_else(|_| unreachable!()),
-        );
+    ($self:tt, $ty:tt, $section:expr) => {{
+        let mut bytes = [0x00; size_of::<$ty>()];
+        bytes.copy_from_slice($self.take_bytes(size_of::<$ty>(), $section)?);
 
-        $self.index += size_of::<$ty>();
-
-        int
+        $ty::from_be_bytes(bytes)
     }};
 }
 
-#[derive(Debug, Clone)]
-struct FunctionMeta {
-    values: VecDeque<RuntimeValue>,
-}
-
 #[derive(Debug, Clone)]
 pub struct Decoder<'a> {
     bytes: &'a [u8],
     index: usize,
     functions: Vec<Vec<Instruction>>,
-    function_meta: VecDeque<FunctionMeta>,
+    values: Vec<RuntimeValue>,
     number_functions: usize,
 }
 
@@ -38,162 +32,243 @@ impl<'a> Decoder<'a> {
             bytes,
             index: 0,
             functions: Vec::new(),
-            function_meta: VecDeque::new(),
+            values: Vec::new(),
             number_functions: 0,
         }
     }
 
-    pub fn decode(mut self) -> Result<(Vec<Instruction>, Vec<Vec<Instruction>>)> {
-        self.get_number_functions();
+    /// Decodes and verifies bytecode into the Main Function and Function Table
+    pub fn decode(self) -> Result<(Vec<Instruction>, Vec<Vec<Instruction>>)> {
+        let mut functions = self.decode_functions()?;
+        verify_functions(&functions)?;
+
+        let main = functions.remove(0);
+        Ok((main, functions))
+    }
+
+    /// Decodes bytecode into the Function Table without verifying the decoded instructions
+    pub(super) fn decode_functions(
+        mut self,
+    ) -> std::result::Result<Vec<Vec<Instruction>>, VerifyError> {
+        self.check_header()?;
+        self.get_number_functions()?;
         self.fill_meta()?;
         self.fill_functions()?;
 
-        Ok((self.functions.remove(0), self.functions))
+        if self.index != self.bytes.len() {
+            return Err(VerifyError::TrailingBytes { offset: self.index });
+        }
+
+        Ok(self.functions)
     }
 
-    fn fill_functions(&mut self) -> Result<()> {
-        let mut functions = Vec::with_capacity(self.number_functions);
-        for _ in 0..self.number_functions {
-            functions.push(self.decode_function()?);
+    fn take_bytes(
+        &mut self,
+        len: usize,
+        section: Section,
+    ) -> std::result::Result<&'a [u8], VerifyError> {
+        let bytes = self
+            .index
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(self.index..end))
+            .ok_or(VerifyError::UnexpectedEnd {
+                section,
+                offset: self.bytes.len(),
+            })?;
+
+        self.index += len;
+
+        Ok(bytes)
+    }
+
+    fn check_header(&mut self) -> std::result::Result<(), VerifyError> {
+        if self.take_bytes(MAGIC_NUMBER.len(), Section::Header)? != MAGIC_NUMBER {
+            return Err(VerifyError::InvalidMagicNumber);
+        }
+
+        let version = take!(self, u16, Section::Header);
+        if version != BYTECODE_VERSION {
+            return Err(VerifyError::UnsupportedVersion(version));
         }
-        self.functions = functions;
 
         Ok(())
     }
 
-    fn get_number_functions(&mut self) {
-        self.number_functions = take!(self, u32) as usize;
+    fn fill_functions(&mut self) -> std::result::Result<(), VerifyError> {
+        // Don't trust the encoded number of functions for the allocation
+        let mut functions = Vec::new();
+        for function in 0..self.number_functions {
+            functions.push(self.decode_function(function)?);
+        }
+        self.functions = functions;
+
+        Ok(())
     }
 
-    fn decode_function(&mut self) -> Result<Vec<Instruction>> {
-        let function_len = take!(self, u32) as usize;
+    fn get_number_functions(&mut self) -> std::result::Result<(), VerifyError> {
+        self.number_functions = take!(self, u32, Section::Meta) as usize;
+
+        Ok(())
+    }
 
-        let mut instructions = Vec::with_capacity(function_len);
-        let mut meta = &mut self
-            .function_meta
-            .pop_front()
-            .expect("Expected a FunctionMeta to decode a function");
+    fn decode_function(
+        &mut self,
+        function: usize,
+    ) -> std::result::Result<Vec<Instruction>, VerifyError> {
+        let function_len = take!(self, u32, Section::Functions) as usize;
+
+        let mut instructions = Vec::new();
+        for instruction in 0..function_len {
+            let location = Location {
+                function,
+                instruction,
+            };
+            let bytes = self.take_bytes(INSTRUCTION_LENGTH, Section::Functions)?;
 
-        for _ in 0..function_len {
-            instructions.push(self.decode_instruction(&mut meta)?);
+            instructions.push(self.decode_instruction(bytes, location)?);
         }
 
         Ok(instructions)
     }
 
-    fn decode_instruction(&mut self, meta: &mut FunctionMeta) -> Result<Instruction> {
+    fn decode_instruction(
+        &self,
+        bytes: &[u8],
+        location: Location,
+    ) -> std::result::Result<Instruction, VerifyError> {
         macro_rules! take_from {
-            ($self:tt, $offset:tt, $ty:tt) => {
-                $ty::from_be_bytes(
-                    $self.bytes[$self.index + $offset..$self.index + $offset + size_of::<$ty>()]
-                        .try_into()
-                        .unwrap_or_else(|_| unreachable!()),
-                )
+            ($offset:tt, $ty:tt) => {{
+                let mut int = [0x00; size_of::<$ty>()];
+                int.copy_from_slice(&bytes[$offset..$offset + size_of::<$ty>()]);
+
+                $ty::from_be_bytes(int)
+            }};
+        }
+
+        macro_rules! reg {
+            ($offset:tt) => {
+                bytes[$offset].into()
             };
         }
 
-        let instruction = match self.bytes[self.index] {
+        let instruction = match bytes[0] {
             0x00 => Instruction::NoOp,
-            0x01 => Instruction::Load(
-                if let Some(value) = meta.values.pop_front() {
-                    value
-                } else {
-                    return Err(RuntimeError {
-                        ty: RuntimeErrorTy::MissingValue,
-                        message: "Not enough values were encoded".to_string(),
-                    });
-                },
-                self.bytes[1].into(),
-            ),
-            0x03 => Instruction::CompToReg(self.bytes[1].into()),
-            0x04 => Instruction::OpToReg(self.bytes[1].into()),
-            0x05 => Instruction::Drop(self.bytes[1].into()),
-
-            0x07 => Instruction::Add(self.bytes[1].into(), self.bytes[2].into()),
-            0x08 => Instruction::Sub(self.bytes[1].into(), self.bytes[2].into()),
-            0x09 => Instruction::Mult(self.bytes[1].into(), self.bytes[2].into()),
-            0x0A => Instruction::Div(self.bytes[1].into(), self.bytes[2].into()),
-
-            0x0B => Instruction::Print(self.bytes[1].into()),
-
-            0x0C => Instruction::Jump(take_from!(self, 1, i32)),
-            0x0D => Instruction::JumpComp(take_from!(self, 1, i32)),
-
-            0x0E => Instruction::And(self.bytes[1].into(), self.bytes[2].into()),
-            0x0F => Instruction::Or(self.bytes[1].into(), self.bytes[2].into()),
-            0x10 => Instruction::Xor(self.bytes[1].into(), self.bytes[2].into()),
-            0x11 => Instruction::Not(self.bytes[1].into()),
-
-            0x12 => Instruction::Eq(self.bytes[1].into(), self.bytes[2].into()),
-            0x13 => Instruction::NotEq(self.bytes[1].into(), self.bytes[2].into()),
-            0x14 => Instruction::GreaterThan(self.bytes[1].into(), self.bytes[2].into()),
-            0x15 => Instruction::LessThan(self.bytes[1].into(), self.bytes[2].into()),
+            0x01 => {
+                let value = take_from!(1, u32);
+
+                Instruction::Load(
+                    if let Some(value) = self.values.get(value as usize) {
+                        value.clone()
+                    } else {
+                        return Err(VerifyError::InvalidValueIndex { location, value });
+                    },
+                    reg!(5),
+                )
+            }
+            0x03 => Instruction::CompToReg(reg!(1)),
+            0x04 => Instruction::OpToReg(reg!(1)),
+            0x05 => Instruction::Drop(reg!(1)),
+            0x06 => Instruction::Move(reg!(1), reg!(2)),
+
+            0x07 => Instruction::Add(reg!(1), reg!(2)),
+            0x08 => Instruction::Sub(reg!(1), reg!(2)),
+            0x09 => Instruction::Mult(reg!(1), reg!(2)),
+            0x0A => Instruction::Div(reg!(1), reg!(2)),
+            0x24 => Instruction::FallibleAdd(reg!(1), reg!(2)),
+            0x25 => Instruction::FallibleSub(reg!(1), reg!(2)),
+            0x26 => Instruction::FallibleMult(reg!(1), reg!(2)),
+            0x27 => Instruction::FallibleDiv(reg!(1), reg!(2)),
+            0x28 => Instruction::PanickingAdd(reg!(1), reg!(2)),
+            0x29 => Instruction::PanickingSub(reg!(1), reg!(2)),
+            0x2A => Instruction::PanickingMult(reg!(1), reg!(2)),
+            0x2B => Instruction::PanickingDiv(reg!(1), reg!(2)),
+
+            0x0B => Instruction::Print(reg!(1)),
+
+            0x0C => Instruction::Jump(take_from!(1, i32)),
+            0x0D => Instruction::JumpComp(take_from!(1, i32)),
+
+            0x0E => Instruction::And(reg!(1), reg!(2)),
+            0x0F => Instruction::Or(reg!(1), reg!(2)),
+            0x10 => Instruction::Xor(reg!(1), reg!(2)),
+            0x11 => Instruction::Not(reg!(1)),
+
+            0x12 => Instruction::Eq(reg!(1), reg!(2)),
+            0x13 => Instruction::NotEq(reg!(1), reg!(2)),
+            0x14 => Instruction::GreaterThan(reg!(1), reg!(2)),
+            0x15 => Instruction::LessThan(reg!(1), reg!(2)),
+            0x1F => Instruction::GreaterEq(reg!(1), reg!(2)),
+            0x20 => Instruction::LessEq(reg!(1), reg!(2)),
 
             0x16 => Instruction::Return,
             0x17 => Instruction::Halt,
+            0x18 => Instruction::Func(take_from!(1, u32)),
 
             0x19 => Instruction::Collect,
+            0x1A => Instruction::Yield,
 
-            _ => Instruction::Illegal,
-        };
+            0x1B => Instruction::Push(reg!(1)),
+            0x1C => Instruction::Pop(reg!(1)),
+            0x1D => Instruction::Spill(reg!(1), take_from!(2, u32)),
+            0x1E => Instruction::Reload(take_from!(2, u32), reg!(1)),
 
-        self.index += INSTRUCTION_LENGTH;
+            0x21 => Instruction::NewObject(reg!(1), take_from!(2, u32)),
+            0x22 => Instruction::GetMember(reg!(1), reg!(2), take_from!(3, u32)),
+            0x23 => Instruction::SetMember(reg!(1), reg!(2), take_from!(3, u32)),
 
-        Ok(instruction)
-    }
+            opcode => {
+                debug_assert!(!INSTRUCTION_BYTES.contains(&opcode));
 
-    fn fill_meta(&mut self) -> Result<()> {
-        let mut meta = VecDeque::with_capacity(self.number_functions);
-        for _ in 0..self.number_functions {
-            let strings = self.take_strings()?;
-            let values = self.take_values(strings)?;
+                return Err(VerifyError::InvalidOpcode { location, opcode });
+            }
+        };
 
-            meta.push_back(FunctionMeta { values });
-        }
+        Ok(instruction)
+    }
 
-        self.function_meta = meta;
+    fn fill_meta(&mut self) -> std::result::Result<(), VerifyError> {
+        let strings = self.take_strings()?;
+        self.values = self.take_values(strings)?;
 
         Ok(())
     }
 
-    fn take_values(&mut self, mut strings: VecDeque<String>) -> Result<VecDeque<RuntimeValue>> {
-        let number_values = take!(self, u32) as usize;
-
-        let mut values = VecDeque::with_capacity(number_values);
-        for _ in 0..number_values {
-            let len = take!(self, u32) as usize;
-            let bytes = self.bytes[self.index..self.index + len]
-                .try_into()
-                .unwrap_or_else(|_| unreachable!());
-
-            // TODO: Handle this error and make it a RuntimeError
-            let value =
-                RuntimeValue::from_bytes(bytes, &mut strings).unwrap_or_else(|_| unreachable!());
-
-            values.push_back(value);
-
-            self.index += len;
+    fn take_values(
+        &mut self,
+        mut strings: VecDeque<String>,
+    ) -> std::result::Result<Vec<RuntimeValue>, VerifyError> {
+        let number_values = take!(self, u32, Section::Meta) as usize;
+
+        let mut values = Vec::new();
+        for index in 0..number_values {
+            let len = take!(self, u32, Section::Values) as usize;
+            let bytes = self.take_bytes(len, Section::Values)?;
+
+            let value = RuntimeValue::from_bytes(bytes, &mut strings).map_err(|err| {
+                VerifyError::InvalidValue {
+                    index,
+                    message: err.message,
+                }
+            })?;
+
+            values.push(value);
         }
 
         Ok(values)
     }
 
-    fn take_strings(&mut self) -> Result<VecDeque<String>> {
-        let number_strings = take!(self, u32) as usize;
+    fn take_strings(&mut self) -> std::result::Result<VecDeque<String>, VerifyError> {
+        let number_strings = take!(self, u32, Section::Meta) as usize;
 
-        let mut strings = VecDeque::with_capacity(number_strings);
-        for _ in 0..number_strings {
-            let len = take!(self, u32) as usize;
+        let mut strings = VecDeque::new();
+        for index in 0..number_strings {
+            let len = take!(self, u32, Section::Strings) as usize;
+            let bytes = self.take_bytes(len, Section::Strings)?;
 
-            let string = if let Ok(string) =
-                String::from_utf8(self.bytes[self.index..self.index + len].to_vec())
-            {
+            let string = if let Ok(string) = String::from_utf8(bytes.to_vec()) {
                 string
             } else {
-                return Err(RuntimeError {
-                    ty: RuntimeErrorTy::InvalidString,
-                    message: "Incorrectly encoded string".to_string(),
-                });
+                return Err(VerifyError::InvalidString { index });
             };
 
             strings.push_back(string);
@@ -1,5 +1,5 @@
-use super::INSTRUCTION_LENGTH;
-use crate::{Instruction, RuntimeValue};
+use super::{BYTECODE_VERSION, INSTRUCTION_LENGTH, MAGIC_NUMBER};
+use crate::{Instruction, Result, RuntimeValue};
 use std::mem::size_of;
 
 // TODO: Document & Test all functions
@@ -28,22 +28,24 @@ impl Encoder {
         }
     }
 
-    #[must_use]
-    pub fn encode(mut self) -> Vec<u8> {
+    pub fn encode(mut self) -> Result<Vec<u8>> {
+        self.bytes.extend_from_slice(&MAGIC_NUMBER);
+        self.bytes
+            .extend_from_slice(&BYTECODE_VERSION.to_be_bytes());
         self.bytes
-            .extend_from_slice(&self.num_functions.to_be_bytes());
+            .extend_from_slice(&(self.num_functions as u32).to_be_bytes());
 
         let (mut bytes, values) = self.encode_functions();
-        self.encode_values(values);
+        self.encode_values(values)?;
 
         self.bytes.append(&mut self.strings);
         self.bytes.append(&mut self.values);
         self.bytes.append(&mut bytes);
 
-        self.bytes
+        Ok(self.bytes)
     }
 
-    fn encode_values(&mut self, orig_values: Vec<RuntimeValue>) {
+    fn encode_values(&mut self, orig_values: Vec<RuntimeValue>) -> Result<()> {
         let mut values =
             Vec::with_capacity(size_of::<u32>() + (orig_values.len() * size_of::<RuntimeValue>()));
         values.extend_from_slice(&(orig_values.len() as u32).to_be_bytes());
@@ -52,7 +54,7 @@ impl Encoder {
         let mut len = 0;
 
         for value in orig_values {
-            let (val_bytes, string) = value.as_bytes();
+            let (val_bytes, string) = value.as_bytes()?;
 
             values.extend_from_slice(&val_bytes);
 
@@ -75,6 +77,8 @@ impl Encoder {
 
         self.strings.append(&mut strings_raw);
         self.values.append(&mut values);
+
+        Ok(())
     }
 
     fn encode_functions(&mut self) -> (Vec<u8>, Vec<RuntimeValue>) {
@@ -88,35 +92,35 @@ impl Encoder {
         let mut functions = Vec::new();
         std::mem::swap(&mut functions, &mut self.functions);
         for function in functions {
-            for instruction in function {
-                let (bytes, value) = self.encode_instruction(instruction);
+            output_bytes.extend_from_slice(&(function.len() as u32).to_be_bytes());
 
+            for instruction in function {
+                let bytes = self.encode_instruction(instruction, &mut output_values);
                 output_bytes.extend_from_slice(&bytes);
-
-                if let Some(value) = value {
-                    output_values.push(value);
-                }
             }
         }
 
         (output_bytes, output_values)
     }
 
+    /// Encodes a single instruction, adding any values it holds to the values section
     fn encode_instruction(
         &self,
         instruction: Instruction,
-    ) -> ([u8; INSTRUCTION_LENGTH], Option<RuntimeValue>) {
+        values: &mut Vec<RuntimeValue>,
+    ) -> [u8; INSTRUCTION_LENGTH] {
         let mut bytes = [0x00; INSTRUCTION_LENGTH];
-        let mut value = None;
 
         match instruction {
-            Instruction::NoOp => {
+            // Jumps are already resolved into relative offsets, so jump points are only markers
+            Instruction::NoOp | Instruction::JumpPoint(_) => {
                 bytes[0] = 0x00;
             }
             Instruction::Load(val, reg) => {
                 bytes[0] = 0x01;
-                bytes[size_of::<u32>() + 2] = *reg;
-                value = Some(val);
+                bytes[1..=size_of::<u32>()].copy_from_slice(&(values.len() as u32).to_be_bytes());
+                bytes[size_of::<u32>() + 1] = *reg;
+                values.push(val);
             }
             Instruction::CompToReg(reg) => {
                 bytes[0] = 0x03;
@@ -130,6 +134,46 @@ impl Encoder {
                 bytes[0] = 0x05;
                 bytes[1] = *reg;
             }
+            Instruction::Move(target, source) => {
+                bytes[0] = 0x06;
+                bytes[1] = *target;
+                bytes[2] = *source;
+            }
+            Instruction::Push(reg) => {
+                bytes[0] = 0x1B;
+                bytes[1] = *reg;
+            }
+            Instruction::Pop(reg) => {
+                bytes[0] = 0x1C;
+                bytes[1] = *reg;
+            }
+            Instruction::Spill(reg, slot) => {
+                bytes[0] = 0x1D;
+                bytes[1] = *reg;
+                bytes[2..2 + size_of::<u32>()].copy_from_slice(&slot.to_be_bytes());
+            }
+            Instruction::Reload(slot, reg) => {
+                bytes[0] = 0x1E;
+                bytes[1] = *reg;
+                bytes[2..2 + size_of::<u32>()].copy_from_slice(&slot.to_be_bytes());
+            }
+            Instruction::NewObject(reg, members) => {
+                bytes[0] = 0x21;
+                bytes[1] = *reg;
+                bytes[2..2 + size_of::<u32>()].copy_from_slice(&members.to_be_bytes());
+            }
+            Instruction::GetMember(output, object, member) => {
+                bytes[0] = 0x22;
+                bytes[1] = *output;
+                bytes[2] = *object;
+                bytes[3..3 + size_of::<u32>()].copy_from_slice(&member.to_be_bytes());
+            }
+            Instruction::SetMember(object, value, member) => {
+                bytes[0] = 0x23;
+                bytes[1] = *object;
+                bytes[2] = *value;
+                bytes[3..3 + size_of::<u32>()].copy_from_slice(&member.to_be_bytes());
+            }
 
             Instruction::Add(left, right) => {
                 bytes[0] = 0x07;
@@ -151,6 +195,46 @@ impl Encoder {
                 bytes[1] = *left;
                 bytes[2] = *right;
             }
+            Instruction::FallibleAdd(left, right) => {
+                bytes[0] = 0x24;
+                bytes[1] = *left;
+                bytes[2] = *right;
+            }
+            Instruction::FallibleSub(left, right) => {
+                bytes[0] = 0x25;
+                bytes[1] = *left;
+                bytes[2] = *right;
+            }
+            Instruction::FallibleMult(left, right) => {
+                bytes[0] = 0x26;
+                bytes[1] = *left;
+                bytes[2] = *right;
+            }
+            Instruction::FallibleDiv(left, right) => {
+                bytes[0] = 0x27;
+                bytes[1] = *left;
+                bytes[2] = *right;
+            }
+            Instruction::PanickingAdd(left, right) => {
+                bytes[0] = 0x28;
+                bytes[1] = *left;
+                bytes[2] = *right;
+            }
+            Instruction::PanickingSub(left, right) => {
+                bytes[0] = 0x29;
+                bytes[1] = *left;
+                bytes[2] = *right;
+            }
+            Instruction::PanickingMult(left, right) => {
+                bytes[0] = 0x2A;
+                bytes[1] = *left;
+                bytes[2] = *right;
+            }
+            Instruction::PanickingDiv(left, right) => {
+                bytes[0] = 0x2B;
+                bytes[1] = *left;
+                bytes[2] = *right;
+            }
 
             Instruction::Print(reg) => {
                 bytes[0] = 0x0B;
@@ -206,6 +290,16 @@ impl Encoder {
                 bytes[1] = *left;
                 bytes[2] = *right;
             }
+            Instruction::GreaterEq(left, right) => {
+                bytes[0] = 0x1F;
+                bytes[1] = *left;
+                bytes[2] = *right;
+            }
+            Instruction::LessEq(left, right) => {
+                bytes[0] = 0x20;
+                bytes[1] = *left;
+                bytes[2] = *right;
+            }
 
             Instruction::Collect => {
                 bytes[0] = 0x19;
@@ -216,18 +310,23 @@ impl Encoder {
             Instruction::Halt => {
                 bytes[0] = 0x17;
             }
+            Instruction::Func(func) => {
+                bytes[0] = 0x18;
+                bytes[1..=size_of::<u32>()].copy_from_slice(&func.to_be_bytes());
+            }
+            Instruction::Yield => {
+                bytes[0] = 0x1A;
+            }
 
-            Instruction::Illegal | Instruction::JumpPoint(_) => {
+            Instruction::Illegal => {
                 // TODO: Should this be allowed? What should an illegal instruction be legally encoded as?
                 error!("Tried to encode {:?}", instruction);
                 panic!(
                     "I mean, why are you *purposefully* making an Illegal Instruction? Just... Why?"
                 );
             }
-
-            _ => todo!("Implement procedural encoding/decoding"),
         }
 
-        (bytes, value)
+        bytes
     }
 }
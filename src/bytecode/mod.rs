@@ -1,11 +1,18 @@
 mod decode;
 mod encode;
+mod verify;
 
 pub use decode::Decoder;
 pub use encode::Encoder;
+pub use verify::{verify, Location, Section, VerifyError};
 
 // Bytecode Format
 //
+// ====   Header Section    ====
+//
+// Magic Number: [u8; 4] = MAGIC_NUMBER
+// Version: u16 = BYTECODE_VERSION
+//
 // ====    Meta Section     ====
 //
 // >    Number of Functions    <
@@ -20,7 +27,12 @@ pub use encode::Encoder;
 //
 // >       Encoded Values      <
 // Number of Values: u32
-// Values: [u8; VALUE_LENGTH] * Number of Values
+// Values: [
+//      Value Length: u32,
+//      Value Bytes: [u8; Value Length],
+// ] * Number of Values
+// Note: The contents of string values are stored in order in the strings section, and
+//       `Load` instructions refer to values by their index
 //
 // ==== Instruction Section ====
 //
@@ -31,178 +43,33 @@ pub use encode::Encoder;
 //      Instructions: [u8; INSTRUCTION_LENGTH] * Number of Instructions
 // ] * Number of Functions
 
+/// The bytes that every encoded program starts with
+pub const MAGIC_NUMBER: [u8; 4] = *b"CRNC";
+
+/// The version of the bytecode format, bumped whenever the format changes
+pub const BYTECODE_VERSION: u16 = 3;
+
 /// The length of an encoded instruction, in bytes
 pub const INSTRUCTION_LENGTH: usize = 8;
 
 /// An array containing all Instruction byte headers, for verification purposes
 #[rustfmt::skip]
-pub const INSTRUCTION_BYTES: [u8; 27] = [
-    0x00, 0x01, 0x02, 0x03, 0x04,
-    0x05, 0x06, 0x07, 0x08, 0x09,
-    0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
-    0x0F, 0x10, 0x11, 0x12, 0x13,
-    0x14, 0x15, 0x16, 0x17, 0x18,
-    0x19, 0x1A,
+pub const INSTRUCTION_BYTES: [u8; 43] = [
+    0x00, 0x01, 0x03, 0x04, 0x05,
+    0x06, 0x07, 0x08, 0x09, 0x0A,
+    0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
+    0x10, 0x11, 0x12, 0x13, 0x14,
+    0x15, 0x16, 0x17, 0x18, 0x19,
+    0x1A, 0x1B, 0x1C, 0x1D, 0x1E,
+    0x1F, 0x20, 0x21, 0x22, 0x23,
+    0x24, 0x25, 0x26, 0x27, 0x28,
+    0x29, 0x2A, 0x2B,
 ];
 
-/// Disassembles bytecode into a human-readable format
-// TODO: Refractor and test
-#[must_use]
-pub fn disassemble(_bytes: &[u8]) -> String {
-    /*
-    use super::{Instruction, RuntimeValue, NUMBER_REGISTERS};
-    use std::{collections::HashMap, fmt::Write};
-
-    let functions = {
-        let (main, functions) = Decoder::new(bytes).decode().unwrap();
-        let mut funcs = vec![main];
-        funcs.extend_from_slice(&functions);
-        funcs
-    };
-
-    let mut output = String::new();
-    for (index, function) in functions.into_iter().enumerate() {
-        if index == 0 {
-            write!(&mut output, "=> Main Function\n").unwrap();
-        } else {
-            write!(&mut output, "=> Function {}\n", index).unwrap();
-        }
-
-        let mut registers = [RuntimeValue::None; NUMBER_REGISTERS];
-        let mut heap: HashMap<u32, RuntimeValue> = HashMap::new();
-
-        for (instruction_index, instruction) in function.into_iter().enumerate() {
-            match &instruction {
-                Instruction::Load(val, reg) => {
-                    registers[**reg as usize] = val.clone();
-                }
-                Instruction::Cache(heap_loc, val, reg) => {
-                    heap.insert(*heap_loc, val.clone());
-                    registers[**reg as usize] = val.clone();
-                }
-                Instruction::Drop(heap_loc) => {
-                    heap.remove(&heap_loc);
-                }
-                Instruction::DropReg(reg) => {
-                    registers[**reg as usize] = RuntimeValue::None;
-                }
-                Instruction::Save(heap_loc, reg) => {
-                    heap.insert(*heap_loc, registers[**reg as usize].clone());
-                    registers[**reg as usize] = RuntimeValue::None;
-                }
-                _ => {}
-            }
-
-            let param = {
-                use super::Instruction::*;
-
-                match &instruction {
-                    Load(val, reg) => format!("{}, {:?}", reg, val),
-                    Save(heap, reg) => format!("{:p}, {}", *heap as *const u8, reg),
-                    Cache(heap, ref val, reg) => {
-                        format!("{:p}, {}, {}", *heap as *const u8, val, reg)
-                    }
-                    CompToReg(reg) | OpToReg(reg) | DropReg(reg) => format!("{}", reg),
-                    Drop(reg) => format!("{}", reg),
-
-                    Print(reg) => {
-                        if registers[**reg as usize] != RuntimeValue::None {
-                            format!("{}: {}", reg, registers[**reg as usize].to_string())
-                        } else {
-                            format!("{}", reg)
-                        }
-                    }
-
-                    Jump(abs) | JumpComp(abs) => format!("{:04}", abs),
-
-                    Not(reg) => format!(
-                        "{}",
-                        if registers[**reg as usize] != RuntimeValue::None {
-                            format!("{}: {}", reg, registers[**reg as usize].to_string())
-                        } else {
-                            format!("{}", reg)
-                        }
-                    ),
-
-                    Add(left, right)
-                    | Sub(left, right)
-                    | Mult(left, right)
-                    | Div(left, right)
-                    | And(left, right)
-                    | Or(left, right)
-                    | Xor(left, right)
-                    | Eq(left, right)
-                    | NotEq(left, right)
-                    | GreaterThan(left, right)
-                    | LessThan(left, right) => format!(
-                        "{}, {}",
-                        if registers[**left as usize] != RuntimeValue::None {
-                            format!("{}: {:?}", left, registers[**left as usize].to_string())
-                        } else {
-                            format!("{}", left)
-                        },
-                        if registers[**right as usize] != RuntimeValue::None {
-                            format!("{}: {:?}", right, registers[**right as usize].to_string())
-                        } else {
-                            format!("{}", right)
-                        }
-                    ),
-
-                    Syscall(offset, output, param1, param2, param3, param4, param5) => format!(
-                        "0x{:X} ({}, {}, {}, {}, {}) -> {}",
-                        crate::syscall::SYSCALL_TABLE[*offset as usize],
-                        if registers[**param1 as usize] != RuntimeValue::None {
-                            format!("{}: {:?}", param1, registers[**param1 as usize].to_string())
-                        } else {
-                            format!("{}", param1)
-                        },
-                        if registers[**param2 as usize] != RuntimeValue::None {
-                            format!("{}: {:?}", param2, registers[**param2 as usize].to_string())
-                        } else {
-                            format!("{}", param2)
-                        },
-                        if registers[**param3 as usize] != RuntimeValue::None {
-                            format!("{}: {:?}", param3, registers[**param3 as usize].to_string())
-                        } else {
-                            format!("{}", param3)
-                        },
-                        if registers[**param4 as usize] != RuntimeValue::None {
-                            format!("{}: {:?}", param4, registers[**param4 as usize].to_string())
-                        } else {
-                            format!("{}", param4)
-                        },
-                        if registers[**param5 as usize] != RuntimeValue::None {
-                            format!("{}: {:?}", param5, registers[**param5 as usize].to_string())
-                        } else {
-                            format!("{}", param5)
-                        },
-                        output,
-                    ),
-
-                    Return | Halt | Illegal | Collect | JumpPoint(_) | NoOp => String::new(),
-                }
-            };
-
-            write!(
-                &mut output,
-                "  {:04}: {} {}\n",
-                instruction_index,
-                instruction.to_str(),
-                param,
-            )
-            .unwrap();
-        }
-    }
-
-    output
-    */
-
-    "".to_string()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::disassemble;
 
     #[test]
     fn byte_test() {
@@ -233,14 +100,15 @@ mod tests {
             func.insert(0, instructions.clone());
             func
         })
-        .encode();
+        .encode()
+        .unwrap();
         let (main, mut funcs) = Decoder::new(&encoded_program).decode().unwrap();
         funcs.insert(0, main);
 
         let mut file = std::fs::File::create("./examples/hello_world.crunched").unwrap();
         file.write_all(&encoded_program).unwrap();
 
-        println!("{}", disassemble(&encoded_program));
+        println!("{}", disassemble(&encoded_program).unwrap());
 
         assert_eq!(
             {
@@ -253,4 +121,263 @@ mod tests {
         let mut crunch = crate::Crunch::new(crate::OptionBuilder::new("./byte_test").build());
         crunch.execute(funcs).unwrap();
     }
+
+    #[test]
+    fn heap_values_are_not_encoded() {
+        use crate::{Heap, Instruction, RuntimeErrorTy, RuntimeValue};
+
+        let encoded = Encoder::new(vec![vec![Instruction::Load(
+            RuntimeValue::GcInt(Heap::new(0.into(), 0)),
+            0.into(),
+        )]])
+        .encode();
+
+        assert_eq!(encoded.unwrap_err().ty, RuntimeErrorTy::BytecodeError);
+    }
+
+    #[test]
+    fn verify_malformed_bytecode() {
+        use crate::{Instruction::*, RuntimeValue};
+
+        fn encode(functions: Vec<Vec<crate::Instruction>>) -> Vec<u8> {
+            Encoder::new(functions).encode().unwrap()
+        }
+
+        let valid = encode(vec![
+            vec![Load(RuntimeValue::I32(10), 0.into()), Func(1), Halt],
+            vec![Print(0.into()), Return],
+        ]);
+        assert_eq!(verify(&valid), Ok(()));
+
+        let mut invalid = valid.clone();
+        invalid[0] = 0x00;
+        assert_eq!(verify(&invalid), Err(VerifyError::InvalidMagicNumber));
+
+        let mut invalid = valid.clone();
+        invalid[MAGIC_NUMBER.len()..MAGIC_NUMBER.len() + 2]
+            .copy_from_slice(&(BYTECODE_VERSION + 1).to_be_bytes());
+        assert_eq!(
+            verify(&invalid),
+            Err(VerifyError::UnsupportedVersion(BYTECODE_VERSION + 1))
+        );
+
+        assert_eq!(
+            verify(&valid[..valid.len() - 1]),
+            Err(VerifyError::UnexpectedEnd {
+                section: Section::Functions,
+                offset: valid.len() - 1,
+            })
+        );
+
+        let mut invalid = valid.clone();
+        invalid.push(0x00);
+        assert_eq!(
+            verify(&invalid),
+            Err(VerifyError::TrailingBytes {
+                offset: valid.len()
+            })
+        );
+
+        // The opcode of the last instruction in the last function
+        let mut invalid = valid.clone();
+        let last = valid.len() - INSTRUCTION_LENGTH;
+        invalid[last] = 0x02;
+        assert_eq!(
+            verify(&invalid),
+            Err(VerifyError::InvalidOpcode {
+                location: Location {
+                    function: 1,
+                    instruction: 1,
+                },
+                opcode: 0x02,
+            })
+        );
+
+        assert_eq!(verify(&encode(Vec::new())), Err(VerifyError::MissingMain));
+        assert_eq!(
+            verify(&encode(vec![vec![Halt], vec![NoOp]])),
+            Err(VerifyError::MissingTerminator { function: 1 })
+        );
+        assert_eq!(
+            verify(&encode(vec![vec![Print(32.into()), Halt]])),
+            Err(VerifyError::InvalidRegister {
+                location: Location {
+                    function: 0,
+                    instruction: 0,
+                },
+                register: 32,
+            })
+        );
+        assert_eq!(
+            verify(&encode(vec![vec![JumpComp(1), Halt]])),
+            Err(VerifyError::InvalidJump {
+                location: Location {
+                    function: 0,
+                    instruction: 0,
+                },
+                target: 2,
+            })
+        );
+        assert_eq!(
+            verify(&encode(vec![vec![NoOp, Jump(-3)]])),
+            Err(VerifyError::InvalidJump {
+                location: Location {
+                    function: 0,
+                    instruction: 1,
+                },
+                target: -1,
+            })
+        );
+        assert_eq!(
+            verify(&encode(vec![vec![Func(1), Halt]])),
+            Err(VerifyError::InvalidFunction {
+                location: Location {
+                    function: 0,
+                    instruction: 0,
+                },
+                function: 1,
+            })
+        );
+        assert_eq!(
+            verify(&encode(vec![vec![
+                Reload(u32::max_value(), 0.into()),
+                Halt
+            ]])),
+            Err(VerifyError::InvalidSlot {
+                location: Location {
+                    function: 0,
+                    instruction: 0,
+                },
+                slot: u32::max_value(),
+            })
+        );
+        assert_eq!(
+            verify(&encode(vec![vec![
+                NewObject(0.into(), u32::max_value()),
+                Halt
+            ]])),
+            Err(VerifyError::TooManyMembers {
+                location: Location {
+                    function: 0,
+                    instruction: 0,
+                },
+                members: u32::max_value(),
+            })
+        );
+        assert_eq!(
+            verify(&encode(vec![vec![Yield, Halt]])),
+            Err(VerifyError::UnsupportedInstruction {
+                location: Location {
+                    function: 0,
+                    instruction: 0,
+                },
+                instruction: "yield",
+            })
+        );
+    }
+
+    mod property_tests {
+        use super::super::*;
+        use crate::{AllocId, Instruction, RuntimeValue, NUMBER_REGISTERS};
+        use proptest::prelude::*;
+        use std::{collections::VecDeque, mem::size_of};
+
+        fn value() -> impl Strategy<Value = RuntimeValue> {
+            prop_oneof![
+                Just(RuntimeValue::None),
+                Just(RuntimeValue::Null),
+                any::<u8>().prop_map(RuntimeValue::Byte),
+                any::<u16>().prop_map(RuntimeValue::U16),
+                any::<u32>().prop_map(RuntimeValue::U32),
+                any::<u64>().prop_map(RuntimeValue::U64),
+                any::<u128>().prop_map(RuntimeValue::U128),
+                any::<i8>().prop_map(RuntimeValue::IByte),
+                any::<i16>().prop_map(RuntimeValue::I16),
+                any::<i32>().prop_map(RuntimeValue::I32),
+                any::<i64>().prop_map(RuntimeValue::I64),
+                any::<i128>().prop_map(RuntimeValue::I128),
+                any::<f32>().prop_map(RuntimeValue::F32),
+                any::<f64>().prop_map(RuntimeValue::F64),
+                any::<usize>().prop_map(|ptr| RuntimeValue::Pointer(AllocId(ptr))),
+                any::<bool>().prop_map(RuntimeValue::Bool),
+                any::<char>().prop_map(RuntimeValue::Char),
+                any::<String>()
+                    .prop_map(|string| RuntimeValue::Str(Box::leak(string.into_boxed_str()))),
+            ]
+        }
+
+        proptest! {
+            #[test]
+            fn value_round_trip(value in value()) {
+                let (bytes, string) = value.as_bytes().unwrap();
+                let mut strings = string.into_iter().collect::<VecDeque<_>>();
+
+                // Skip the length of the value
+                let decoded = RuntimeValue::from_bytes(&bytes[size_of::<u32>()..], &mut strings).unwrap();
+
+                // Compare the encoded values, since that also compares floats bit for bit
+                prop_assert_eq!(value.as_bytes().unwrap(), decoded.as_bytes().unwrap());
+                prop_assert!(strings.is_empty());
+            }
+
+            #[test]
+            fn program_round_trip(functions in prop::collection::vec(prop::collection::vec(value(), 0..20), 1..5)) {
+                let functions = functions
+                    .into_iter()
+                    .map(|values| {
+                        values
+                            .into_iter()
+                            .enumerate()
+                            .map(|(reg, value)| Instruction::Load(value, ((reg % NUMBER_REGISTERS) as u8).into()))
+                            .chain(std::iter::once(Instruction::Return))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+
+                let encoded = Encoder::new(functions.clone()).encode().unwrap();
+                prop_assert_eq!(verify(&encoded), Ok(()));
+
+                let (main, mut decoded) = Decoder::new(&encoded).decode().unwrap();
+                decoded.insert(0, main);
+
+                prop_assert_eq!(&functions, &decoded);
+                for (function, decoded) in functions.iter().zip(decoded.iter()) {
+                    for (instruction, decoded) in function.iter().zip(decoded.iter()) {
+                        if let (Instruction::Load(value, _), Instruction::Load(decoded, _)) = (instruction, decoded) {
+                            prop_assert_eq!(value.as_bytes().unwrap(), decoded.as_bytes().unwrap());
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn truncated_bytecode_is_rejected(values in prop::collection::vec(value(), 0..20), len in any::<prop::sample::Index>()) {
+                let function = values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(reg, value)| Instruction::Load(value, ((reg % NUMBER_REGISTERS) as u8).into()))
+                    .chain(std::iter::once(Instruction::Return))
+                    .collect::<Vec<_>>();
+
+                let encoded = Encoder::new(vec![function]).encode().unwrap();
+                let truncated = &encoded[..len.index(encoded.len())];
+
+                prop_assert!(verify(truncated).is_err());
+                prop_assert!(Decoder::new(truncated).decode().is_err());
+            }
+
+            #[test]
+            fn arbitrary_bytes_do_not_panic(mut bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+                let _ = verify(&bytes);
+
+                // Also make sure that bytes with a valid header make it past the header checks
+                let mut header = MAGIC_NUMBER.to_vec();
+                header.extend_from_slice(&BYTECODE_VERSION.to_be_bytes());
+                header.append(&mut bytes);
+
+                let _ = verify(&header);
+                let _ = Decoder::new(&header).decode();
+            }
+        }
+    }
 }
@@ -0,0 +1,258 @@
+use super::Decoder;
+use crate::{
+    Instruction, RuntimeError, RuntimeErrorTy, MAX_OBJECT_MEMBERS, MAX_SPILL_SLOTS,
+    NUMBER_REGISTERS,
+};
+use std::fmt;
+
+/// A section of encoded bytecode
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Section {
+    /// The magic number and version
+    Header,
+    /// The number of functions, strings and values
+    Meta,
+    /// The encoded strings
+    Strings,
+    /// The encoded values
+    Values,
+    /// The encoded functions and their instructions
+    Functions,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let section = match self {
+            Self::Header => "header",
+            Self::Meta => "meta",
+            Self::Strings => "strings",
+            Self::Values => "values",
+            Self::Functions => "functions",
+        };
+
+        write!(f, "{}", section)
+    }
+}
+
+/// The location of an instruction within bytecode
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// The index of the function, where the main function is `0`
+    pub function: usize,
+    /// The index of the instruction within the function
+    pub instruction: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "instruction {} of function {}",
+            self.instruction, self.function
+        )
+    }
+}
+
+/// An error found while verifying bytecode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The bytecode doesn't start with [`MAGIC_NUMBER`](super::MAGIC_NUMBER)
+    InvalidMagicNumber,
+    /// The bytecode was encoded with a version other than [`BYTECODE_VERSION`](super::BYTECODE_VERSION)
+    UnsupportedVersion(u16),
+    /// The bytecode ended in the middle of a section
+    UnexpectedEnd { section: Section, offset: usize },
+    /// Bytes were left over after the last function
+    TrailingBytes { offset: usize },
+    /// An encoded string isn't valid utf-8
+    InvalidString { index: usize },
+    /// An encoded value couldn't be decoded
+    InvalidValue { index: usize, message: String },
+    /// There are no functions, so there is no main function
+    MissingMain,
+    /// A function doesn't end with a `Return`, `Halt` or `Jump`, so execution could run off of it
+    MissingTerminator { function: usize },
+    /// An opcode isn't one of [`INSTRUCTION_BYTES`](super::INSTRUCTION_BYTES)
+    InvalidOpcode { location: Location, opcode: u8 },
+    /// A register is outside of the VM's registers
+    InvalidRegister { location: Location, register: u8 },
+    /// A jump lands outside of its function
+    InvalidJump { location: Location, target: i64 },
+    /// A function call refers to a function that doesn't exist
+    InvalidFunction { location: Location, function: u32 },
+    /// A `Load` refers to a value that doesn't exist
+    InvalidValueIndex { location: Location, value: u32 },
+    /// A `Spill` or `Reload` uses a stack slot past [`MAX_SPILL_SLOTS`]
+    InvalidSlot { location: Location, slot: u32 },
+    /// A `NewObject` has more than [`MAX_OBJECT_MEMBERS`] members
+    TooManyMembers { location: Location, members: u32 },
+    /// An instruction that the VM can't execute yet
+    UnsupportedInstruction {
+        location: Location,
+        instruction: &'static str,
+    },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagicNumber => write!(f, "The bytecode has an invalid magic number"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "The bytecode version {} is not supported", version)
+            }
+            Self::UnexpectedEnd { section, offset } => write!(
+                f,
+                "The bytecode ended unexpectedly in the {} section at byte {}",
+                section, offset
+            ),
+            Self::TrailingBytes { offset } => write!(
+                f,
+                "The bytecode has trailing bytes after the last function at byte {}",
+                offset
+            ),
+            Self::InvalidString { index } => write!(f, "The string {} is not valid utf-8", index),
+            Self::InvalidValue { index, message } => {
+                write!(f, "The value {} is invalid: {}", index, message)
+            }
+            Self::MissingMain => write!(f, "The bytecode contains no main function"),
+            Self::MissingTerminator { function } => write!(
+                f,
+                "Function {} does not end with a return, halt or jump",
+                function
+            ),
+            Self::InvalidOpcode { location, opcode } => {
+                write!(f, "Invalid opcode 0x{:02X} at {}", opcode, location)
+            }
+            Self::InvalidRegister { location, register } => write!(
+                f,
+                "Invalid register {} at {}, there are only {} registers",
+                register, location, NUMBER_REGISTERS
+            ),
+            Self::InvalidJump { location, target } => write!(
+                f,
+                "The jump at {} lands outside of its function at instruction {}",
+                location, target
+            ),
+            Self::InvalidFunction { location, function } => write!(
+                f,
+                "The call at {} refers to the nonexistent function {}",
+                location, function
+            ),
+            Self::InvalidValueIndex { location, value } => write!(
+                f,
+                "The load at {} refers to the nonexistent value {}",
+                location, value
+            ),
+            Self::InvalidSlot { location, slot } => write!(
+                f,
+                "Invalid stack slot {} at {}, there are only {} stack slots",
+                slot, location, MAX_SPILL_SLOTS
+            ),
+            Self::TooManyMembers { location, members } => write!(
+                f,
+                "The object allocated at {} has {} members, objects can only have {}",
+                location, members, MAX_OBJECT_MEMBERS
+            ),
+            Self::UnsupportedInstruction {
+                location,
+                instruction,
+            } => write!(
+                f,
+                "The {} instruction at {} is not supported yet",
+                instruction, location
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl From<VerifyError> for RuntimeError {
+    fn from(err: VerifyError) -> Self {
+        Self {
+            ty: RuntimeErrorTy::BytecodeError,
+            message: err.to_string(),
+            backtrace: None,
+        }
+    }
+}
+
+/// Verifies that bytes are well-formed bytecode which can be safely decoded and executed
+pub fn verify(bytes: &[u8]) -> Result<(), VerifyError> {
+    let functions = Decoder::new(bytes).decode_functions()?;
+    verify_functions(&functions)
+}
+
+/// Verifies that decoded functions stay within their registers, functions and instructions
+pub(super) fn verify_functions(functions: &[Vec<Instruction>]) -> Result<(), VerifyError> {
+    if functions.is_empty() {
+        return Err(VerifyError::MissingMain);
+    }
+
+    for (function_index, function) in functions.iter().enumerate() {
+        match function.last() {
+            Some(Instruction::Return | Instruction::Halt | Instruction::Jump(_)) => {}
+            _ => {
+                return Err(VerifyError::MissingTerminator {
+                    function: function_index,
+                })
+            }
+        }
+
+        for (index, instruction) in function.iter().enumerate() {
+            let location = Location {
+                function: function_index,
+                instruction: index,
+            };
+
+            for register in instruction.registers() {
+                if *register as usize >= NUMBER_REGISTERS {
+                    return Err(VerifyError::InvalidRegister {
+                        location,
+                        register: *register,
+                    });
+                }
+            }
+
+            match *instruction {
+                Instruction::Jump(offset) | Instruction::JumpComp(offset) => {
+                    // Jumps land on the instruction after their target
+                    let target = index as i64 + i64::from(offset) + 1;
+
+                    if target < 0 || target >= function.len() as i64 {
+                        return Err(VerifyError::InvalidJump { location, target });
+                    }
+                }
+
+                Instruction::Func(func) if func as usize >= functions.len() => {
+                    return Err(VerifyError::InvalidFunction {
+                        location,
+                        function: func,
+                    });
+                }
+
+                Instruction::Spill(_, slot) | Instruction::Reload(slot, _)
+                    if slot as usize >= MAX_SPILL_SLOTS =>
+                {
+                    return Err(VerifyError::InvalidSlot { location, slot });
+                }
+
+                Instruction::NewObject(_, members) if members as usize > MAX_OBJECT_MEMBERS => {
+                    return Err(VerifyError::TooManyMembers { location, members });
+                }
+
+                // Generators aren't implemented, so executing a yield would panic the VM
+                Instruction::Yield => {
+                    return Err(VerifyError::UnsupportedInstruction {
+                        location,
+                        instruction: instruction.to_str(),
+                    });
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
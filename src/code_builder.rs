@@ -1,8 +1,10 @@
 #![allow(dead_code)]
 
 use crate::{
-    Instruction, Register, Result, RuntimeError, RuntimeErrorTy, RuntimeValue, NUMBER_REGISTERS,
+    allocator, DebugInfo, FunctionInfo, Instruction, Register, Result, RuntimeError,
+    RuntimeErrorTy, RuntimeValue, NUMBER_CALLER_REGISTERS,
 };
+use codespan::Span;
 use rand::{
     distributions::{Alphanumeric, Distribution, Standard},
     rngs::SmallRng,
@@ -38,6 +40,8 @@ pub struct Namespace {
 #[derive(Debug, Clone)]
 pub struct CodeBuilder {
     functions: HashMap<Sym, (FunctionContext, Option<u32>)>,
+    /// Every function, in the order they were declared
+    declared: Vec<Sym>,
     pub interner: StringInterner<Sym>,
     gc_ids: HashSet<u32>,
     local_symbols: HashMap<Sym, u32>,
@@ -45,12 +49,20 @@ pub struct CodeBuilder {
     func_index: u32,
     last_jump_id: u32,
     current_namespace: Option<Sym>,
+    /// Every declared type
+    types: HashMap<Sym, TypeContext>,
+    /// The mangled names of the instances of generic functions, keyed by the generic function and
+    /// the concrete types of its generics
+    instances: HashMap<(Sym, Vec<crate::parser::Type>), Sym>,
+    /// The readable names of functions that have mangled names
+    names: HashMap<Sym, String>,
 }
 
 impl CodeBuilder {
     pub fn new() -> Self {
         Self {
             functions: HashMap::new(),
+            declared: Vec::new(),
             interner: StringInterner::new(),
             gc_ids: HashSet::new(),
             local_symbols: HashMap::new(),
@@ -58,12 +70,16 @@ impl CodeBuilder {
             func_index: 1,
             last_jump_id: 0,
             current_namespace: None,
+            types: HashMap::new(),
+            instances: HashMap::new(),
+            names: HashMap::new(),
         }
     }
 
     pub fn from_interner(interner: StringInterner<Sym>) -> Self {
         Self {
             functions: HashMap::new(),
+            declared: Vec::new(),
             interner,
             gc_ids: HashSet::new(),
             local_symbols: HashMap::new(),
@@ -71,6 +87,9 @@ impl CodeBuilder {
             func_index: 1,
             last_jump_id: 0,
             current_namespace: None,
+            types: HashMap::new(),
+            instances: HashMap::new(),
+            names: HashMap::new(),
         }
     }
 
@@ -82,11 +101,90 @@ impl CodeBuilder {
 
         (function)(self, &mut context)?;
 
-        self.functions.insert(name, (context, None));
+        if self.functions.insert(name, (context, None)).is_none() {
+            self.declared.push(name);
+        }
 
         Ok(())
     }
 
+    /// Declare a type, making its members and methods available to every function
+    #[inline]
+    pub fn declare_type(&mut self, ty: TypeContext) {
+        self.types.insert(ty.name, ty);
+    }
+
+    /// Get the mangled name of the instance of a generic function for a set of concrete types,
+    /// creating a new one if the function hasn't been instantiated with them yet
+    pub fn instance(&mut self, name: Sym, generics: &[crate::parser::Type]) -> Sym {
+        let key = (name, generics.to_vec());
+        if let Some(&instance) = self.instances.get(&key) {
+            return instance;
+        }
+
+        let mut path = vec![self
+            .interner
+            .resolve(name)
+            .unwrap_or("<unknown>")
+            .to_string()];
+        path.extend(generics.iter().map(|ty| ty.name(&self.interner)));
+
+        let mangled = self.mangle(
+            &path.iter().map(String::as_str).collect::<Vec<_>>(),
+            MangleStatus::Function,
+        );
+        let instance = self.intern(mangled);
+        self.instances.insert(key, instance);
+        self.name_function(instance, format!("{}<{}>", path[0], path[1..].join(", ")));
+
+        instance
+    }
+
+    /// Get the mangled name of an existing instance of a generic function
+    pub fn get_instance(&self, name: Sym, generics: &[crate::parser::Type]) -> Result<Sym> {
+        if let Some(&instance) = self.instances.get(&(name, generics.to_vec())) {
+            Ok(instance)
+        } else {
+            error!("Attempted to call an instance of a generic function that doesn't exist");
+            Err(RuntimeError {
+                ty: RuntimeErrorTy::CompilationError,
+                message: format!(
+                    "The generic function `{}` has no instance for `<{}>`",
+                    self.interner.resolve(name).unwrap_or("<unknown>"),
+                    generics
+                        .iter()
+                        .map(|ty| ty.name(&self.interner))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+                backtrace: None,
+            })
+        }
+    }
+
+    /// Give a function with a mangled name the name it should be shown with
+    #[inline]
+    pub fn name_function(&mut self, function: Sym, name: String) {
+        self.names.insert(function, name);
+    }
+
+    /// Get a declared type
+    pub fn get_type(&self, name: Sym) -> Result<&TypeContext> {
+        if let Some(ty) = self.types.get(&name) {
+            Ok(ty)
+        } else {
+            error!("Attempted to use an undeclared type");
+            Err(RuntimeError {
+                ty: RuntimeErrorTy::CompilationError,
+                message: format!(
+                    "The type `{}` does not exist",
+                    self.interner.resolve(name).unwrap_or("<unknown>"),
+                ),
+                backtrace: None,
+            })
+        }
+    }
+
     #[inline]
     pub fn intern<T>(&mut self, string: T) -> Sym
     where
@@ -155,28 +253,69 @@ impl CodeBuilder {
         self.last_jump_id
     }
 
-    pub fn build(mut self) -> Result<Vec<Vec<Instruction>>> {
-        let mut functions = Vec::new();
+    /// Builds every function into the function table, along with the debug info of each function
+    pub fn build(mut self) -> Result<(Vec<Vec<Instruction>>, DebugInfo)> {
+        // The main function is always the first function
+        let main = self
+            .interner
+            .get("main")
+            .and_then(|main| self.functions.get_mut(&main));
+
+        if let Some((_func, index)) = main {
+            *index = Some(0);
+        } else {
+            error!("The program has no main function");
+            return Err(RuntimeError {
+                ty: RuntimeErrorTy::MissingMain,
+                message: "A `main` function must be declared".to_string(),
+                backtrace: None,
+            });
+        }
 
-        for (sym, (func, _index)) in self.functions.clone().into_iter() {
-            let mut func = func.build(&mut self)?;
+        // Calls are given their callee's index as they're built, so functions are built in the
+        // order they were declared to keep the output the same between builds
+        let mut functions = Vec::with_capacity(self.functions.len());
+        for sym in self.declared.clone() {
+            let func = self.functions[&sym].0.clone();
+            let (mut func, mut spans) = func.build(&mut self)?;
 
-            if func[func.len() - 1] != Instruction::Return {
+            if func.last() != Some(&Instruction::Return) {
                 func.push(Instruction::Return);
+                spans.push(None);
             }
 
-            if let Some(ident) = self.interner.resolve(sym) {
-                if ident == "main" {
-                    functions.insert(0, func);
-                } else {
-                    functions.push(func);
-                }
-            } else {
-                error!("Unresolved function name: {:?}", sym);
-            }
+            let name = self.names.get(&sym).cloned().unwrap_or_else(|| {
+                self.interner
+                    .resolve(sym)
+                    .unwrap_or("<unknown>")
+                    .to_string()
+            });
+            functions.push((sym, (func, FunctionInfo { name, spans })));
         }
 
-        Ok(functions)
+        // Function indices are handed out as the functions are called, so functions that are
+        // never called still need one
+        let (table, func_index) = (&mut self.functions, &mut self.func_index);
+        let mut functions = functions
+            .into_iter()
+            .map(|(sym, func)| {
+                let index = &mut table
+                    .get_mut(&sym)
+                    .expect("All built functions are in the function table")
+                    .1;
+
+                let index = *index.get_or_insert_with(|| {
+                    *func_index += 1;
+                    *func_index - 1
+                });
+
+                (index, func)
+            })
+            .collect::<Vec<_>>();
+        functions.sort_by_key(|(index, _func)| *index);
+
+        let (functions, info) = functions.into_iter().map(|(_index, func)| func).unzip();
+        Ok((functions, DebugInfo::new(info)))
     }
 }
 
@@ -202,18 +341,81 @@ pub enum Type {
     Custom(Sym),
 }
 
+/// A declared type, whose objects hold the values of its members
 #[derive(Debug, Clone)]
 pub struct TypeContext {
-    name: Sym,
-    members: HashMap<Sym, Type>,
-    methods: HashMap<Sym, (Visibility, FunctionContext)>,
+    pub name: Sym,
+    /// The members of the type, in the order that they're stored in its objects
+    pub members: Vec<(Sym, crate::parser::Type)>,
+    /// The mangled names of the type's methods and associated functions, along with whether they
+    /// take `self`
+    pub methods: HashMap<Sym, (Sym, bool)>,
+}
+
+impl TypeContext {
+    #[inline]
+    pub fn new(name: Sym) -> Self {
+        Self {
+            name,
+            members: Vec::new(),
+            methods: HashMap::new(),
+        }
+    }
+
+    /// Get the index and type of a member
+    pub fn member(&self, member: Sym) -> Option<(u32, &crate::parser::Type)> {
+        self.members
+            .iter()
+            .position(|(name, _ty)| *name == member)
+            .map(|index| (index as u32, &self.members[index].1))
+    }
+}
+
+/// A register that has yet to be allocated to a physical register or a stack slot
+///
+/// The first `NUMBER_CALLER_REGISTERS` virtual registers are the caller registers themselves
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VirtualRegister(pub u32);
+
+impl VirtualRegister {
+    /// Whether the virtual register is one of the caller registers
+    #[inline]
+    #[must_use]
+    pub fn is_caller(self) -> bool {
+        (self.0 as usize) < NUMBER_CALLER_REGISTERS
+    }
+}
+
+impl From<u8> for VirtualRegister {
+    fn from(register: u8) -> Self {
+        Self(register as u32)
+    }
+}
+
+/// The register that instructions hold until their virtual registers are allocated
+const UNALLOCATED: Register = Register(0);
+
+/// The jump targets of a loop that's currently being built
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LoopLabels {
+    /// The jump id that a `continue` jumps to
+    pub continue_id: u32,
+    /// The jump id that a `break` jumps to
+    pub break_id: u32,
 }
 
 #[derive(Debug, Clone)]
 pub struct FunctionContext {
-    registers: [Option<Option<Sym>>; NUMBER_REGISTERS],
+    /// The reserved virtual registers and the variables they hold, temporaries hold no variable
+    registers: HashMap<VirtualRegister, Option<Sym>>,
+    /// The next virtual register to be reserved
+    next_register: u32,
     variables: HashSet<Sym>,
     block: Vec<PartialInstruction>,
+    /// The labels of all loops currently being built, the innermost loop is last
+    loops: Vec<LoopLabels>,
+    /// The source that instructions are currently being built from
+    span: Option<Span>,
     pub scope: Scope,
 }
 
@@ -221,75 +423,83 @@ impl FunctionContext {
     #[inline]
     pub fn new() -> Self {
         Self {
-            registers: [None; NUMBER_REGISTERS],
+            registers: HashMap::new(),
+            next_register: NUMBER_CALLER_REGISTERS as u32,
             variables: HashSet::new(),
             block: Vec::new(),
+            loops: Vec::new(),
+            span: None,
             scope: Scope::new(),
         }
     }
 
     #[inline]
-    pub fn free_reg(&mut self, reg: impl Into<Register>) -> &mut Self {
+    pub fn free_reg(&mut self, reg: impl Into<VirtualRegister>) -> &mut Self {
         let reg = reg.into();
         trace!("Freeing register {}", reg.0);
 
-        self.registers[*reg as usize] = None;
+        self.registers.remove(&reg);
 
         self
     }
 
     #[inline]
-    pub fn reserve_caller_reg(&mut self, sym: impl Into<Option<Sym>>) -> Result<Register> {
-        match self.registers.iter().take(5).position(Option::is_none) {
-            Some(idx) => {
+    pub fn reserve_caller_reg(&mut self, sym: impl Into<Option<Sym>>) -> Result<VirtualRegister> {
+        match (0..NUMBER_CALLER_REGISTERS as u32)
+            .map(VirtualRegister)
+            .find(|reg| !self.registers.contains_key(reg))
+        {
+            Some(reg) => {
                 let sym = sym.into();
-                trace!("Reserving register {} for {:?}", idx, sym);
+                trace!("Reserving register {} for {:?}", reg.0, sym);
 
-                self.registers[idx] = Some(sym);
-                Ok((idx as u8).into())
+                self.registers.insert(reg, sym);
+                Ok(reg)
             }
             None => {
                 error!("Failed to find avaliable register");
                 Err(RuntimeError {
                     ty: RuntimeErrorTy::CompilationError,
                     message: "Failed to fetch available register".to_string(),
+                    backtrace: None,
                 })
             }
         }
     }
 
+    /// Reserve a new virtual register, which is given a callee-saved register or a stack slot once
+    /// the function is built
+    #[inline]
+    pub fn reserve_reg(&mut self, sym: impl Into<Option<Sym>>) -> VirtualRegister {
+        let (reg, sym) = (VirtualRegister(self.next_register), sym.into());
+        trace!("Reserving register {} for {:?}", reg.0, sym);
+
+        self.next_register += 1;
+        self.registers.insert(reg, sym);
+
+        reg
+    }
+
+    /// Whether the register holds a temporary value instead of a variable
     #[inline]
-    pub fn reserve_reg(&mut self, sym: impl Into<Option<Sym>>) -> Result<Register> {
+    pub fn is_temp(&self, reg: impl Into<VirtualRegister>) -> bool {
+        self.registers.get(&reg.into()) == Some(&None)
+    }
+
+    /// Get the register of a variable, the most recently declared variable wins if it was shadowed
+    pub fn get_cached_reg(&mut self, sym: Sym) -> Result<VirtualRegister> {
         match self
             .registers
             .iter()
-            .enumerate()
-            .rev()
-            .find(|(_idx, r)| r.is_none())
+            .filter(|(_reg, var)| **var == Some(sym))
+            .map(|(reg, _var)| *reg)
+            .max()
         {
-            Some((idx, _)) => {
-                let sym = sym.into();
-                trace!("Reserving register {} for {:?}", idx, sym);
-
-                self.registers[idx] = Some(sym);
-                Ok((idx as u8).into())
-            }
-            None => {
-                error!("Failed to find avaliable register");
-                Err(RuntimeError {
-                    ty: RuntimeErrorTy::CompilationError,
-                    message: "Failed to fetch available register".to_string(),
-                })
-            }
-        }
-    }
-
-    pub fn get_cached_reg(&mut self, sym: Sym) -> Result<Register> {
-        match self.registers.iter().position(|r| *r == Some(Some(sym))) {
-            Some(pos) => Ok((pos as u8).into()),
+            Some(reg) => Ok(reg),
             None => Err(RuntimeError {
                 ty: RuntimeErrorTy::CompilationError,
                 message: "Failed to fetch cached register".to_string(),
+                backtrace: None,
             }),
         }
     }
@@ -299,12 +509,57 @@ impl FunctionContext {
         self.variables.insert(sym);
     }
 
-    pub fn build(self, builder: &mut CodeBuilder) -> Result<Vec<Instruction>> {
+    /// Enter a loop, making its labels the target of any `break` or `continue`
+    #[inline]
+    pub fn push_loop(&mut self, continue_id: u32, break_id: u32) -> &mut Self {
+        self.loops.push(LoopLabels {
+            continue_id,
+            break_id,
+        });
+
+        self
+    }
+
+    /// Exit the innermost loop
+    #[inline]
+    pub fn pop_loop(&mut self) -> Option<LoopLabels> {
+        self.loops.pop()
+    }
+
+    /// Get the labels of the innermost loop
+    pub fn current_loop(&self) -> Result<LoopLabels> {
+        if let Some(labels) = self.loops.last() {
+            Ok(*labels)
+        } else {
+            error!("Attempted to break or continue outside of a loop");
+            Err(RuntimeError {
+                ty: RuntimeErrorTy::CompilationError,
+                message: "`break` and `continue` can only be used inside of a loop".to_string(),
+                backtrace: None,
+            })
+        }
+    }
+
+    /// Marks all instructions built after this as coming from `span`, returning the previous span
+    /// so that it can be restored once the source has been built
+    pub fn set_span(&mut self, span: impl Into<Option<Span>>) -> Option<Span> {
+        std::mem::replace(&mut self.span, span.into())
+    }
+
+    /// Builds the function's instructions, along with the span of each instruction
+    pub fn build(self, builder: &mut CodeBuilder) -> Result<(Vec<Instruction>, Vec<Option<Span>>)> {
         let mut instructions = Vec::with_capacity(self.block.len());
-        for inst in self.block {
-            instructions.push(inst.solidify(builder)?);
+        let mut spans = Vec::with_capacity(self.block.len());
+        for mut inst in self.block {
+            let registers = std::mem::take(&mut inst.registers);
+            spans.push(inst.span);
+            instructions.push((inst.solidify(builder)?, registers));
         }
 
+        // Registers have to be allocated before jumps are resolved, since spilling inserts instructions
+        let (mut instructions, origins) = allocator::allocate(instructions);
+        let spans = origins.into_iter().map(|origin| spans[origin]).collect();
+
         let mut jumps: HashMap<u32, u32> = HashMap::new(); // JumpId, JumpIndex
         for _ in 0..2 {
             for (index, instruction) in instructions.iter_mut().enumerate() {
@@ -323,168 +578,347 @@ impl FunctionContext {
             }
         }
 
-        Ok(instructions)
+        Ok((instructions, spans))
     }
 
-    pub fn inst_load(&mut self, register: impl Into<Register>, value: RuntimeValue) -> &mut Self {
-        self.block
-            .push(Instruction::Load(value, register.into()).into());
+    /// Add an instruction whose registers will be replaced by the given virtual registers once
+    /// they're allocated, in the order given by [`Instruction::registers_mut`]
+    fn push_inst(&mut self, instruction: Instruction, registers: &[VirtualRegister]) -> &mut Self {
+        self.block.push(PartialInstruction {
+            registers: registers.to_vec(),
+            span: self.span,
+            ..instruction.into()
+        });
 
         self
     }
+
+    pub fn inst_load(
+        &mut self,
+        register: impl Into<VirtualRegister>,
+        value: RuntimeValue,
+    ) -> &mut Self {
+        self.push_inst(Instruction::Load(value, UNALLOCATED), &[register.into()])
+    }
     pub fn inst_mov(
         &mut self,
-        target: impl Into<Register>,
-        source: impl Into<Register>,
+        target: impl Into<VirtualRegister>,
+        source: impl Into<VirtualRegister>,
     ) -> &mut Self {
-        self.block
-            .push(Instruction::Move(target.into(), source.into()).into());
-
-        self
+        self.push_inst(
+            Instruction::Move(UNALLOCATED, UNALLOCATED),
+            &[target.into(), source.into()],
+        )
     }
 
-    pub fn inst_comp_to_reg(&mut self, register: impl Into<Register>) -> &mut Self {
-        self.block
-            .push(Instruction::CompToReg(register.into()).into());
+    pub fn inst_push(&mut self, register: impl Into<VirtualRegister>) -> &mut Self {
+        self.push_inst(Instruction::Push(UNALLOCATED), &[register.into()])
+    }
+    pub fn inst_pop(&mut self, register: impl Into<VirtualRegister>) -> &mut Self {
+        self.push_inst(Instruction::Pop(UNALLOCATED), &[register.into()])
+    }
 
-        self
+    pub fn inst_new_object(
+        &mut self,
+        register: impl Into<VirtualRegister>,
+        members: u32,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::NewObject(UNALLOCATED, members),
+            &[register.into()],
+        )
+    }
+    pub fn inst_get_member(
+        &mut self,
+        output: impl Into<VirtualRegister>,
+        object: impl Into<VirtualRegister>,
+        member: u32,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::GetMember(UNALLOCATED, UNALLOCATED, member),
+            &[output.into(), object.into()],
+        )
+    }
+    pub fn inst_set_member(
+        &mut self,
+        object: impl Into<VirtualRegister>,
+        value: impl Into<VirtualRegister>,
+        member: u32,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::SetMember(UNALLOCATED, UNALLOCATED, member),
+            &[object.into(), value.into()],
+        )
     }
-    pub fn inst_op_to_reg(&mut self, register: impl Into<Register>) -> &mut Self {
-        self.block
-            .push(Instruction::OpToReg(register.into()).into());
 
-        self
+    pub fn inst_comp_to_reg(&mut self, register: impl Into<VirtualRegister>) -> &mut Self {
+        self.push_inst(Instruction::CompToReg(UNALLOCATED), &[register.into()])
+    }
+    pub fn inst_op_to_reg(&mut self, register: impl Into<VirtualRegister>) -> &mut Self {
+        self.push_inst(Instruction::OpToReg(UNALLOCATED), &[register.into()])
     }
 
-    pub fn inst_drop(&mut self, register: impl Into<Register>) -> &mut Self {
+    pub fn inst_drop(&mut self, register: impl Into<VirtualRegister>) -> &mut Self {
         let register = register.into();
 
-        self.block.push(Instruction::Drop(register).into());
+        self.push_inst(Instruction::Drop(UNALLOCATED), &[register]);
         self.free_reg(register);
 
         self
     }
 
-    pub fn inst_add(&mut self, left: impl Into<Register>, right: impl Into<Register>) -> &mut Self {
-        self.block
-            .push(Instruction::Add(left.into(), right.into()).into());
-
-        self
+    pub fn inst_add(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::Add(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
     }
-    pub fn inst_sub(&mut self, left: impl Into<Register>, right: impl Into<Register>) -> &mut Self {
-        self.block
-            .push(Instruction::Sub(left.into(), right.into()).into());
-
-        self
+    pub fn inst_sub(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::Sub(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
     }
     pub fn inst_mult(
         &mut self,
-        left: impl Into<Register>,
-        right: impl Into<Register>,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
     ) -> &mut Self {
-        self.block
-            .push(Instruction::Mult(left.into(), right.into()).into());
+        self.push_inst(
+            Instruction::Mult(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
+    }
+    pub fn inst_div(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::Div(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
+    }
 
-        self
+    pub fn inst_fallible_add(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::FallibleAdd(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
     }
-    pub fn inst_div(&mut self, left: impl Into<Register>, right: impl Into<Register>) -> &mut Self {
-        self.block
-            .push(Instruction::Div(left.into(), right.into()).into());
 
-        self
+    pub fn inst_fallible_sub(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::FallibleSub(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
     }
 
-    pub fn inst_print(&mut self, register: impl Into<Register>) -> &mut Self {
-        self.block.push(Instruction::Print(register.into()).into());
+    pub fn inst_fallible_mult(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::FallibleMult(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
+    }
 
-        self
+    pub fn inst_fallible_div(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::FallibleDiv(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
     }
 
-    pub fn inst_jump(&mut self, id: u32) -> &mut Self {
-        self.block.push(Instruction::Jump(id as i32).into());
+    pub fn inst_panicking_add(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::PanickingAdd(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
+    }
 
-        self
+    pub fn inst_panicking_sub(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::PanickingSub(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
     }
-    pub fn inst_jump_comp(&mut self, id: u32) -> &mut Self {
-        self.block.push(Instruction::JumpComp(id as i32).into());
 
-        self
+    pub fn inst_panicking_mult(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::PanickingMult(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
     }
 
-    pub fn inst_jump_point(&mut self, id: u32) -> &mut Self {
-        self.block.push(Instruction::JumpPoint(id).into());
+    pub fn inst_panicking_div(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::PanickingDiv(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
+    }
 
-        self
+    pub fn inst_print(&mut self, register: impl Into<VirtualRegister>) -> &mut Self {
+        self.push_inst(Instruction::Print(UNALLOCATED), &[register.into()])
     }
 
-    pub fn inst_and(&mut self, left: impl Into<Register>, right: impl Into<Register>) -> &mut Self {
-        self.block
-            .push(Instruction::And(left.into(), right.into()).into());
+    pub fn inst_jump(&mut self, id: u32) -> &mut Self {
+        self.push_inst(Instruction::Jump(id as i32), &[]);
 
         self
     }
-    pub fn inst_or(&mut self, left: impl Into<Register>, right: impl Into<Register>) -> &mut Self {
-        self.block
-            .push(Instruction::Or(left.into(), right.into()).into());
+    pub fn inst_jump_comp(&mut self, id: u32) -> &mut Self {
+        self.push_inst(Instruction::JumpComp(id as i32), &[]);
 
         self
     }
-    pub fn inst_xor(&mut self, left: impl Into<Register>, right: impl Into<Register>) -> &mut Self {
-        self.block
-            .push(Instruction::Xor(left.into(), right.into()).into());
 
-        self
-    }
-    pub fn inst_not(&mut self, register: impl Into<Register>) -> &mut Self {
-        self.block.push(Instruction::Not(register.into()).into());
+    pub fn inst_jump_point(&mut self, id: u32) -> &mut Self {
+        self.push_inst(Instruction::JumpPoint(id), &[]);
 
         self
     }
 
-    pub fn inst_eq(&mut self, left: impl Into<Register>, right: impl Into<Register>) -> &mut Self {
-        self.block
-            .push(Instruction::Eq(left.into(), right.into()).into());
+    pub fn inst_and(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::And(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
+    }
+    pub fn inst_or(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::Or(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
+    }
+    pub fn inst_xor(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::Xor(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
+    }
+    pub fn inst_not(&mut self, register: impl Into<VirtualRegister>) -> &mut Self {
+        self.push_inst(Instruction::Not(UNALLOCATED), &[register.into()])
+    }
 
-        self
+    pub fn inst_eq(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::Eq(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
     }
     pub fn inst_not_eq(
         &mut self,
-        left: impl Into<Register>,
-        right: impl Into<Register>,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
     ) -> &mut Self {
-        self.block
-            .push(Instruction::NotEq(left.into(), right.into()).into());
-
-        self
+        self.push_inst(
+            Instruction::NotEq(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
     }
     pub fn inst_greater_than(
         &mut self,
-        left: impl Into<Register>,
-        right: impl Into<Register>,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
     ) -> &mut Self {
-        self.block
-            .push(Instruction::GreaterThan(left.into(), right.into()).into());
-
-        self
+        self.push_inst(
+            Instruction::GreaterThan(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
     }
     pub fn inst_less_than(
         &mut self,
-        left: impl Into<Register>,
-        right: impl Into<Register>,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
     ) -> &mut Self {
-        self.block
-            .push(Instruction::LessThan(left.into(), right.into()).into());
-
-        self
+        self.push_inst(
+            Instruction::LessThan(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
+    }
+    pub fn inst_greater_eq(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::GreaterEq(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
+    }
+    pub fn inst_less_eq(
+        &mut self,
+        left: impl Into<VirtualRegister>,
+        right: impl Into<VirtualRegister>,
+    ) -> &mut Self {
+        self.push_inst(
+            Instruction::LessEq(UNALLOCATED, UNALLOCATED),
+            &[left.into(), right.into()],
+        )
     }
 
     pub fn inst_collect(&mut self) -> &mut Self {
-        self.block.push(Instruction::Collect.into());
+        self.push_inst(Instruction::Collect, &[]);
 
         self
     }
     pub fn inst_return(&mut self) -> &mut Self {
-        self.block.push(Instruction::Return.into());
+        self.push_inst(Instruction::Return, &[]);
 
         self
     }
@@ -494,22 +928,24 @@ impl FunctionContext {
             func_sym: Some(func_name),
             global_sym: None,
             local_sym: None,
+            registers: Vec::new(),
+            span: self.span,
         });
 
         self
     }
     pub fn inst_halt(&mut self) -> &mut Self {
-        self.block.push(Instruction::Halt.into());
+        self.push_inst(Instruction::Halt, &[]);
 
         self
     }
     pub fn inst_noop(&mut self) -> &mut Self {
-        self.block.push(Instruction::NoOp.into());
+        self.push_inst(Instruction::NoOp, &[]);
 
         self
     }
     pub fn inst_illegal(&mut self) -> &mut Self {
-        self.block.push(Instruction::Illegal.into());
+        self.push_inst(Instruction::Illegal, &[]);
 
         self
     }
@@ -521,6 +957,8 @@ struct PartialInstruction {
     func_sym: Option<Sym>,
     global_sym: Option<Sym>,
     local_sym: Option<Sym>,
+    registers: Vec<VirtualRegister>,
+    span: Option<Span>,
 }
 
 impl PartialInstruction {
@@ -539,6 +977,7 @@ impl PartialInstruction {
                         message:
                             "A malformed function instruction was encountered during compilation"
                                 .to_string(),
+                        backtrace: None,
                     });
                 };
 
@@ -587,6 +1026,8 @@ impl From<Instruction> for PartialInstruction {
             func_sym: None,
             global_sym: None,
             local_sym: None,
+            registers: Vec::new(),
+            span: None,
         }
     }
 }
@@ -631,7 +1072,7 @@ mod tests {
             })
             .unwrap();
 
-        let functions = builder.build().unwrap();
+        let (functions, _debug) = builder.build().unwrap();
 
         Crunch::new(
             OptionBuilder::new("./codebuilder_test")
@@ -1,7 +1,11 @@
 use super::{
-    disassemble, interpreter::Interpreter, Bytecode, Decoder, Instruction, Options, ReplOutput,
-    Result, RuntimeError, RuntimeErrorTy, Vm,
+    disassemble, disassemble_functions, interpreter::Interpreter, Bytecode, DebugInfo, Decoder,
+    Encoder, Instruction, Options, ReplOutput, Result, RuntimeError, RuntimeErrorTy, TypeChecker,
+    VerifyError, Vm,
 };
+use codespan::Files;
+use codespan_reporting::diagnostic::Diagnostic;
+use std::path::PathBuf;
 
 /// The main interface to the crunch language
 #[allow(missing_debug_implementations)]
@@ -38,79 +42,181 @@ impl Crunch {
     pub fn run_source_file(options: Options) {
         trace!("Running Source File: {}", options.file.display());
 
-        let source = {
-            use std::{fs::File, io::Read};
+        let source = match Self::read_source_file(&options) {
+            Some(source) => source,
+            None => return,
+        };
 
-            let mut buf = String::new();
+        if let Some((functions, debug)) = Self::compile(&options, &source) {
+            info!("Executing Crunch Program");
 
-            let mut file = match File::open(&options.file) {
-                Ok(file) => file,
-                Err(err) => {
-                    println!("Error Opening File: {:?}", err);
-                    return;
-                }
-            };
+            let file_name = Self::file_name(&options).unwrap_or("Crunch Source File");
+            Self::new(options.clone()).execute_source(functions, &debug, file_name, &source);
+        }
+    }
+
+    /// Execute a program compiled from `source`, pointing any runtime error at the source that
+    /// caused it
+    fn execute_source(
+        &mut self,
+        functions: Vec<Vec<Instruction>>,
+        debug: &DebugInfo,
+        file_name: &str,
+        source: &str,
+    ) {
+        if let Err(err) = self.execute(functions) {
+            let mut files = Files::new();
+            let file = files.add(file_name, source);
+
+            match err.diagnostic(debug, &files, file) {
+                Some(diagnostic) => Self::emit_file_diagnostics(&files, &[diagnostic]),
+                None => err.emit(),
+            }
+        }
+    }
+
+    /// Build a source file in the `.crunch` format into bytecode in the `.crunched` format,
+    /// writing it to `output` or next to the source file if no output is given
+    #[inline]
+    pub fn build_source_file(options: Options, output: Option<PathBuf>) {
+        trace!("Building Source File: {}", options.file.display());
+
+        let source = match Self::read_source_file(&options) {
+            Some(source) => source,
+            None => return,
+        };
+
+        let functions = match Self::compile(&options, &source) {
+            Some((functions, _debug)) => functions,
+            None => return,
+        };
 
-            if let Err(err) = file.read_to_string(&mut buf) {
-                println!("Error Reading File: {:?}", err);
+        let bytes = match Self::encode(functions) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                err.emit();
                 return;
             }
+        };
 
-            buf
+        let output = output.unwrap_or_else(|| options.file.with_extension("crunched"));
+        if let Err(err) = std::fs::write(&output, bytes) {
+            println!("Error Writing File: {:?}", err);
+            return;
+        }
+
+        info!("Built {} into {}", options.file.display(), output.display());
+    }
+
+    /// Read the source file given in `options`, printing any errors that occur
+    fn read_source_file(options: &Options) -> Option<String> {
+        use std::{fs::File, io::Read};
+
+        let mut buf = String::new();
+
+        let mut file = match File::open(&options.file) {
+            Ok(file) => file,
+            Err(err) => {
+                println!("Error Opening File: {:?}", err);
+                return None;
+            }
         };
 
-        let mut parser = super::parser::Parser::new(
-            match options.file.file_name() {
-                Some(name) => name.to_str(),
-                None => None,
-            },
-            &source,
-        );
+        if let Err(err) = file.read_to_string(&mut buf) {
+            println!("Error Reading File: {:?}", err);
+            return None;
+        }
+
+        Some(buf)
+    }
+
+    /// The name of the source file given in `options`
+    fn file_name(options: &Options) -> Option<&str> {
+        match options.file.file_name() {
+            Some(name) => name.to_str(),
+            None => None,
+        }
+    }
+
+    /// Parse and interpret source code into the Function Table and its debug info, emitting any
+    /// errors that occur
+    fn compile(options: &Options, source: &str) -> Option<(Vec<Vec<Instruction>>, DebugInfo)> {
+        let file_name = Self::file_name(options);
+        let mut parser = super::parser::Parser::new(file_name, source);
+        let file_name = file_name.unwrap_or("Crunch Source File");
 
         match parser.parse() {
-            Ok(ast) => match Interpreter::from_interner(&options, parser.interner)
-                .interpret(ast.0.clone())
-            {
-                Ok(functions) => {
-                    info!("Executing Crunch Program");
+            Ok(mut ast) => {
+                if let Err(err) = TypeChecker::new(&parser).check(&mut ast.0) {
+                    error!("Error Type Checking Crunch Source File");
+                    Self::emit_diagnostics(file_name, source, &err);
 
-                    if let Err(err) = Self::new(options).execute(functions) {
-                        err.emit()
+                    return None;
+                }
+
+                match Interpreter::from_interner(options, parser.interner).interpret(ast.0) {
+                    Ok(compiled) => Some(compiled),
+                    Err(err) => {
+                        err.emit();
+                        None
                     }
                 }
-                Err(err) => err.emit(),
-            },
+            }
 
             // Emit parsing errors
             Err(err) => {
                 error!("Error Parsing Crunch Source File");
+                Self::emit_diagnostics(file_name, source, &err);
 
-                let writer = codespan_reporting::term::termcolor::StandardStream::stderr(
-                    codespan_reporting::term::termcolor::ColorChoice::Auto,
-                );
-
-                let config = codespan_reporting::term::Config::default();
-
-                let mut files = codespan::Files::new();
-                files.add(
-                    match options.file.file_name() {
-                        Some(name) => name.to_str().unwrap_or("Crunch Source File"),
-                        None => "Crunch Source File",
-                    },
-                    &source,
-                );
-
-                for e in err {
-                    if let Err(err) =
-                        codespan_reporting::term::emit(&mut writer.lock(), &config, &files, &e)
-                    {
-                        println!("Error Emitting Error: {:?}", err);
-                    }
-                }
+                None
             }
         }
     }
 
+    /// Emit parsing or type checking errors for a source file
+    fn emit_diagnostics(file_name: &str, source: &str, diagnostics: &[Diagnostic]) {
+        let mut files = Files::new();
+        files.add(file_name, source);
+
+        Self::emit_file_diagnostics(&files, diagnostics);
+    }
+
+    /// Emit diagnostics that point into `files`
+    fn emit_file_diagnostics(files: &Files, diagnostics: &[Diagnostic]) {
+        let writer = codespan_reporting::term::termcolor::StandardStream::stderr(
+            codespan_reporting::term::termcolor::ColorChoice::Auto,
+        );
+
+        let config = codespan_reporting::term::Config::default();
+
+        for diagnostic in diagnostics {
+            if let Err(err) =
+                codespan_reporting::term::emit(&mut writer.lock(), &config, files, diagnostic)
+            {
+                println!("Error Emitting Error: {:?}", err);
+            }
+        }
+
+        let mut codes = diagnostics
+            .iter()
+            .filter_map(|diagnostic| diagnostic.code.as_deref())
+            .collect::<Vec<_>>();
+        codes.sort_unstable();
+        codes.dedup();
+
+        match codes.as_slice() {
+            [] => {}
+            [code] => eprintln!(
+                "For more information about this error, try `crunch explain {}`",
+                code
+            ),
+            codes => eprintln!(
+                "Some errors have codes: {}\nFor more information about an error, try `crunch explain <code>`",
+                codes.join(", ")
+            ),
+        }
+    }
+
     /// Run a byte file in the `.crunched` format
     #[inline]
     pub fn run_byte_file(options: Options) -> Result<()> {
@@ -127,6 +233,7 @@ impl Crunch {
                     return Err(RuntimeError {
                         ty: RuntimeErrorTy::FileError,
                         message: format!("Error Opening {}", options.file.display()),
+                        backtrace: None,
                     });
                 }
             };
@@ -135,21 +242,14 @@ impl Crunch {
                 return Err(RuntimeError {
                     ty: RuntimeErrorTy::FileError,
                     message: format!("Error Reading {}", options.file.display()),
+                    backtrace: None,
                 });
             }
 
             buf
         };
 
-        let bytes = match Self::validate(&source) {
-            Ok(bytes) => bytes,
-            Err(_err) => {
-                return Err(RuntimeError {
-                    ty: RuntimeErrorTy::BytecodeError,
-                    message: "Invalid Bytecode".to_string(),
-                });
-            }
-        };
+        let bytes = Self::validate(&source)?;
 
         let (main, mut functions) = Self::parse_bytecode(bytes)?;
         functions.insert(0, main);
@@ -209,57 +309,56 @@ impl Crunch {
             let mut parser = super::parser::Parser::new(Some("CrunchRepl"), &input);
 
             match parser.parse() {
-                Ok(ast) => {
+                Ok(mut ast) => {
                     trace!("Parsing successful");
 
                     if repl_outputs.contains(&ReplOutput::Ast) {
                         println!("[Program AST]: {:#?}", &ast);
                     }
 
-                    match Interpreter::from_interner(&options, parser.interner)
-                        .interpret(ast.0.clone())
-                    {
-                        Ok(functions) => {
-                            if repl_outputs.contains(&ReplOutput::Bytecode) {
-                                println!(
-                                    "[Program Bytecode]:\n  [Functions]: {:#?}",
-                                    functions
-                                        .iter()
-                                        .map(|f| format!("{:?}", f))
-                                        .collect::<Vec<String>>()
+                    if let Err(err) = TypeChecker::new(&parser).check(&mut ast.0) {
+                        trace!("Type checking unsuccessful");
+                        Self::emit_diagnostics("CrunchRepl", &input, &err);
+                    } else {
+                        match Interpreter::from_interner(&options, parser.interner)
+                            .interpret(ast.0.clone())
+                        {
+                            Ok((functions, debug)) => {
+                                if repl_outputs.contains(&ReplOutput::Bytecode) {
+                                    println!(
+                                        "[Program Bytecode]:\n  [Functions]: {:#?}",
+                                        functions
+                                            .iter()
+                                            .map(|f| format!("{:?}", f))
+                                            .collect::<Vec<String>>()
+                                    );
+                                }
+
+                                if repl_outputs.contains(&ReplOutput::Disassembly) {
+                                    println!(
+                                        "[Program Disassembly]:\n{}",
+                                        disassemble_functions(&functions)
+                                    );
+                                }
+
+                                println!("[Output]:");
+
+                                Self::new(options.clone()).execute_source(
+                                    functions,
+                                    &debug,
+                                    "CrunchRepl",
+                                    &input,
                                 );
                             }
-
-                            println!("[Output]:");
-
-                            if let Err(err) = Self::new(options.clone()).execute(functions) {
-                                err.emit()
-                            }
+                            Err(err) => err.emit(),
                         }
-                        Err(err) => err.emit(),
                     }
                 }
 
                 // Emit parsing errors
                 Err(err) => {
                     trace!("Parsing unsuccessful");
-
-                    let writer = codespan_reporting::term::termcolor::StandardStream::stderr(
-                        codespan_reporting::term::termcolor::ColorChoice::Auto,
-                    );
-
-                    let config = codespan_reporting::term::Config::default();
-
-                    let mut files = codespan::Files::new();
-                    files.add("CrunchRepl", &input);
-
-                    for e in err {
-                        if let Err(err) =
-                            codespan_reporting::term::emit(&mut writer.lock(), &config, &files, &e)
-                        {
-                            println!("Error Emitting Error: {:?}", err);
-                        }
-                    }
+                    Self::emit_diagnostics("CrunchRepl", &input, &err);
                 }
             }
 
@@ -275,31 +374,19 @@ impl Crunch {
 
     /// Validate raw bytes as valid [`Bytecode`]
     #[inline]
-    pub fn validate<'b>(bytes: &'b [u8]) -> std::result::Result<Bytecode<'b>, &'static str> {
+    pub fn validate<'b>(bytes: &'b [u8]) -> std::result::Result<Bytecode<'b>, VerifyError> {
         Bytecode::validate(bytes)
     }
 
-    /// Encode the currently loaded program as bytes
-    // #[inline]
-    // fn encode(self) -> Vec<u8> {
-    //     Encoder::new({
-    //         let mut funcs = self.vm.functions;
-    //         funcs.insert(0, self.instructions);
-    //         funcs
-    //             .into_iter()
-    //             .map(|f| match f.0 {
-    //                 Either::Left(inst) => inst,
-    //                 Either::Right((_jit, inst)) => inst,
-    //             })
-    //             .collect()
-    //     })
-    //     .encode()
-    // }
+    /// Encode the Function Table as bytecode in the `.crunched` format
+    #[inline]
+    pub fn encode(functions: Vec<Vec<Instruction>>) -> Result<Vec<u8>> {
+        Encoder::new(functions).encode()
+    }
 
     /// Disassemble bytecode in the `.crunched` format
     #[inline]
-    #[must_use]
-    pub fn disassemble(bytes: Bytecode<'_>) -> String {
+    pub fn disassemble(bytes: Bytecode<'_>) -> Result<String> {
         disassemble(&*bytes)
     }
 }
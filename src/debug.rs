@@ -0,0 +1,111 @@
+use crate::Index;
+use codespan::{FileId, Files, Span};
+use std::fmt;
+
+/// Maps the instructions of a compiled program back to the source they were built from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DebugInfo {
+    /// The debug info of every function, ordered like the function table
+    functions: Vec<FunctionInfo>,
+}
+
+impl DebugInfo {
+    #[inline]
+    #[must_use]
+    pub fn new(functions: Vec<FunctionInfo>) -> Self {
+        Self { functions }
+    }
+
+    /// Get the name that a function was declared with
+    #[must_use]
+    pub fn name(&self, function: u32) -> Option<&str> {
+        self.functions
+            .get(function as usize)
+            .map(|func| func.name.as_str())
+    }
+
+    /// Get the span of the source that an instruction was built from, if it was built from any
+    #[must_use]
+    pub fn span(&self, function: u32, index: Index) -> Option<Span> {
+        self.functions
+            .get(function as usize)?
+            .spans
+            .get(*index as usize)
+            .copied()
+            .flatten()
+    }
+}
+
+/// The debug info of a single function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionInfo {
+    /// The readable name of the function, rather than its mangled name
+    pub name: String,
+    /// The span of every instruction in the function
+    pub spans: Vec<Option<Span>>,
+}
+
+/// The functions that were being executed when a runtime error occurred
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Backtrace {
+    /// Every function being executed, innermost first
+    pub frames: Vec<Frame>,
+}
+
+/// A function that was being executed when a runtime error occurred
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// The function's index in the function table
+    pub function: u32,
+    /// The instruction being executed, for every frame but the innermost this is a call
+    pub index: Index,
+}
+
+impl Backtrace {
+    /// Formats the backtrace, naming every function and showing the line of source it was
+    /// executing when the program's debug info and source file are given
+    #[must_use]
+    pub fn render(&self, source: Option<(&DebugInfo, &Files, FileId)>) -> String {
+        let mut lines = vec!["Backtrace:".to_string()];
+
+        for (depth, frame) in self.frames.iter().enumerate() {
+            let name = source
+                .and_then(|(debug, _files, _file)| debug.name(frame.function))
+                .map_or_else(|| format!("function {}", frame.function), str::to_string);
+            lines.push(format!(
+                "  {}: {} at instruction {}",
+                depth, name, frame.index
+            ));
+
+            let location = source.and_then(|(debug, files, file)| {
+                let span = debug.span(frame.function, frame.index)?;
+                let location = files.location(file, span.start()).ok()?;
+                let line = files.line_span(file, location.line).ok()?;
+
+                Some((
+                    files.name(file),
+                    location,
+                    files.source_slice(file, line).ok()?,
+                ))
+            });
+
+            if let Some((file_name, location, line)) = location {
+                lines.push(format!(
+                    "        at {}:{}:{}",
+                    file_name,
+                    location.line.number(),
+                    location.column.number(),
+                ));
+                lines.push(format!("        {}", line.trim()));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl fmt::Display for Backtrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(None))
+    }
+}
@@ -0,0 +1,559 @@
+use crate::RuntimeErrorTy;
+use std::fmt;
+
+/// A stable code for every kind of error Crunch reports, which `crunch explain` can look up
+///
+/// Codes are never reused or renumbered: parsing errors are `E00XX`, type errors are `E01XX` and
+/// runtime errors are `E02XX`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    InvalidToken,
+    InvalidTopLevelToken,
+    EmptyExposing,
+    MissingGenericComma,
+    AmbiguousOperators,
+    UnexpectedEof,
+    UnexpectedToken,
+    InvalidEscape,
+    LiteralOverflow,
+
+    MismatchedTypes,
+    InvalidOperands,
+    ArgumentCount,
+    InvalidGenerics,
+    InstanceLimit,
+    UndefinedName,
+    UndefinedMember,
+    MethodCallStyle,
+    InvalidStructLiteral,
+    MissingReturn,
+
+    GcError,
+    DivideByZero,
+    IncompatibleTypes,
+    MissingMain,
+    NullVar,
+    IllegalInstruction,
+    InvalidJump,
+    MissingValue,
+    MissingString,
+    InvalidString,
+    FileError,
+    BytecodeError,
+    CompilationError,
+    MissingFile,
+    InvalidInt,
+    StdoutError,
+    IntegerOverflow,
+    MissingSymbol,
+    JitError,
+    EmptyStack,
+}
+
+impl ErrorCode {
+    /// Every error code, in the order of their codes
+    pub const ALL: &'static [Self] = &[
+        Self::InvalidToken,
+        Self::InvalidTopLevelToken,
+        Self::EmptyExposing,
+        Self::MissingGenericComma,
+        Self::AmbiguousOperators,
+        Self::UnexpectedEof,
+        Self::UnexpectedToken,
+        Self::InvalidEscape,
+        Self::LiteralOverflow,
+        Self::MismatchedTypes,
+        Self::InvalidOperands,
+        Self::ArgumentCount,
+        Self::InvalidGenerics,
+        Self::InstanceLimit,
+        Self::UndefinedName,
+        Self::UndefinedMember,
+        Self::MethodCallStyle,
+        Self::InvalidStructLiteral,
+        Self::MissingReturn,
+        Self::GcError,
+        Self::DivideByZero,
+        Self::IncompatibleTypes,
+        Self::MissingMain,
+        Self::NullVar,
+        Self::IllegalInstruction,
+        Self::InvalidJump,
+        Self::MissingValue,
+        Self::MissingString,
+        Self::InvalidString,
+        Self::FileError,
+        Self::BytecodeError,
+        Self::CompilationError,
+        Self::MissingFile,
+        Self::InvalidInt,
+        Self::StdoutError,
+        Self::IntegerOverflow,
+        Self::MissingSymbol,
+        Self::JitError,
+        Self::EmptyStack,
+    ];
+
+    /// The code itself, such as `E0201`
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::InvalidToken => "E0001",
+            Self::InvalidTopLevelToken => "E0002",
+            Self::EmptyExposing => "E0003",
+            Self::MissingGenericComma => "E0004",
+            Self::AmbiguousOperators => "E0005",
+            Self::UnexpectedEof => "E0006",
+            Self::UnexpectedToken => "E0007",
+            Self::InvalidEscape => "E0008",
+            Self::LiteralOverflow => "E0009",
+
+            Self::MismatchedTypes => "E0100",
+            Self::InvalidOperands => "E0101",
+            Self::ArgumentCount => "E0102",
+            Self::InvalidGenerics => "E0103",
+            Self::InstanceLimit => "E0104",
+            Self::UndefinedName => "E0105",
+            Self::UndefinedMember => "E0106",
+            Self::MethodCallStyle => "E0107",
+            Self::InvalidStructLiteral => "E0108",
+            Self::MissingReturn => "E0109",
+
+            Self::GcError => "E0200",
+            Self::DivideByZero => "E0201",
+            Self::IncompatibleTypes => "E0202",
+            Self::MissingMain => "E0203",
+            Self::NullVar => "E0204",
+            Self::IllegalInstruction => "E0205",
+            Self::InvalidJump => "E0206",
+            Self::MissingValue => "E0207",
+            Self::MissingString => "E0208",
+            Self::InvalidString => "E0209",
+            Self::FileError => "E0210",
+            Self::BytecodeError => "E0211",
+            Self::CompilationError => "E0212",
+            Self::MissingFile => "E0213",
+            Self::InvalidInt => "E0214",
+            Self::StdoutError => "E0215",
+            Self::IntegerOverflow => "E0216",
+            Self::MissingSymbol => "E0217",
+            Self::JitError => "E0218",
+            Self::EmptyStack => "E0219",
+        }
+    }
+
+    /// Looks up an error code, ignoring its case
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|error| error.code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// A short summary of the error
+    #[must_use]
+    pub const fn title(self) -> &'static str {
+        match self {
+            Self::InvalidToken => "Invalid token",
+            Self::InvalidTopLevelToken => "Invalid top-level token",
+            Self::EmptyExposing => "Nothing was exposed by an import",
+            Self::MissingGenericComma => "Generic parameters are missing a comma",
+            Self::AmbiguousOperators => "Operators of equal precedence were chained",
+            Self::UnexpectedEof => "Unexpected end of file",
+            Self::UnexpectedToken => "Unexpected token",
+            Self::InvalidEscape => "Invalid escape sequence",
+            Self::LiteralOverflow => "An integer literal is too large",
+
+            Self::MismatchedTypes => "Mismatched types",
+            Self::InvalidOperands => "An operator was used on values it doesn't support",
+            Self::ArgumentCount => "The wrong number of arguments were given",
+            Self::InvalidGenerics => "Generics were given incorrectly",
+            Self::InstanceLimit => "Too many instances of generic functions",
+            Self::UndefinedName => "A variable, function or type does not exist",
+            Self::UndefinedMember => "A member or method does not exist",
+            Self::MethodCallStyle => "A method or associated function was called incorrectly",
+            Self::InvalidStructLiteral => "The members of a type were given incorrectly",
+            Self::MissingReturn => "A function can end without returning a value",
+
+            Self::GcError => "The garbage collector failed",
+            Self::DivideByZero => "Division by zero",
+            Self::IncompatibleTypes => "Incompatible types",
+            Self::MissingMain => "The program has no `main` function",
+            Self::NullVar => "A value was null",
+            Self::IllegalInstruction => "An illegal instruction was executed",
+            Self::InvalidJump => "An invalid jump was executed",
+            Self::MissingValue => "A value is missing",
+            Self::MissingString => "A string is missing",
+            Self::InvalidString => "A string is invalid",
+            Self::FileError => "A file couldn't be read or written",
+            Self::BytecodeError => "The bytecode is malformed",
+            Self::CompilationError => "The program couldn't be compiled",
+            Self::MissingFile => "A file does not exist",
+            Self::InvalidInt => "An integer is invalid",
+            Self::StdoutError => "Printing to stdout failed",
+            Self::IntegerOverflow => "Integer overflow",
+            Self::MissingSymbol => "A symbol is missing",
+            Self::JitError => "The JIT failed",
+            Self::EmptyStack => "A value was popped from an empty stack",
+        }
+    }
+
+    /// A long-form explanation of what causes the error
+    #[must_use]
+    pub const fn explanation(self) -> &'static str {
+        match self {
+            Self::InvalidToken => {
+                "The source contains characters that aren't part of any Crunch syntax, such as a \
+                 stray `$` or `#`. Comments start with `::`, not `//` or `#`."
+            }
+            Self::InvalidTopLevelToken => {
+                "Only function declarations, type declarations and imports can appear at the top \
+                 level of a file. Statements and expressions have to be inside of a function."
+            }
+            Self::EmptyExposing => {
+                "An import used the `exposing` keyword without listing anything after it, so \
+                 there's nothing for it to expose."
+            }
+            Self::MissingGenericComma => {
+                "The generic parameters of a function have to be separated by commas, as in \
+                 `fn pair<A, B>(a: A, b: B)`."
+            }
+            Self::AmbiguousOperators => {
+                "Comparisons can't be chained, since `a < b < c` could mean either `(a < b) < c` \
+                 or `a < (b < c)`. Mixing `and`, `or` and `xor` without parentheses is ambiguous \
+                 for the same reason."
+            }
+            Self::UnexpectedEof => {
+                "The file ended while something was still being parsed, usually a block that's \
+                 missing its `end` or an expression that was cut off."
+            }
+            Self::UnexpectedToken => {
+                "The parser found a token where the syntax doesn't allow one, for example a \
+                 missing `)` in a function call or a `let` without a `=`."
+            }
+            Self::InvalidEscape => {
+                "A string contains a backslash that isn't followed by a known escape sequence. \
+                 The supported escapes are `\\n`, `\\r`, `\\t`, `\\\\`, `\\'` and `\\\"`, along \
+                 with `\\x{XX}` for a byte in hex, `\\u{XXXX}` for a unicode codepoint in hex and \
+                 `\\b{XXXXXXXX}` for a byte in binary."
+            }
+            Self::LiteralOverflow => {
+                "Integer literals have to fit in an `int`, which holds the values from \
+                 -2147483648 to 2147483647."
+            }
+
+            Self::MismatchedTypes => {
+                "A value was used where a value of a different type was expected, such as \
+                 assigning a `str` to a variable declared as `int`, returning the wrong type or \
+                 passing an argument of the wrong type. Values that may be `null` have the type \
+                 `nullable<T>` and can't be used where a `T` is expected."
+            }
+            Self::InvalidOperands => {
+                "An operator was used on values that it isn't defined for, such as subtracting \
+                 strings or negating a `bool`. Strings can only be added with `+`, not with the \
+                 fallible or panicking operators."
+            }
+            Self::ArgumentCount => {
+                "A function was called with more or fewer arguments than it declares."
+            }
+            Self::InvalidGenerics => {
+                "Generics were given to a function that isn't generic, the wrong number of \
+                 generics were given, or a generic couldn't be inferred from the arguments of a \
+                 call. Generics that only appear in a function's return type or that are only \
+                 given `null` can't be inferred."
+            }
+            Self::InstanceLimit => {
+                "Every set of types that a generic function is called with creates a new instance \
+                 of it. A generic function that calls itself with ever-growing types, such as \
+                 `T` and then `nullable<T>`, would create instances forever."
+            }
+            Self::UndefinedName => {
+                "A variable, function or type was used that was never declared. Variables only \
+                 exist after their `let` and within the function that declared them, and functions \
+                 have to be declared in the same file unless it imports others."
+            }
+            Self::UndefinedMember => {
+                "A member or method was used on a type that doesn't declare it, or on a value \
+                 that isn't an object, like an `int`."
+            }
+            Self::MethodCallStyle => {
+                "Methods take `self` and have to be called on an object, as in `point.sum()`. \
+                 Associated functions don't take `self` and have to be called on the type itself, \
+                 as in `Point.new(1, 2)`."
+            }
+            Self::InvalidStructLiteral => {
+                "Constructing a type has to give every one of its members exactly once, as in \
+                 `Point { x: 1, y: 2 }`."
+            }
+            Self::MissingReturn => {
+                "A function that declares a return type has to return a value on every path \
+                 through it, but the end of its body can be reached without a `return`, such as \
+                 when an `if` has no `else` or a `while` loop finishes."
+            }
+
+            Self::GcError => {
+                "The garbage collector failed to allocate, find or free a value. This usually \
+                 means the heap ran out of memory or an object was used after being collected."
+            }
+            Self::DivideByZero => {
+                "A panicking division such as `x /! y` was given a divisor of zero, which halts \
+                 the program. Normal division by zero evaluates to zero instead and fallible \
+                 division evaluates to `null`."
+            }
+            Self::IncompatibleTypes => {
+                "An operation was given values of types that it can't be performed on while the \
+                 program was running, such as adding a `bool` to an `int`."
+            }
+            Self::MissingMain => {
+                "Every program starts by calling its `main` function, which wasn't declared."
+            }
+            Self::NullVar => {
+                "A value was used that is `null` or was never given a value, such as comparing \
+                 a variable that was dropped."
+            }
+            Self::IllegalInstruction => {
+                "The program executed an `Illegal` instruction, which only exists to mark code \
+                 that should never be reached."
+            }
+            Self::InvalidJump => {
+                "A jump pointed outside of its function, which means the bytecode is malformed."
+            }
+            Self::MissingValue => {
+                "An instruction referred to a value that doesn't exist, such as a constant that \
+                 isn't in the bytecode's constant pool."
+            }
+            Self::MissingString => {
+                "An instruction referred to a string that isn't in the bytecode's string pool."
+            }
+            Self::InvalidString => "A string in the bytecode isn't valid UTF-8.",
+            Self::FileError => "A file couldn't be opened, read or written.",
+            Self::BytecodeError => {
+                "The bytecode failed verification, either because it was made by an incompatible \
+                 version of Crunch or because it was corrupted."
+            }
+            Self::CompilationError => {
+                "The program couldn't be compiled into bytecode, for example because `break` or \
+                 `continue` was used outside of a loop or something other than a range was \
+                 iterated over."
+            }
+            Self::MissingFile => "A file that was needed does not exist.",
+            Self::InvalidInt => "An integer couldn't be read or is too large for its type.",
+            Self::StdoutError => "Writing to stdout failed, such as when it has been closed.",
+            Self::IntegerOverflow => {
+                "A panicking operation such as `x +! y` overflowed the width of its operands, \
+                 which halts the program. Normal operators grow into wider integers instead and \
+                 fallible operators evaluate to `null`."
+            }
+            Self::MissingSymbol => {
+                "A function or variable was referred to that doesn't exist in the compiled \
+                 program."
+            }
+            Self::JitError => "The JIT failed to compile or run a function.",
+            Self::EmptyStack => {
+                "A value was popped from the value stack while it was empty, which means the \
+                 bytecode passes arguments or return values incorrectly."
+            }
+        }
+    }
+
+    /// Ways the error might be fixed
+    #[must_use]
+    pub const fn solutions(self) -> &'static [&'static str] {
+        match self {
+            Self::InvalidToken => &["Remove the character, or put it inside of a string"],
+            Self::InvalidTopLevelToken => &["Move the code into a function, such as `main`"],
+            Self::EmptyExposing => &[
+                "List what should be exposed after `exposing`",
+                "Remove `exposing` to import the whole module",
+            ],
+            Self::MissingGenericComma => &["Add a comma between the generic parameters"],
+            Self::AmbiguousOperators => &[
+                "Add parentheses to say which operation happens first",
+                "Split the comparisons with `and`, as in `a < b and b < c`",
+            ],
+            Self::UnexpectedEof => &[
+                "Make sure every `fn`, `type`, `if`, `while`, `loop` and `for` has an `end`",
+                "Finish the last expression of the file",
+            ],
+            Self::UnexpectedToken => &["Add the expected token, or remove the unexpected one"],
+            Self::InvalidEscape => &[
+                "Use one of the supported escape sequences",
+                "Write `\\\\` to put a literal backslash in a string",
+            ],
+            Self::LiteralOverflow => &[
+                "Use a smaller integer",
+                "Build the integer with arithmetic, which grows into wider integers",
+            ],
+
+            Self::MismatchedTypes => &[
+                "Change the value to have the expected type",
+                "Change the declared type of the variable, member or function",
+                "Use a `nullable<T>` type where a value may be `null`",
+            ],
+            Self::InvalidOperands => &[
+                "Convert the values to types that the operator supports",
+                "Use the normal operator instead of a fallible or panicking one",
+            ],
+            Self::ArgumentCount => &["Give the function exactly the arguments it declares"],
+            Self::InvalidGenerics => &[
+                "Give the generics explicitly, as in `identity<int>(null)`",
+                "Remove the generics from calls to functions that aren't generic",
+            ],
+            Self::InstanceLimit => {
+                &["Make sure generic functions call themselves with the same generics"]
+            }
+            Self::UndefinedName => &[
+                "Check the name for typos",
+                "Declare the variable with `let` before using it",
+                "Declare the function, or import the file that declares it",
+            ],
+            Self::UndefinedMember => &[
+                "Check the name for typos",
+                "Declare the member or method in the type",
+            ],
+            Self::MethodCallStyle => &[
+                "Call methods on an object, as in `value.method()`",
+                "Call associated functions on the type, as in `Type.function()`",
+            ],
+            Self::InvalidStructLiteral => &[
+                "Give every member of the type exactly once",
+                "Use an associated function that fills in the remaining members",
+            ],
+            Self::MissingReturn => &[
+                "Add a `return` to the end of the function",
+                "Add an `else` that returns to the last `if` of the function",
+                "Remove the return type if the function doesn't return anything",
+            ],
+
+            Self::GcError => &[
+                "Raise the heap size given to the VM",
+                "Report the error, since it may be a bug in the garbage collector",
+            ],
+            Self::DivideByZero => &[
+                "Check that the divisor isn't zero before dividing",
+                "Use fallible division, `/?`, and handle the `null` it gives",
+            ],
+            Self::IncompatibleTypes => &["Convert the values to compatible types first"],
+            Self::MissingMain => &["Declare a `fn main()`"],
+            Self::NullVar => &[
+                "Check that the value isn't `null` before using it",
+                "Make sure the variable was given a value",
+            ],
+            Self::IllegalInstruction => {
+                &["Report the error, since the compiler reached code it shouldn't"]
+            }
+            Self::InvalidJump | Self::MissingValue | Self::MissingString | Self::InvalidString => {
+                &[
+                    "Rebuild the bytecode from its source with `crunch build`",
+                    "Check the bytecode with `crunch verify`",
+                ]
+            }
+            Self::FileError | Self::MissingFile => &[
+                "Check that the path is correct",
+                "Check that the file can be read and written",
+            ],
+            Self::BytecodeError => {
+                &["Rebuild the bytecode from its source with this version of Crunch"]
+            }
+            Self::CompilationError => &[
+                "Only use `break` and `continue` inside of loops",
+                "Only iterate over ranges, as in `for i in 0..10`",
+            ],
+            Self::InvalidInt => &["Use a smaller integer"],
+            Self::StdoutError => &["Make sure stdout is still open"],
+            Self::IntegerOverflow => &[
+                "Use the normal operator, which grows into a wider integer",
+                "Use the fallible operator, such as `+?`, and handle the `null` it gives",
+            ],
+            Self::MissingSymbol => &["Check that the function or variable is declared"],
+            Self::JitError => &["Report the error, since it may be a bug in the JIT"],
+            Self::EmptyStack => &[
+                "Rebuild the bytecode from its source with `crunch build`",
+                "Report the error if the bytecode was built by Crunch",
+            ],
+        }
+    }
+
+    /// The full explanation of the error, as shown by `crunch explain`
+    #[must_use]
+    pub fn explain(self) -> String {
+        let solutions = self
+            .solutions()
+            .iter()
+            .map(|solution| format!("  - {}", solution))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!(
+            "{}: {}\n\n{}\n\nPossible solutions:\n{}\n",
+            self.code(),
+            self.title(),
+            self.explanation(),
+            solutions,
+        )
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl From<RuntimeErrorTy> for ErrorCode {
+    fn from(ty: RuntimeErrorTy) -> Self {
+        match ty {
+            RuntimeErrorTy::GcError => Self::GcError,
+            RuntimeErrorTy::DivideByZero => Self::DivideByZero,
+            RuntimeErrorTy::IncompatibleTypes => Self::IncompatibleTypes,
+            RuntimeErrorTy::MissingMain => Self::MissingMain,
+            RuntimeErrorTy::NullVar => Self::NullVar,
+            RuntimeErrorTy::IllegalInstruction => Self::IllegalInstruction,
+            RuntimeErrorTy::InvalidJump => Self::InvalidJump,
+            RuntimeErrorTy::MissingValue => Self::MissingValue,
+            RuntimeErrorTy::MissingString => Self::MissingString,
+            RuntimeErrorTy::InvalidString => Self::InvalidString,
+            RuntimeErrorTy::FileError => Self::FileError,
+            RuntimeErrorTy::BytecodeError => Self::BytecodeError,
+            RuntimeErrorTy::CompilationError => Self::CompilationError,
+            RuntimeErrorTy::MissingFile => Self::MissingFile,
+            RuntimeErrorTy::InvalidInt => Self::InvalidInt,
+            RuntimeErrorTy::StdoutError => Self::StdoutError,
+            RuntimeErrorTy::IntegerOverflow => Self::IntegerOverflow,
+            RuntimeErrorTy::MissingSymbol => Self::MissingSymbol,
+            RuntimeErrorTy::JitError => Self::JitError,
+            RuntimeErrorTy::EmptyStack => Self::EmptyStack,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn codes_are_unique() {
+        let codes = ErrorCode::ALL
+            .iter()
+            .map(|error| error.code())
+            .collect::<HashSet<_>>();
+        assert_eq!(codes.len(), ErrorCode::ALL.len());
+
+        for error in ErrorCode::ALL {
+            assert_eq!(ErrorCode::from_code(error.code()), Some(*error));
+            assert!(!error.solutions().is_empty(), "{} has no solutions", error);
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(ErrorCode::from_code("e0201"), Some(ErrorCode::DivideByZero));
+        assert_eq!(ErrorCode::from_code("E9999"), None);
+
+        let explanation = ErrorCode::DivideByZero.explain();
+        assert!(explanation.starts_with("E0201: Division by zero\n"));
+        assert!(explanation.contains("Possible solutions:\n  - "));
+    }
+}
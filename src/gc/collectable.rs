@@ -1,5 +1,5 @@
 use super::*;
-use crate::{AllocId, Result, RuntimeError, RuntimeErrorTy};
+use crate::{AllocId, Result, RuntimeError, RuntimeErrorTy, RuntimeValue};
 use num_bigint::{BigInt, BigUint};
 use std::{marker::PhantomData, mem};
 
@@ -88,6 +88,7 @@ macro_rules! collectable_int {
                                 bytes.len(),
                                 std::mem::size_of::<Self>()
                             ),
+                            backtrace: None,
                         })?;
 
                     let int = Self::from_le_bytes(array);
@@ -110,7 +111,7 @@ impl Collectable for &str {
     fn alloc(self, gc: &mut Gc) -> Result<Heap<Self>> {
         let bytes = self.as_bytes();
         let id = gc.allocate_zeroed(bytes.len())?;
-        unsafe { gc.write(id, bytes)? };
+        gc.write_bytes(id, bytes)?;
 
         Ok(Heap::new(id, bytes.len()))
     }
@@ -120,6 +121,7 @@ impl Collectable for &str {
         let string = String::from_utf8(bytes).map_err(|_| RuntimeError {
             ty: RuntimeErrorTy::InvalidString,
             message: "The string retrieved from the GC was invalid UTF-8".to_string(),
+            backtrace: None,
         })?;
 
         Ok(string)
@@ -133,13 +135,13 @@ impl Collectable for BigInt {
         let bytes = self.to_signed_bytes_le();
         let id = gc.allocate_zeroed(bytes.len())?;
 
-        unsafe { gc.write(id, &bytes)? };
+        gc.write_bytes(id, &bytes)?;
 
         Ok(Heap::new(id, bytes.len()))
     }
 
     fn fetch(stub: &Heap<Self>, gc: &Gc) -> Result<Self::Owned> {
-        Ok(BigInt::from_signed_bytes_be(gc.fetch_bytes(stub.id)?))
+        Ok(BigInt::from_signed_bytes_le(gc.fetch_bytes(stub.id)?))
     }
 }
 
@@ -150,13 +152,74 @@ impl Collectable for BigUint {
         let bytes = self.to_bytes_le();
         let id = gc.allocate_zeroed(bytes.len())?;
 
-        unsafe { gc.write(id, &bytes)? };
+        gc.write_bytes(id, &bytes)?;
 
         Ok(Heap::new(id, bytes.len()))
     }
 
     fn fetch(stub: &Heap<Self>, gc: &Gc) -> Result<Self::Owned> {
-        Ok(BigUint::from_bytes_be(gc.fetch_bytes(stub.id)?))
+        Ok(BigUint::from_bytes_le(gc.fetch_bytes(stub.id)?))
+    }
+}
+
+/// The members of an object, which are stored inline one after another
+///
+/// Values never own any memory outside of the heap, so they can be freely copied in and out of it
+impl Collectable for Vec<RuntimeValue> {
+    type Owned = Self;
+
+    fn alloc(self, gc: &mut Gc) -> Result<Heap<Self>> {
+        let size = mem::size_of::<RuntimeValue>() * self.len();
+        let id = gc.allocate_values(self.len())?;
+        let stub = Heap::new(id, size);
+
+        for (index, member) in self.into_iter().enumerate() {
+            stub.set_member(gc, index, member)?;
+        }
+
+        Ok(stub)
+    }
+
+    fn fetch(stub: &Heap<Self>, gc: &Gc) -> Result<Self::Owned> {
+        (0..stub.size / mem::size_of::<RuntimeValue>())
+            .map(|index| stub.member(gc, index))
+            .collect()
+    }
+}
+
+impl Heap<Vec<RuntimeValue>> {
+    /// Gets a pointer to a member of the object
+    fn member_ptr(&self, gc: &Gc, index: usize) -> Result<*mut RuntimeValue> {
+        if index >= self.size / mem::size_of::<RuntimeValue>() {
+            return Err(RuntimeError {
+                ty: RuntimeErrorTy::MissingValue,
+                message: format!("The object has no member {}", index),
+                backtrace: None,
+            });
+        }
+
+        // Safety: The member is within the allocation
+        let ptr = unsafe { *gc.get_ptr(self.id)? as *mut RuntimeValue };
+        Ok(ptr.wrapping_add(index))
+    }
+
+    /// Reads a member of the object
+    pub fn member(&self, gc: &Gc, index: usize) -> Result<RuntimeValue> {
+        let ptr = self.member_ptr(gc, index)?;
+
+        // Safety: The heap isn't aligned, and all members were written when the object was allocated
+        Ok(unsafe { ptr.read_unaligned() })
+    }
+
+    /// Overwrites a member of the object
+    pub fn set_member(&self, gc: &mut Gc, index: usize, member: RuntimeValue) -> Result<()> {
+        let ptr = self.member_ptr(gc, index)?;
+
+        // Safety: The heap isn't aligned, and the member being overwritten owns nothing
+        unsafe { ptr.write_unaligned(member) };
+        gc.mark_written(self.id);
+
+        Ok(())
     }
 }
 
@@ -207,13 +270,13 @@ mod tests {
         stub.root(&mut gc)?;
         assert_eq!(int, stub.fetch(&mut gc)?);
 
-        gc.collect()?;
+        gc.collect(&[])?;
         assert_eq!(int, stub.fetch(&mut gc)?);
 
         stub.unroot(&mut gc)?;
         assert_eq!(int, stub.fetch(&mut gc)?);
 
-        gc.collect()?;
+        gc.collect(&[])?;
         assert!(stub.fetch(&mut gc).is_err());
 
         Ok(())
@@ -0,0 +1,308 @@
+use super::page_size;
+use crate::{AllocId, HeapPointer};
+use std::{alloc, collections::BTreeSet, ptr};
+
+/// The number of bytes of the old generation covered by a single card
+pub const CARD_SIZE: usize = 128;
+
+/// The generation that an allocation lives in
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Generation {
+    /// Recently allocated objects, copied between the halves of the nursery while they survive
+    Young,
+    /// Objects that were promoted out of the nursery, these are only moved when a full collection
+    /// compacts the old generation
+    Old,
+}
+
+/// The layout of a heap region
+fn region_layout(size: usize) -> alloc::Layout {
+    alloc::Layout::from_size_align(size, page_size())
+        .expect("Failed to create GC memory block layout")
+}
+
+/// Allocates a zeroed block of memory for a heap region
+fn allocate_region(size: usize) -> HeapPointer {
+    HeapPointer::new(unsafe { alloc::alloc_zeroed(region_layout(size)) })
+}
+
+/// Frees a heap region allocated by `allocate_region`
+fn free_region(region: HeapPointer, size: usize) {
+    // Safety: The region was allocated with the same layout
+    unsafe { alloc::dealloc(*region, region_layout(size)) };
+}
+
+/// Moves the `usage` bytes at the start of a region into a new region of `size` bytes, freeing
+/// the old region of `old_size` bytes
+fn move_region(region: HeapPointer, old_size: usize, usage: usize, size: usize) -> HeapPointer {
+    let new = allocate_region(size);
+
+    // Safety: Both regions are at least `usage` bytes large and are separate allocations
+    unsafe { ptr::copy_nonoverlapping(*region, *new, usage) };
+    free_region(region, old_size);
+
+    new
+}
+
+/// Gets the pointer `size` bytes past `ptr`
+#[inline]
+pub(crate) fn offset(ptr: HeapPointer, size: usize) -> HeapPointer {
+    HeapPointer::new(ptr.wrapping_add(size))
+}
+
+/// The young generation, a semispace that new objects are bump allocated into
+#[derive(Debug)]
+pub(crate) struct Nursery {
+    /// The left half
+    left: HeapPointer,
+    /// The right half
+    right: HeapPointer,
+    /// The start of free memory in the current half
+    latest: HeapPointer,
+    /// The half currently in use
+    current_side: Side,
+    /// The size of each half, in bytes
+    pub size: usize,
+    /// Every object currently allocated in the nursery
+    pub objects: Vec<AllocId>,
+}
+
+impl Nursery {
+    pub fn new(size: usize) -> Self {
+        let (left, right) = (allocate_region(size), allocate_region(size));
+        trace!("Nursery Left Heap: {:p}, Right Heap: {:p}", left, right);
+
+        Self {
+            left,
+            right,
+            latest: left,
+            current_side: Side::Left,
+            size,
+            objects: Vec::new(),
+        }
+    }
+
+    /// Gets the start of the half currently in use
+    #[must_use]
+    pub fn side(&self) -> HeapPointer {
+        match self.current_side {
+            Side::Left => self.left,
+            Side::Right => self.right,
+        }
+    }
+
+    /// Gets the start of the half not currently in use
+    #[must_use]
+    pub fn other_side(&self) -> HeapPointer {
+        match !self.current_side {
+            Side::Left => self.left,
+            Side::Right => self.right,
+        }
+    }
+
+    /// The size of both halves together, in bytes
+    #[must_use]
+    pub const fn footprint(&self) -> usize {
+        self.size * 2
+    }
+
+    /// The number of bytes used in the current half
+    #[must_use]
+    pub fn usage(&self) -> usize {
+        *self.latest as usize - *self.side() as usize
+    }
+
+    /// Bump allocates `size` bytes in the current half, returning `None` if it's full
+    pub fn bump(&mut self, size: usize) -> Option<HeapPointer> {
+        if self.usage() + size > self.size {
+            return None;
+        }
+
+        let ptr = self.latest;
+        self.latest = offset(ptr, size);
+
+        Some(ptr)
+    }
+
+    /// Moves the nursery into two halves of `size` bytes, keeping the contents of the current half.
+    /// Returns the start of the current half before it was moved
+    pub fn resize(&mut self, size: usize) -> HeapPointer {
+        trace!("Resizing the nursery from {} to {} bytes", self.size, size);
+
+        let (from, usage) = (self.side(), self.usage());
+        let other = self.other_side();
+
+        self.left = move_region(from, self.size, usage, size);
+        free_region(other, self.size);
+        self.right = allocate_region(size);
+
+        self.current_side = Side::Left;
+        self.latest = offset(self.left, usage);
+        self.size = size;
+
+        from
+    }
+
+    /// Swaps to the other half, with free memory starting at `latest`
+    pub fn flip(&mut self, latest: HeapPointer) {
+        self.current_side = !self.current_side;
+        self.latest = latest;
+    }
+}
+
+/// The old generation, objects are bump allocated into it and compacted by full collections
+///
+/// The old generation is split into cards of [`CARD_SIZE`] bytes, a card is marked whenever an
+/// object starting in it is written to, so that the nursery can be collected without tracing the
+/// entire old generation
+#[derive(Debug)]
+pub(crate) struct OldGeneration {
+    /// The start of the old generation
+    start: HeapPointer,
+    /// The start of free memory
+    pub latest: HeapPointer,
+    /// The size of the old generation, in bytes
+    pub size: usize,
+    /// Every object in the old generation along with its offset into it, ordered by their offset.
+    /// Empty objects can share an offset with another object, so the id is part of the key
+    objects: BTreeSet<(usize, AllocId)>,
+    /// Whether each card may contain objects that point into the nursery
+    cards: Vec<bool>,
+}
+
+impl OldGeneration {
+    pub fn new(size: usize) -> Self {
+        let start = allocate_region(size);
+        trace!("Old Generation Heap: {:p}", start);
+
+        Self {
+            start,
+            latest: start,
+            size,
+            objects: BTreeSet::new(),
+            cards: vec![false; size / CARD_SIZE + 1],
+        }
+    }
+
+    #[must_use]
+    pub const fn start(&self) -> HeapPointer {
+        self.start
+    }
+
+    /// The number of bytes in use
+    #[must_use]
+    pub fn usage(&self) -> usize {
+        *self.latest as usize - *self.start as usize
+    }
+
+    /// Bump allocates `size` bytes, returning `None` if the old generation is full
+    pub fn bump(&mut self, size: usize) -> Option<HeapPointer> {
+        if self.usage() + size > self.size {
+            return None;
+        }
+
+        let ptr = self.latest;
+        self.latest = offset(ptr, size);
+
+        Some(ptr)
+    }
+
+    /// Moves the old generation into a region of `size` bytes, keeping its contents and cards.
+    /// Returns the start of the old generation before it was moved
+    pub fn resize(&mut self, size: usize) -> HeapPointer {
+        trace!(
+            "Resizing the old generation from {} to {} bytes",
+            self.size,
+            size
+        );
+
+        let (from, usage) = (self.start, self.usage());
+
+        self.start = move_region(from, self.size, usage, size);
+        self.latest = offset(self.start, usage);
+        self.size = size;
+        self.cards.resize(size / CARD_SIZE + 1, false);
+
+        from
+    }
+
+    /// Records that the object `id` starts at `ptr`
+    pub fn insert(&mut self, ptr: HeapPointer, id: AllocId) {
+        self.objects.insert((self.offset(ptr), id));
+    }
+
+    /// Takes every object out of the old generation, ordered by their address
+    pub fn take_objects(&mut self) -> BTreeSet<(usize, AllocId)> {
+        std::mem::take(&mut self.objects)
+    }
+
+    /// Every object in the old generation, ordered by their address
+    pub fn objects(&self) -> impl Iterator<Item = AllocId> + '_ {
+        self.objects.iter().map(|(_offset, id)| *id)
+    }
+
+    /// Marks the card containing `ptr`
+    pub fn mark_card(&mut self, ptr: HeapPointer) {
+        let card = self.offset(ptr) / CARD_SIZE;
+        trace!("Marking card {}", card);
+
+        self.cards[card] = true;
+    }
+
+    /// Unmarks every card
+    pub fn clear_cards(&mut self) {
+        for card in &mut self.cards {
+            *card = false;
+        }
+    }
+
+    /// Gets every object that starts in a marked card
+    #[must_use]
+    pub fn marked_objects(&self) -> Vec<AllocId> {
+        self.cards
+            .iter()
+            .enumerate()
+            .filter(|(_, marked)| **marked)
+            .flat_map(|(card, _)| {
+                self.objects
+                    .range((card * CARD_SIZE, AllocId(0))..((card + 1) * CARD_SIZE, AllocId(0)))
+                    .map(|(_, id)| *id)
+            })
+            .collect()
+    }
+
+    fn offset(&self, ptr: HeapPointer) -> usize {
+        *ptr as usize - *self.start as usize
+    }
+}
+
+impl Drop for Nursery {
+    fn drop(&mut self) {
+        free_region(self.left, self.size);
+        free_region(self.right, self.size);
+    }
+}
+
+impl Drop for OldGeneration {
+    fn drop(&mut self) {
+        free_region(self.start, self.size);
+    }
+}
+
+/// Represents the heap side currently used
+#[derive(Debug, Copy, Clone)]
+enum Side {
+    Left,
+    Right,
+}
+
+impl std::ops::Not for Side {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
@@ -1,8 +1,13 @@
-use crate::{AllocId, HeapPointer, Result, RuntimeError, RuntimeErrorTy};
-use std::{alloc, collections::HashMap, mem, pin::Pin, ptr, slice};
+use crate::{AllocId, HeapPointer, Result, RuntimeError, RuntimeErrorTy, RuntimeValue};
+use std::{collections::HashMap, mem, pin::Pin, ptr};
 
 mod collectable;
+mod generation;
+mod verify;
+
 pub use collectable::*;
+use generation::{offset, Nursery, OldGeneration};
+pub use generation::{Generation, CARD_SIZE};
 
 /// Gets the memory page size
 #[inline(always)]
@@ -36,14 +41,41 @@ pub(crate) fn page_size() -> usize {
     size
 }
 
+/// The number of times larger the old generation is than a half of the nursery
+pub const OLD_GENERATION_SCALE: usize = 4;
+
+/// Objects larger than this fraction of a nursery half are allocated straight into the old generation
+pub const LARGE_OBJECT_FRACTION: usize = 4;
+
+/// A generation is grown when more than this percent of it is in use after a collection
+pub const GROWTH_THRESHOLD: usize = 50;
+
+/// A generation is shrunk when less than this percent of it is in use after a collection
+pub const SHRINK_THRESHOLD: usize = 25;
+
+/// The smallest that a generation will be shrunk to, in bytes
+pub const MIN_GENERATION_SIZE: usize = 64;
+
+/// The byte that free heap memory is overwritten with, so that stray reads and writes stand out
+pub const POISON: u8 = 0xDE;
+
 /// The options for an initialized GC
 #[derive(Debug, Copy, Clone)]
 pub struct GcOptions {
     /// Activates a GC collect at every opportunity
     pub burn_gc: bool,
-    /// Overwrites the heap on a side swap
+    /// Poisons all free heap memory after every collection
     pub overwrite_heap: bool,
+    /// Verifies the heap after every collection
+    pub verify_heap: bool,
+    /// The initial size of a nursery half, in bytes
     pub heap_size: usize,
+    /// The size that the heap won't be shrunk below, in bytes
+    pub min_heap: usize,
+    /// The size that the heap won't be grown past, in bytes
+    pub max_heap: usize,
+    /// The number of nursery collections an object must survive before being promoted
+    pub promotion_age: usize,
     pub debug: bool,
 }
 
@@ -52,27 +84,44 @@ impl From<&crate::Options> for GcOptions {
         Self {
             burn_gc: options.burn_gc,
             overwrite_heap: options.overwrite_heap,
+            verify_heap: options.verify_heap,
             heap_size: options.heap_size,
+            min_heap: options.min_heap,
+            max_heap: options.max_heap,
+            promotion_age: options.promotion_age,
             debug: options.debug_log,
         }
     }
 }
 
 /// The Crunch Garbage Collector
+///
+/// A generational collector, new objects are allocated into the nursery, which is collected often
+/// by copying its survivors between its two halves. Objects that survive `promotion_age` nursery
+/// collections are promoted into the old generation, which is only collected by full collections.
+/// Writes to old objects mark their card, so that nursery collections can find the old objects
+/// pointing into the nursery without tracing the whole heap
+///
+/// After every collection each generation is grown if most of it survived and shrunk if little of
+/// it did, keeping the size of the heap between `min_heap` and `max_heap`
+///
+/// Allocations never collect, since the objects being built by the running instruction aren't
+/// rooted anywhere yet. Allocations that don't fit request a collection instead, which the VM runs
+/// at its next safepoint with every value in its registers and stacks as the roots
 #[derive(Debug)]
 pub struct Gc {
-    /// The current root objects
+    /// Objects rooted by hand, which are kept alive alongside the roots given to a collection
     roots: Vec<AllocId>,
-    /// The left heap
-    left: HeapPointer,
-    /// The right heap
-    right: HeapPointer,
-    /// The start of free memory
-    latest: HeapPointer,
-    /// The heap side currently in use
-    current_side: Side,
-    /// A vector of each allocation's id and current pointer
+    /// The collection to run at the next safepoint
+    requested: Option<Collection>,
+    /// The young generation
+    nursery: Nursery,
+    /// The old generation
+    old: OldGeneration,
+    /// Each allocation's current pointer, keyed by its id
     allocations: HashMap<AllocId, (HeapPointer, GcValue)>,
+    /// The id that the next allocation will be given
+    next_id: AllocId,
     options: GcOptions,
 }
 
@@ -82,82 +131,101 @@ impl Gc {
     pub fn new(options: &crate::Options) -> Self {
         trace!("Initializing GC");
 
-        let (left, right) = {
-            // Get the memory page size
-            let page_size = page_size();
-
-            // Create the layout for a heap half
-            let layout = alloc::Layout::from_size_align(options.heap_size, page_size)
-                .expect("Failed to create GC memory block layout");
-
-            // Left heap
-            let left = HeapPointer::new(unsafe { alloc::alloc_zeroed(layout) });
-            trace!("Left Heap: {:p}", left);
-
-            // Right heap
-            let right = HeapPointer::new(unsafe { alloc::alloc_zeroed(layout) });
-            trace!("Right Heap: {:p}", right);
-
-            (left, right)
-        };
-
-        Self {
-            left,
-            right,
+        let gc = Self {
+            nursery: Nursery::new(options.heap_size),
+            old: OldGeneration::new(options.heap_size * OLD_GENERATION_SCALE),
             allocations: HashMap::new(),
+            next_id: AllocId::new(0),
             roots: Vec::new(),
-            current_side: Side::Left,
-            latest: left,
+            requested: None,
             options: GcOptions::from(options),
+        };
+
+        if gc.poisons() {
+            gc.poison_free_memory();
         }
+
+        gc
     }
 
     /// Allocate the space for an object
     pub fn allocate(&mut self, size: usize) -> Result<(HeapPointer, AllocId)> {
         trace!("Allocating size {}", size);
 
-        if self.options.burn_gc {
-            self.collect()?;
-        }
+        // Large objects are never copied around the nursery, they're allocated as old objects
+        let (block_start, generation) = if size > self.nursery.size / LARGE_OBJECT_FRACTION {
+            (self.allocate_old(size)?, Generation::Old)
+        } else {
+            self.allocate_young(size)?
+        };
 
-        let (block_end, block_start) = (
-            *self.latest as usize + size,
-            (*self.latest as usize) as *mut u8,
-        );
-        let heap = self.get_side();
+        // Ids are never reused, so stale ids can't alias a new allocation
+        let new_id = self.next_id;
+        self.next_id += 1.into();
+
+        let value = GcValue {
+            id: new_id,
+            size,
+            children: Vec::new(),
+            marked: false,
+            holds_values: false,
+            generation,
+            age: 0,
+        };
+        self.allocations.insert(new_id, (block_start, value));
 
-        // If the object is too large return None
-        if block_end - *heap as usize > self.options.heap_size {
-            self.collect()?; // Collect garbage
-            return Err(RuntimeError {
-                ty: RuntimeErrorTy::GcError,
-                message: "The heap is full".to_string(),
-            });
+        match generation {
+            Generation::Young => self.nursery.objects.push(new_id),
+            Generation::Old => self.old.insert(block_start, new_id),
         }
 
-        // Generate the Id of the new allocation based off of its pointer
-        let mut new_id: AllocId = AllocId::new(block_start as usize >> 2);
-        loop {
-            if !self.allocations.iter().any(|(id, _)| *id == new_id) {
-                // Create the GcValue
-                let value = GcValue {
-                    id: new_id,
-                    size,
-                    children: Vec::new(),
-                    marked: false,
-                };
+        Ok((block_start, new_id))
+    }
 
-                self.allocations
-                    .insert(new_id, (HeapPointer::new(block_start), value));
+    /// Allocates into the nursery. If it's full a nursery collection is requested and the
+    /// allocation is made in the old generation
+    fn allocate_young(&mut self, size: usize) -> Result<(HeapPointer, Generation)> {
+        if let Some(ptr) = self.nursery.bump(size) {
+            return Ok((ptr, Generation::Young));
+        }
 
-                break;
-            }
-            new_id += 1.into();
+        self.request(Collection::Nursery);
+        Ok((self.allocate_old(size)?, Generation::Old))
+    }
+
+    /// Allocates into the old generation. If it's full a full collection is requested and the old
+    /// generation is grown
+    fn allocate_old(&mut self, size: usize) -> Result<HeapPointer> {
+        if let Some(ptr) = self.old.bump(size) {
+            return Ok(ptr);
         }
 
-        self.latest = HeapPointer::new(block_end as *mut u8);
+        self.request(Collection::Full);
+
+        // Grow the old generation until the object fits
+        let needed = self.old.usage() + size;
+        let grown = (self.old.size * 2).max(needed).min(
+            self.options
+                .max_heap
+                .saturating_sub(self.nursery.footprint()),
+        );
+
+        if grown < needed {
+            return Err(RuntimeError {
+                ty: RuntimeErrorTy::GcError,
+                message: format!(
+                    "The heap is full, allocating {} bytes would grow it past the maximum of {} bytes",
+                    size, self.options.max_heap,
+                ),
+                backtrace: None,
+            });
+        }
 
-        Ok((HeapPointer::new(block_start), new_id))
+        self.resize_old(grown);
+        Ok(self
+            .old
+            .bump(size)
+            .expect("The old generation was grown to fit"))
     }
 
     pub fn allocate_heap<T: Collectable>(&mut self, item: T) -> Result<AllocId> {
@@ -178,104 +246,363 @@ impl Gc {
         Ok(id)
     }
 
-    /// Collect all unused objects and shift to the other heap half
-    pub fn collect(&mut self) -> Result<()> {
+    /// Allocates space for `count` values, which are traced as references to the objects they hold
+    ///
+    /// Every value must be written before the next safepoint, since collections read them
+    pub fn allocate_values(&mut self, count: usize) -> Result<AllocId> {
+        trace!("Allocating space for {} values", count);
+
+        let id = self.allocate_zeroed(count * mem::size_of::<RuntimeValue>())?;
+        self.fetch_value_mut(id)?.holds_values = true;
+
+        Ok(id)
+    }
+
+    /// Requests a collection at the next safepoint
+    fn request(&mut self, collection: Collection) {
+        trace!("Requesting a {:?} collection", collection);
+        self.requested = self.requested.max(Some(collection));
+    }
+
+    /// Whether a collection should be run at the next safepoint
+    #[inline]
+    #[must_use]
+    pub fn collection_requested(&self) -> bool {
+        self.options.burn_gc || self.requested.is_some()
+    }
+
+    /// Runs the collection requested since the last safepoint, every safepoint runs a full
+    /// collection when burning the GC
+    pub fn collect_requested(&mut self, roots: &[AllocId]) -> Result<()> {
+        match self.requested.take() {
+            _ if self.options.burn_gc => self.collect(roots),
+            Some(Collection::Full) => self.collect(roots),
+            Some(Collection::Nursery) => self.collect_nursery(roots),
+            None => Ok(()),
+        }
+    }
+
+    /// Collect all objects unreachable from `roots` from both generations, compacting the old
+    /// generation
+    pub fn collect(&mut self, roots: &[AllocId]) -> Result<()> {
         trace!("GC Collecting");
 
-        // The allocations to be transferred over to the new heap
-        let mut keep = HashMap::with_capacity(self.roots.len());
+        // Mark everything reachable from the roots
         let mut queue = Vec::with_capacity(self.allocations.len());
         queue.extend_from_slice(&self.roots);
+        queue.extend_from_slice(roots);
 
         while let Some(val) = queue.pop() {
             if let Some((ptr, root)) = self.allocations.get_mut(&val) {
                 if !root.marked {
-                    root.collect(*ptr, &mut queue, &mut keep);
+                    root.mark(*ptr, &mut queue);
                 }
             }
         }
 
-        let heap = {
-            match !self.current_side {
-                Side::Left => self.left,
-                Side::Right => self.right,
-            }
-        };
-        self.latest = heap;
-
         trace!("Allocations before collect: {}", self.allocations.len());
 
-        let mut new_allocations = HashMap::with_capacity(keep.len());
-        // Iterate over allocations to keep to move them onto the new heap
-        for (id, (old_ptr, val)) in keep.into_iter() {
-            let size = val.size;
+        // Compact the old generation first so that there's as much room as possible for promotions
+        self.compact_old();
+        self.evacuate_nursery();
+
+        // Every old object could have been pointing into the nursery
+        let old = self.old.objects().collect::<Vec<_>>();
+        self.mark_cards(old);
+
+        if let Some(size) =
+            self.resized(self.old.size, self.old.usage(), 1, self.nursery.footprint())
+        {
+            self.resize_old(size);
+        }
+        self.resize_nursery_to_fit();
+
+        trace!("Allocations after collect: {}", self.allocations.len());
+
+        self.finish_collection(roots)
+    }
+
+    /// Collect all objects unreachable from `roots` from the nursery.
+    /// Only the nursery is traced, old objects are assumed to be alive and only the ones in marked
+    /// cards are scanned for pointers into the nursery
+    pub fn collect_nursery(&mut self, roots: &[AllocId]) -> Result<()> {
+        trace!("GC Collecting the nursery");
+
+        let remembered = self.old.marked_objects();
+
+        let mut queue = Vec::with_capacity(self.nursery.objects.len());
+        queue.extend_from_slice(&self.roots);
+        queue.extend_from_slice(roots);
+        for id in &remembered {
+            if let Some((ptr, value)) = self.allocations.get(id) {
+                value.trace(*ptr, &mut queue);
+            }
+        }
 
-            // Safety: Copying bytes from one heap to the other
-            unsafe {
-                let target: &mut [u8] = slice::from_raw_parts_mut(*self.latest as *mut _, size);
-                target.copy_from_slice(slice::from_raw_parts(*old_ptr, size));
+        while let Some(val) = queue.pop() {
+            if let Some((ptr, value)) = self.allocations.get_mut(&val) {
+                if value.generation == Generation::Young && !value.marked {
+                    value.mark(*ptr, &mut queue);
+                }
             }
+        }
 
-            // Push the new allocation to new_allocations
-            new_allocations.insert(id, (self.latest, val));
+        let promoted = self.evacuate_nursery();
 
-            // Increment by the size of the moved object
-            self.latest = self.latest.wrapping_offset(size as isize).into();
+        // Only the previously marked objects and the newly promoted ones can point into the nursery
+        self.mark_cards(remembered.into_iter().chain(promoted));
 
-            trace!("Saving allocation {:?}", id);
+        // Promotions can fill the old generation, but only full collections know enough to shrink it
+        match self.resized(self.old.size, self.old.usage(), 1, self.nursery.footprint()) {
+            Some(size) if size > self.old.size => self.resize_old(size),
+            _ => {}
         }
-        self.allocations = new_allocations;
+        self.resize_nursery_to_fit();
 
-        trace!("Allocations after collect: {}", self.allocations.len());
+        self.finish_collection(roots)
+    }
+
+    /// Poisons the heap's free memory and verifies the heap after a collection, if enabled
+    fn finish_collection(&mut self, roots: &[AllocId]) -> Result<()> {
+        if self.poisons() {
+            self.poison_free_memory();
+        }
+
+        if self.options.verify_heap {
+            self.verify_heap(roots)?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether free memory is poisoned, verifying the heap relies on it
+    const fn poisons(&self) -> bool {
+        self.options.overwrite_heap || self.options.verify_heap
+    }
+
+    /// Overwrites all of the heap's free memory with [`POISON`]
+    fn poison_free_memory(&self) {
+        trace!("Poisoning free heap memory");
+
+        // Safety: Each range is the free part of a heap region
+        unsafe {
+            ptr::write_bytes(
+                *offset(self.nursery.side(), self.nursery.usage()),
+                POISON,
+                self.nursery.size - self.nursery.usage(),
+            );
+            ptr::write_bytes(*self.nursery.other_side(), POISON, self.nursery.size);
+            ptr::write_bytes(*self.old.latest, POISON, self.old.size - self.old.usage());
+        }
+    }
+
+    /// Decides the new size of a generation of `size` bytes with `usage` bytes surviving a
+    /// collection, where the generation is made of `copies` regions of that size and `rest` is
+    /// the size of the rest of the heap.
+    /// Returns `None` if the generation should keep its size
+    fn resized(&self, size: usize, usage: usize, copies: usize, rest: usize) -> Option<usize> {
+        if usage * 100 > size * GROWTH_THRESHOLD {
+            let grown = (size * 2).min(self.options.max_heap.saturating_sub(rest) / copies);
+
+            Some(grown).filter(|grown| *grown > size)
+        } else if usage * 100 < size * SHRINK_THRESHOLD {
+            let shrunk = (size / 2)
+                .max(self.options.min_heap.saturating_sub(rest) / copies)
+                .max(MIN_GENERATION_SIZE);
+
+            Some(shrunk).filter(|shrunk| *shrunk < size)
+        } else {
+            None
+        }
+    }
 
-        if self.options.overwrite_heap {
-            trace!("Overwriting old heap side: {:?}", self.current_side);
+    /// Grows or shrinks the nursery depending on how much of it survived the last collection
+    fn resize_nursery_to_fit(&mut self) {
+        if let Some(size) = self.resized(self.nursery.size, self.nursery.usage(), 2, self.old.size)
+        {
+            let (from, to) = (self.nursery.resize(size), self.nursery.side());
 
-            // Overwrite old heap
-            unsafe {
-                ptr::write_bytes(*self.get_side(), 0x00, self.options.heap_size);
+            for id in &self.nursery.objects {
+                if let Some((ptr, _value)) = self.allocations.get_mut(id) {
+                    *ptr = offset(to, **ptr as usize - *from as usize);
+                }
             }
         }
+    }
 
-        // Change the current side
-        self.current_side = !self.current_side;
+    /// Moves the old generation into a region of `size` bytes
+    fn resize_old(&mut self, size: usize) {
+        let (from, to) = (self.old.resize(size), self.old.start());
 
-        let mut queue = Vec::with_capacity(self.allocations.len());
-        queue.extend_from_slice(&self.roots);
+        for id in self.old.objects() {
+            if let Some((ptr, _value)) = self.allocations.get_mut(&id) {
+                *ptr = offset(to, **ptr as usize - *from as usize);
+            }
+        }
 
-        while let Some(val) = queue.pop() {
-            if let Some((_ptr, root)) = self.allocations.get_mut(&val) {
-                if root.marked {
-                    root.unmark(&mut queue);
+        // Allocations can grow the old generation outside of a collection
+        if self.poisons() {
+            self.poison_free_memory();
+        }
+    }
+
+    /// Frees the unmarked objects in the nursery and moves the marked ones out of it.
+    /// Objects that have survived `promotion_age` collections are promoted into the old generation
+    /// if it has room, the rest are copied into the other half of the nursery.
+    /// Returns the promoted objects
+    fn evacuate_nursery(&mut self) -> Vec<AllocId> {
+        let mut latest = self.nursery.other_side();
+        let mut survivors = Vec::with_capacity(self.nursery.objects.len());
+        let mut promoted = Vec::new();
+
+        for id in mem::take(&mut self.nursery.objects) {
+            let marked = matches!(self.allocations.get(&id), Some((_ptr, value)) if value.marked);
+            if !marked {
+                trace!("Freeing allocation {:?}", id);
+                self.allocations.remove(&id);
+
+                continue;
+            }
+
+            let (ptr, value) = self
+                .allocations
+                .get_mut(&id)
+                .expect("Marked allocations exist");
+            value.marked = false;
+            value.age += 1;
+
+            let promotion = if value.age >= self.options.promotion_age {
+                self.old.bump(value.size)
+            } else {
+                None
+            };
+
+            let target = if let Some(target) = promotion {
+                trace!("Promoting allocation {:?}", id);
+
+                value.generation = Generation::Old;
+                self.old.insert(target, id);
+                promoted.push(id);
+
+                target
+            } else {
+                trace!("Saving allocation {:?}", id);
+
+                let target = latest;
+                latest = offset(latest, value.size);
+                survivors.push(id);
+
+                target
+            };
+
+            // Safety: Copying bytes from the nursery to an unused part of a heap
+            unsafe { ptr::copy_nonoverlapping(**ptr, *target, value.size) };
+            *ptr = target;
+        }
+
+        self.nursery.objects = survivors;
+        self.nursery.flip(latest);
+
+        promoted
+    }
+
+    /// Frees the unmarked objects in the old generation and slides the marked ones down over the gaps
+    fn compact_old(&mut self) {
+        let mut latest = self.old.start();
+
+        for (_offset, id) in self.old.take_objects() {
+            let marked = matches!(self.allocations.get(&id), Some((_ptr, value)) if value.marked);
+            if !marked {
+                trace!("Freeing allocation {:?}", id);
+                self.allocations.remove(&id);
+
+                continue;
+            }
+
+            let (ptr, value) = self
+                .allocations
+                .get_mut(&id)
+                .expect("Marked allocations exist");
+            value.marked = false;
+
+            if *ptr != latest {
+                // Safety: Objects are only ever moved down, and `ptr::copy` allows overlapping
+                unsafe { ptr::copy(**ptr, *latest, value.size) };
+                *ptr = latest;
+            }
+
+            self.old.insert(latest, id);
+            latest = offset(latest, value.size);
+        }
+
+        self.old.latest = latest;
+    }
+
+    /// Clears the card table and re-marks the cards of the given old objects that still point into
+    /// the nursery
+    fn mark_cards(&mut self, objects: impl IntoIterator<Item = AllocId>) {
+        self.old.clear_cards();
+
+        let mut references = Vec::new();
+        for id in objects {
+            if let Some((ptr, value)) = self.allocations.get(&id) {
+                references.clear();
+                value.trace(*ptr, &mut references);
+
+                let points_young = references.iter().any(|child| {
+                    matches!(
+                        self.allocations.get(child),
+                        Some((_, child)) if child.generation == Generation::Young
+                    )
+                });
+
+                if value.generation == Generation::Old && points_young {
+                    self.old.mark_card(*ptr);
                 }
             }
         }
+    }
 
-        Ok(())
+    /// The write barrier, marks the card of an object if it's in the old generation
+    pub(crate) fn mark_written(&mut self, id: AllocId) {
+        if let Some((ptr, value)) = self.allocations.get(&id) {
+            if value.generation == Generation::Old {
+                self.old.mark_card(*ptr);
+            }
+        }
     }
 
     pub unsafe fn get_ptr(&self, id: AllocId) -> Result<HeapPointer> {
         let (ptr, _val) = self.allocations.get(&id).ok_or(RuntimeError {
             ty: RuntimeErrorTy::GcError,
             message: "Requested value does not exist".to_string(),
+            backtrace: None,
         })?;
 
         Ok(*ptr)
     }
 
-    fn dump_heap(&self, side: Side) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    /// Gets the generation that an object is currently in
+    pub fn generation(&self, id: AllocId) -> Result<Generation> {
+        self.allocations
+            .get(&id)
+            .map(|(_ptr, value)| value.generation)
+            .ok_or(RuntimeError {
+                ty: RuntimeErrorTy::GcError,
+                message: "Requested value does not exist".to_string(),
+                backtrace: None,
+            })
+    }
+
+    fn dump_heap(
+        &self,
+        side: HeapPointer,
+        name: &str,
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
         use std::io::Write;
 
-        let mut f = std::fs::File::create("right.dump")?;
-        f.write_all(unsafe {
-            std::slice::from_raw_parts(
-                match side {
-                    Side::Left => *self.left,
-                    Side::Right => *self.right,
-                },
-                self.options.heap_size,
-            )
-        })?;
+        let mut f = std::fs::File::create(name)?;
+        f.write_all(unsafe { std::slice::from_raw_parts(*side, self.nursery.size) })?;
 
         Ok(())
     }
@@ -284,24 +611,19 @@ impl Gc {
     pub fn fetch_bytes<'gc>(&'gc self, id: AllocId) -> Result<&[u8]> {
         trace!("Fetching {}", id);
 
-        if let Some((_, (ptr, val))) = self.allocations.iter().find(|(i, _)| **i == id.into()) {
+        if let Some((ptr, val)) = self.allocations.get(&id) {
             if self.options.debug {
-                self.dump_heap(Side::Right).unwrap();
-                self.dump_heap(Side::Left).unwrap();
+                self.dump_heap(self.nursery.side(), "current.dump").unwrap();
+                self.dump_heap(self.nursery.other_side(), "other.dump")
+                    .unwrap();
             }
 
-            println!(
-                "Ptr: {:p}, Heap: {:p}, Heap - Ptr: {:p}",
-                **ptr,
-                *self.get_side(),
-                (**ptr as usize - *self.get_side() as usize) as *const u8
-            );
-
             Ok(unsafe { std::slice::from_raw_parts(**ptr, val.size) })
         } else {
             Err(RuntimeError {
                 ty: RuntimeErrorTy::GcError,
                 message: "Requested value does not exist".to_string(),
+                backtrace: None,
             })
         }
     }
@@ -310,81 +632,38 @@ impl Gc {
     fn fetch_value(&self, id: AllocId) -> Result<&GcValue> {
         trace!("Fetching allocation {}", id);
 
-        let mut queue = Vec::with_capacity(self.allocations.len());
-        queue.extend_from_slice(&self.roots);
-
-        while let Some(val) = queue.pop() {
-            if let Some((_ptr, root)) = self.allocations.get(&val) {
-                if root.id == id {
-                    return Ok(root);
-                } else {
-                    queue.extend_from_slice(&root.children);
-                }
-            }
-        }
-
-        Err(RuntimeError {
-            ty: RuntimeErrorTy::GcError,
-            message: "Requested value does not exist".to_string(),
-        })
+        self.allocations
+            .get(&id)
+            .map(|(_ptr, value)| value)
+            .ok_or(RuntimeError {
+                ty: RuntimeErrorTy::GcError,
+                message: "Requested value does not exist".to_string(),
+                backtrace: None,
+            })
     }
 
     /// Fetch a currently allocated value
     fn fetch_value_mut(&mut self, id: AllocId) -> Result<&mut GcValue> {
         trace!("Fetching allocation {} mutably", id);
 
-        let mut queue = Vec::with_capacity(self.allocations.len());
-        queue.extend_from_slice(&self.roots);
-
-        let allocs = &mut self.allocations;
-
-        while let Some(val) = queue.pop() {
-            if allocs.get(&val).map(|v| v.1.id == id).unwrap_or(false) {
-                return allocs
-                    .get_mut(&val)
-                    .map(|(_, root)| &mut *root)
-                    .ok_or(RuntimeError {
-                        ty: RuntimeErrorTy::GcError,
-                        message: "Requested value does not exist".to_string(),
-                    });
-            } else if let Some((_, root)) = allocs.get(&val) {
-                queue.extend_from_slice(&root.children);
-            }
-        }
-
-        Err(RuntimeError {
-            ty: RuntimeErrorTy::GcError,
-            message: "Requested value does not exist".to_string(),
-        })
-    }
-    /*
-    TODO: When polonius lands, replace current implementation
-    fn fetch_value_mut(&mut self, id: AllocId) -> Result<&mut GcValue> {
-        let mut queue = Vec::with_capacity(self.allocations.len());
-        queue.extend_from_slice(&self.roots);
-
-        let mut value;
-        while let Some(val) = queue.pop() {
-            value = self.allocations.get_mut(&val);
-            if let Some((_ptr, root)) = value {
-                if root.id == id {
-                    return Ok(root);
-                } else {
-                    queue.extend_from_slice(&root.children);
-                }
-            }
-        }
-
-        Err(RuntimeError {
-            ty: RuntimeErrorTy::GcError,
-            message: "Requested value does not exist".to_string(),
-        })
+        self.allocations
+            .get_mut(&id)
+            .map(|(_ptr, value)| value)
+            .ok_or(RuntimeError {
+                ty: RuntimeErrorTy::GcError,
+                message: "Requested value does not exist".to_string(),
+                backtrace: None,
+            })
     }
-    */
 
     pub fn add_child(&mut self, parent: AllocId, child: AllocId) -> Result<()> {
         self.fetch_value_mut(parent)?.add_child(child);
 
+        // An old object now points into the nursery
+        if let Ok(Generation::Young) = self.generation(child) {
+            self.mark_written(parent);
+        }
+
         Ok(())
     }
 
@@ -404,20 +683,17 @@ impl Gc {
         if let Some(index) = self.roots.iter().position(|root_id| *root_id == id) {
             self.roots.remove(index);
 
-            if self.options.burn_gc {
-                self.collect()?;
-            }
-
             return Ok(());
         }
 
         Err(RuntimeError {
             ty: RuntimeErrorTy::GcError,
             message: "The object to be unrooted does not exist".to_string(),
+            backtrace: None,
         })
     }
 
-    pub unsafe fn write<Id, T>(&self, id: Id, data: T) -> Result<()>
+    pub unsafe fn write<Id, T>(&mut self, id: Id, data: T) -> Result<()>
     where
         Id: Into<AllocId> + Copy,
     {
@@ -425,47 +701,79 @@ impl Gc {
         trace!("Writing to allocation {}", id);
 
         if let Some((ptr, val)) = self.allocations.get(&id) {
-            if mem::size_of::<T>() != val.size {
+            if mem::size_of::<T>() == val.size {
                 (**ptr as *mut T).write(data);
                 trace!("Wrote to allocation {}, ptr {:p}", id, *ptr);
+                self.mark_written(id);
 
                 Ok(())
             } else {
                 Err(RuntimeError {
                     ty: RuntimeErrorTy::GcError,
                     message: format!("Size Misalign: {} != {}", val.size, mem::size_of::<T>()),
+                    backtrace: None,
+                })
+            }
+        } else {
+            Err(RuntimeError {
+                ty: RuntimeErrorTy::GcError,
+                message: "Object to be written to does not exist".to_string(),
+                backtrace: None,
+            })
+        }
+    }
+
+    /// Copies bytes into an allocation of the same size
+    pub fn write_bytes<Id>(&mut self, id: Id, bytes: &[u8]) -> Result<()>
+    where
+        Id: Into<AllocId> + Copy,
+    {
+        let id = id.into();
+        trace!("Writing {} bytes to allocation {}", bytes.len(), id);
+
+        if let Some((ptr, val)) = self.allocations.get(&id) {
+            if bytes.len() == val.size {
+                // Safety: The allocation is exactly as large as the bytes being copied into it
+                unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), **ptr, bytes.len()) };
+                self.mark_written(id);
+
+                Ok(())
+            } else {
+                Err(RuntimeError {
+                    ty: RuntimeErrorTy::GcError,
+                    message: format!("Size Misalign: {} != {}", val.size, bytes.len()),
+                    backtrace: None,
                 })
             }
         } else {
             Err(RuntimeError {
                 ty: RuntimeErrorTy::GcError,
                 message: "Object to be written to does not exist".to_string(),
+                backtrace: None,
             })
         }
     }
 
-    /// Gets the current heap side
+    /// Gets the current side of the nursery
     #[must_use]
     pub fn get_side(&self) -> HeapPointer {
-        match self.current_side {
-            Side::Left => self.left,
-            Side::Right => self.right,
-        }
+        self.nursery.side()
     }
 
     /// Information about the state of the GC
     #[must_use]
     pub fn data(&self) -> GcData {
         trace!(
-            "Latest: {:?}, Start: {:?}, Diff {}",
-            self.latest,
-            self.get_side(),
-            *self.latest as usize - *self.get_side() as usize
+            "Nursery Usage: {}, Old Generation Usage: {}",
+            self.nursery.usage(),
+            self.old.usage()
         );
 
         GcData {
-            heap_size: self.options.heap_size,
-            heap_usage: *self.latest as usize - *self.get_side() as usize,
+            heap_size: self.nursery.footprint() + self.old.size,
+            heap_usage: self.nursery.usage() + self.old.usage(),
+            nursery_usage: self.nursery.usage(),
+            old_usage: self.old.usage(),
             num_roots: self.roots.len(),
             num_allocations: self.allocations.len(),
         }
@@ -474,7 +782,7 @@ impl Gc {
     /// See if the GC contains an Id
     #[inline]
     pub fn contains<Id: Into<AllocId> + Copy>(&self, id: Id) -> bool {
-        self.allocations.iter().any(|(__id, _)| *__id == id.into())
+        self.allocations.contains_key(&id.into())
     }
 }
 
@@ -491,6 +799,10 @@ pub struct GcData {
     heap_size: usize,
     /// Amount of the heap currently used
     heap_usage: usize,
+    /// Amount of the nursery currently used
+    nursery_usage: usize,
+    /// Amount of the old generation currently used
+    old_usage: usize,
     /// Number of Root objects
     num_roots: usize,
     /// Total number of allocated objects
@@ -500,32 +812,25 @@ pub struct GcData {
 impl std::fmt::Display for GcData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f,
-            "Total Heap Size: {} bytes\nTotal Heap Usage: {} bytes\nPercent Heap Usage: {:.2}%\nTotal Root Objects: {}\nTotal Allocations: {}",
+            "Total Heap Size: {} bytes\nTotal Heap Usage: {} bytes\nPercent Heap Usage: {:.2}%\nNursery Usage: {} bytes\nOld Generation Usage: {} bytes\nTotal Root Objects: {}\nTotal Allocations: {}",
             self.heap_size / 8,
             self.heap_usage,
             (self.heap_usage as f64 / (self.heap_size / 8) as f64) * 100.0,
+            self.nursery_usage,
+            self.old_usage,
             self.num_roots,
             self.num_allocations
         )
     }
 }
 
-/// Represents the heap side currently used
-#[derive(Debug, Copy, Clone)]
-enum Side {
-    Left,
-    Right,
-}
-
-impl std::ops::Not for Side {
-    type Output = Self;
-
-    fn not(self) -> Self::Output {
-        match self {
-            Self::Left => Self::Right,
-            Self::Right => Self::Left,
-        }
-    }
+/// The kinds of collection that the GC can run, ordered by how much of the heap they collect
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Collection {
+    /// Only the nursery is collected
+    Nursery,
+    /// Both generations are collected
+    Full,
 }
 
 /// A value contained in the GC
@@ -539,20 +844,41 @@ pub struct GcValue {
     children: Vec<AllocId>,
     /// Whether or not the object is marked, for collection purposes
     marked: bool,
+    /// Whether the object is made of values, which can hold other objects
+    holds_values: bool,
+    /// The generation the object is currently in
+    generation: Generation,
+    /// The number of nursery collections the object has survived
+    age: usize,
 }
 
 impl GcValue {
-    /// Fetches The id and size of all children
+    /// Marks self and queues all children to be marked
     #[inline]
-    pub fn collect(
-        &mut self,
-        ptr: HeapPointer,
-        queue: &mut Vec<AllocId>,
-        map: &mut HashMap<AllocId, (HeapPointer, Self)>,
-    ) {
+    pub fn mark(&mut self, ptr: HeapPointer, queue: &mut Vec<AllocId>) {
         self.marked = true;
-        map.insert(self.id, (ptr, self.clone())); // Avoid clone
+        self.trace(ptr, queue);
+    }
+
+    /// Queues every object that the value, which lives at `ptr`, refers to
+    pub fn trace(&self, ptr: HeapPointer, queue: &mut Vec<AllocId>) {
         queue.extend_from_slice(&self.children);
+        queue.extend(self.values(ptr).filter_map(|value| value.heap_id()));
+    }
+
+    /// Reads the values that the object, which lives at `ptr`, is made of. Objects that don't hold
+    /// values have none
+    fn values(&self, ptr: HeapPointer) -> impl Iterator<Item = RuntimeValue> {
+        let values = *ptr as *const RuntimeValue;
+        let count = if self.holds_values {
+            self.size / mem::size_of::<RuntimeValue>()
+        } else {
+            0
+        };
+
+        // Safety: The heap isn't aligned, and every value was written before the object could be
+        // traced
+        (0..count).map(move |index| unsafe { values.add(index).read_unaligned() })
     }
 
     /// Adds a child
@@ -564,11 +890,385 @@ impl GcValue {
     pub fn remove_child(&mut self, child: AllocId) {
         self.children.remove_item(&child);
     }
+}
 
-    /// Unmarks self and all children
-    #[inline]
-    pub fn unmark(&mut self, queue: &mut Vec<AllocId>) {
-        self.marked = false;
-        queue.extend_from_slice(&self.children);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OptionBuilder;
+
+    #[test]
+    fn promotion() -> Result<()> {
+        let mut gc = Gc::new(&OptionBuilder::new("./gc_promotion").build());
+
+        let stub = 10_usize.alloc(&mut gc)?;
+        stub.root(&mut gc)?;
+        assert_eq!(gc.generation(stub.id())?, Generation::Young);
+
+        gc.collect_nursery(&[])?;
+        assert_eq!(gc.generation(stub.id())?, Generation::Young);
+
+        gc.collect_nursery(&[])?;
+        assert_eq!(gc.generation(stub.id())?, Generation::Old);
+
+        // Old objects aren't moved by nursery collections
+        let ptr = unsafe { gc.get_ptr(stub.id())? };
+        gc.collect_nursery(&[])?;
+        assert_eq!(unsafe { gc.get_ptr(stub.id())? }, ptr);
+        assert_eq!(stub.fetch(&gc)?, 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn card_marking() -> Result<()> {
+        let mut gc = Gc::new(&OptionBuilder::new("./gc_card_marking").build());
+
+        let parent = 1_usize.alloc(&mut gc)?;
+        parent.root(&mut gc)?;
+        gc.collect_nursery(&[])?;
+        gc.collect_nursery(&[])?;
+        assert_eq!(gc.generation(parent.id())?, Generation::Old);
+
+        // Only reachable through the old parent's card
+        let child = 2_usize.alloc(&mut gc)?;
+        let garbage = 3_usize.alloc(&mut gc)?;
+        parent.add_child(&child, &mut gc)?;
+
+        gc.collect_nursery(&[])?;
+        assert_eq!(child.fetch(&gc)?, 2);
+        assert!(!gc.contains(garbage.id()));
+
+        // The card stays marked while the child is young
+        gc.collect_nursery(&[])?;
+        assert_eq!(gc.generation(child.id())?, Generation::Old);
+        assert_eq!(child.fetch(&gc)?, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn nursery_overflow() -> Result<()> {
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_nursery_overflow")
+                .heap_size(1024)
+                .build(),
+        );
+
+        let kept = 7_usize.alloc(&mut gc)?;
+        kept.root(&mut gc)?;
+
+        // Far more than fits into the nursery at once, collecting at safepoints like the VM does
+        for int in 0..1000_usize {
+            int.alloc(&mut gc)?;
+
+            if gc.collection_requested() {
+                gc.collect_requested(&[])?;
+            }
+        }
+
+        assert_eq!(kept.fetch(&gc)?, 7);
+        assert_eq!(gc.old.size, 1024 * OLD_GENERATION_SCALE);
+
+        Ok(())
+    }
+
+    #[test]
+    fn large_objects() -> Result<()> {
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_large_objects")
+                .heap_size(1024)
+                .build(),
+        );
+
+        let small = "small".alloc(&mut gc)?;
+        let large = "large".repeat(100);
+        let large = large.as_str().alloc(&mut gc)?;
+
+        assert_eq!(gc.generation(small.id())?, Generation::Young);
+        assert_eq!(gc.generation(large.id())?, Generation::Old);
+
+        Ok(())
+    }
+
+    #[test]
+    fn compaction() -> Result<()> {
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_compaction")
+                .promotion_age(1)
+                .build(),
+        );
+
+        let first = 1_usize.alloc(&mut gc)?;
+        let second = 2_usize.alloc(&mut gc)?;
+        first.root(&mut gc)?;
+        second.root(&mut gc)?;
+
+        gc.collect_nursery(&[])?;
+        assert_eq!(gc.generation(first.id())?, Generation::Old);
+        assert_eq!(gc.generation(second.id())?, Generation::Old);
+
+        let first_ptr = unsafe { gc.get_ptr(first.id())? };
+        first.unroot(&mut gc)?;
+        gc.collect(&[])?;
+
+        assert!(!gc.contains(first.id()));
+        assert_eq!(unsafe { gc.get_ptr(second.id())? }, first_ptr);
+        assert_eq!(second.fetch(&gc)?, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn large_allocation() -> Result<()> {
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_large_allocation")
+                .heap_size(64)
+                .build(),
+        );
+
+        // Far larger than the whole heap was to begin with
+        let string = "large".repeat(2000);
+        let stub = string.as_str().alloc(&mut gc)?;
+
+        assert!(gc.old.size >= string.len());
+        assert_eq!(stub.fetch(&gc)?, string);
+
+        Ok(())
+    }
+
+    #[test]
+    fn heap_resizing() -> Result<()> {
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_heap_resizing")
+                .heap_size(64)
+                .min_heap(0)
+                .build(),
+        );
+        let initial = (gc.nursery.size, gc.old.size);
+
+        let stubs = (0..100_usize)
+            .map(|int| {
+                let stub = int.alloc(&mut gc)?;
+                stub.root(&mut gc)?;
+
+                Ok(stub)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        assert!(gc.old.size > initial.1);
+        for (int, stub) in stubs.iter().enumerate() {
+            assert_eq!(stub.fetch(&gc)?, int);
+        }
+
+        let grown = gc.old.size;
+        for stub in &stubs {
+            stub.unroot(&mut gc)?;
+        }
+        gc.collect(&[])?;
+        gc.collect(&[])?;
+
+        assert!(gc.old.size < grown);
+        assert!(gc.old.size >= MIN_GENERATION_SIZE);
+        assert!(gc.nursery.size >= MIN_GENERATION_SIZE);
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_heap() {
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_max_heap")
+                .heap_size(64)
+                .max_heap(512)
+                .build(),
+        );
+
+        let string = "large".repeat(200);
+        let err = string.as_str().alloc(&mut gc).unwrap_err();
+        assert_eq!(err.ty, RuntimeErrorTy::GcError);
+    }
+
+    #[test]
+    fn max_heap_counts_both_halves() -> Result<()> {
+        let max_heap = 1024;
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_max_heap_halves")
+                .heap_size(64)
+                .min_heap(0)
+                .max_heap(max_heap)
+                .build(),
+        );
+
+        for int in 0..60_usize {
+            int.alloc(&mut gc)?.root(&mut gc)?;
+            gc.collect_requested(&[])?;
+
+            assert!(gc.nursery.footprint() + gc.old.size <= max_heap);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn ids_are_never_reused() -> Result<()> {
+        let mut gc = Gc::new(&OptionBuilder::new("./gc_ids").build());
+
+        let freed = 1_usize.alloc(&mut gc)?;
+        gc.collect(&[])?;
+        assert!(!gc.contains(freed.id()));
+
+        // Allocated into the same memory, but must not be mistaken for the freed object
+        let allocated = 2_usize.alloc(&mut gc)?;
+        assert_ne!(allocated.id(), freed.id());
+        assert!(!gc.contains(freed.id()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn objects_trace_members() -> Result<()> {
+        let mut gc = Gc::new(&OptionBuilder::new("./gc_trace_members").build());
+
+        let string = "member".alloc(&mut gc)?;
+        let object = vec![RuntimeValue::I32(10), RuntimeValue::GcString(string)].alloc(&mut gc)?;
+
+        // The string is only reachable through the object's members
+        gc.collect_nursery(&[object.id()])?;
+        gc.collect(&[object.id()])?;
+        assert_eq!(string.fetch(&gc)?, "member");
+
+        gc.collect(&[])?;
+        assert!(!gc.contains(object.id()));
+        assert!(!gc.contains(string.id()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_heap() -> Result<()> {
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_verify_heap")
+                .verify_heap(true)
+                .heap_size(128)
+                .build(),
+        );
+
+        let member = "member".alloc(&mut gc)?;
+        let object = vec![RuntimeValue::I32(10), RuntimeValue::GcString(member)].alloc(&mut gc)?;
+        let string = "large".repeat(20);
+        let large = string.as_str().alloc(&mut gc)?;
+        let _garbage = 10_usize.alloc(&mut gc)?;
+
+        // Promotes the object and string, and frees the garbage from both generations
+        for _ in 0..3 {
+            gc.collect_nursery(&[object.id(), large.id()])?;
+        }
+        gc.collect(&[object.id()])?;
+        gc.verify_heap(&[object.id()])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty_objects() -> Result<()> {
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_empty_objects")
+                .verify_heap(true)
+                .build(),
+        );
+
+        // Empty objects share their address with whatever is allocated after them
+        let garbage = 1_usize.alloc(&mut gc)?;
+        let empties = ["".alloc(&mut gc)?, "".alloc(&mut gc)?, "".alloc(&mut gc)?];
+        let full = "full".alloc(&mut gc)?;
+
+        let mut roots = vec![
+            empties[0].id(),
+            empties[1].id(),
+            empties[2].id(),
+            full.id(),
+            garbage.id(),
+        ];
+
+        // Promotes everything, then frees the garbage so that the rest are compacted over it
+        for _ in 0..3 {
+            gc.collect_nursery(&roots)?;
+        }
+        roots.pop();
+        gc.collect(&roots)?;
+
+        assert!(!gc.contains(garbage.id()));
+        for empty in &empties {
+            assert_eq!(empty.fetch(&gc)?, "");
+        }
+        assert_eq!(full.fetch(&gc)?, "full");
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_heap_dangling() -> Result<()> {
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_verify_dangling")
+                .verify_heap(true)
+                .build(),
+        );
+
+        let parent = 1_usize.alloc(&mut gc)?;
+        gc.add_child(parent.id(), AllocId::new(1000))?;
+
+        let err = gc.collect(&[parent.id()]).unwrap_err();
+        assert!(
+            err.message.contains("refers to object 1000"),
+            "{}",
+            err.message
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_heap_overlap() -> Result<()> {
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_verify_overlap")
+                .verify_heap(true)
+                .build(),
+        );
+
+        let first = 1_usize.alloc(&mut gc)?;
+        let second = 2_usize.alloc(&mut gc)?;
+        gc.verify_heap(&[first.id(), second.id()])?;
+
+        gc.fetch_value_mut(first.id())?.size += 1;
+        let err = gc.verify_heap(&[]).unwrap_err();
+        assert!(
+            err.message.contains(&format!("Object {} ", first.id()))
+                && err.message.contains("overlaps"),
+            "{}",
+            err.message
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_heap_poison() -> Result<()> {
+        let mut gc = Gc::new(
+            &OptionBuilder::new("./gc_verify_poison")
+                .verify_heap(true)
+                .build(),
+        );
+
+        let stub = 1_usize.alloc(&mut gc)?;
+        let ptr = unsafe { gc.get_ptr(stub.id())? };
+        gc.collect(&[stub.id()])?;
+
+        // A stale pointer into the from-space
+        unsafe { ptr.write(0x01) };
+        let err = gc.verify_heap(&[stub.id()]).unwrap_err();
+        assert!(err.message.contains("from-space"), "{}", err.message);
+
+        Ok(())
     }
 }
@@ -0,0 +1,216 @@
+use super::{offset, Gc, Generation, POISON};
+use crate::{AllocId, HeapPointer, Result, RuntimeError, RuntimeErrorTy, RuntimeValue};
+use std::{collections::HashSet, mem, slice};
+
+/// Creates the error for a failed heap verification
+fn corrupted(message: String) -> RuntimeError {
+    RuntimeError {
+        ty: RuntimeErrorTy::GcError,
+        message: format!("Heap verification failed: {}", message),
+        backtrace: None,
+    }
+}
+
+impl Gc {
+    /// Checks that the heap is consistent, returning an error naming the first object found broken
+    ///
+    /// Every object reachable from `roots` and the hand rooted objects must exist, and every value
+    /// referring to one must agree on its size. Every object must lie within the used part of its
+    /// generation without overlapping another, and all free memory must still be poisoned
+    pub fn verify_heap(&self, roots: &[AllocId]) -> Result<()> {
+        trace!("Verifying the heap");
+
+        self.verify_reachable(roots)?;
+        self.verify_layout()?;
+
+        if self.poisons() {
+            self.verify_poisoned()?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every object reachable from the roots exists and is referred to with its size
+    fn verify_reachable(&self, roots: &[AllocId]) -> Result<()> {
+        let mut visited = HashSet::with_capacity(self.allocations.len());
+        let mut queue: Vec<(Option<AllocId>, AllocId)> = self
+            .roots
+            .iter()
+            .chain(roots)
+            .map(|root| (None, *root))
+            .collect();
+        let mut children = Vec::new();
+
+        while let Some((parent, id)) = queue.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+
+            let (ptr, value) = self.allocations.get(&id).ok_or_else(|| match parent {
+                Some(parent) => corrupted(format!(
+                    "Object {} refers to object {}, which doesn't exist",
+                    parent, id
+                )),
+                None => corrupted(format!("The root {} doesn't exist", id)),
+            })?;
+
+            if value.holds_values && value.size % mem::size_of::<RuntimeValue>() != 0 {
+                return Err(corrupted(format!(
+                    "Object {} holds values, but its {} bytes aren't a whole number of them",
+                    id, value.size
+                )));
+            }
+
+            for member in value.values(*ptr) {
+                if let (Some(child), Some(size)) = (member.heap_id(), member.heap_size()) {
+                    match self.allocations.get(&child) {
+                        Some((_ptr, child_value)) if child_value.size != size => {
+                            return Err(corrupted(format!(
+                                "Object {} refers to object {} as {} bytes, but it's {} bytes",
+                                id, child, size, child_value.size
+                            )));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            value.trace(*ptr, &mut children);
+            queue.extend(children.drain(..).map(|child| (Some(id), child)));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every object is in exactly one generation, and that the objects of each
+    /// generation exactly fill its used part without overlapping
+    fn verify_layout(&self) -> Result<()> {
+        let mut tracked = HashSet::with_capacity(self.allocations.len());
+
+        self.verify_generation(
+            Generation::Young,
+            self.nursery.side(),
+            self.nursery.usage(),
+            self.nursery.objects.iter().copied(),
+            &mut tracked,
+        )?;
+        self.verify_generation(
+            Generation::Old,
+            self.old.start(),
+            self.old.usage(),
+            self.old.objects(),
+            &mut tracked,
+        )?;
+
+        if let Some(id) = self.allocations.keys().find(|id| !tracked.contains(*id)) {
+            return Err(corrupted(format!("Object {} isn't in any generation", id)));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the objects of a generation that starts at `start` and has `usage` bytes in use
+    fn verify_generation(
+        &self,
+        generation: Generation,
+        start: HeapPointer,
+        usage: usize,
+        objects: impl Iterator<Item = AllocId>,
+        tracked: &mut HashSet<AllocId>,
+    ) -> Result<()> {
+        let end = offset(start, usage);
+        let mut layout = Vec::new();
+
+        for id in objects {
+            if !tracked.insert(id) {
+                return Err(corrupted(format!(
+                    "Object {} is tracked more than once",
+                    id
+                )));
+            }
+
+            let (ptr, value) = self.allocations.get(&id).ok_or_else(|| {
+                corrupted(format!(
+                    "The {:?} generation holds object {}, which doesn't exist",
+                    generation, id
+                ))
+            })?;
+
+            if value.generation != generation {
+                return Err(corrupted(format!(
+                    "Object {} is held by the {:?} generation, but thinks it's in the {:?} generation",
+                    id, generation, value.generation
+                )));
+            }
+
+            if (**ptr as usize) < *start as usize
+                || *offset(*ptr, value.size) as usize > *end as usize
+            {
+                return Err(corrupted(format!(
+                    "Object {} ({} bytes at {:p}) lies outside the used part of the {:?} generation, {:p} to {:p}",
+                    id, value.size, ptr, generation, start, end
+                )));
+            }
+
+            layout.push((*ptr, value.size, id));
+        }
+
+        layout.sort_unstable_by_key(|(ptr, _size, _id)| **ptr as usize);
+        for pair in layout.windows(2) {
+            let ((ptr, size, id), (next, _next_size, next_id)) = (pair[0], pair[1]);
+
+            if *offset(ptr, size) as usize > *next as usize {
+                return Err(corrupted(format!(
+                    "Object {} ({} bytes at {:p}) overlaps object {} at {:p}",
+                    id, size, ptr, next_id, next
+                )));
+            }
+        }
+
+        let used: usize = layout.iter().map(|(_ptr, size, _id)| size).sum();
+        if used != usage {
+            return Err(corrupted(format!(
+                "The {:?} generation has {} bytes in use, but its objects only take up {} bytes",
+                generation, usage, used
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that nothing has written to the heap's free memory since it was poisoned
+    fn verify_poisoned(&self) -> Result<()> {
+        let free = [
+            (
+                "the nursery",
+                offset(self.nursery.side(), self.nursery.usage()),
+                self.nursery.size - self.nursery.usage(),
+            ),
+            (
+                "the nursery's from-space",
+                self.nursery.other_side(),
+                self.nursery.size,
+            ),
+            (
+                "the old generation",
+                self.old.latest,
+                self.old.size - self.old.usage(),
+            ),
+        ];
+
+        for (name, start, len) in &free {
+            // Safety: Each range is the free part of a heap region
+            let bytes = unsafe { slice::from_raw_parts(**start, *len) };
+
+            if let Some(index) = bytes.iter().position(|byte| *byte != POISON) {
+                return Err(corrupted(format!(
+                    "Free memory at {:p} in {} was written to after being freed",
+                    offset(*start, index),
+                    name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{Index, Result, ReturnFrame, RuntimeError, RuntimeErrorTy, RuntimeValue, Vm};
+use crate::{
+    Collectable, Index, Result, ReturnFrame, RuntimeError, RuntimeErrorTy, RuntimeValue, Vm,
+    MAX_OBJECT_MEMBERS, MAX_SPILL_SLOTS, NUMBER_CALLER_REGISTERS, NUMBER_REGISTERS,
+};
+use std::cmp::Ordering;
 
 pub fn load(mut vm: &mut Vm, val: RuntimeValue, reg: u8) -> Result<()> {
     trace!("Loading val into {}", reg);
@@ -50,6 +54,136 @@ pub fn mov(vm: &mut Vm, target: u8, source: u8) -> Result<()> {
     Ok(())
 }
 
+pub fn push(vm: &mut Vm, reg: u8) -> Result<()> {
+    trace!("Pushing {} onto the stack", reg);
+
+    vm.stack.push(vm.registers[reg as usize].clone());
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn pop(vm: &mut Vm, reg: u8) -> Result<()> {
+    trace!("Popping the stack into {}", reg);
+
+    vm.registers[reg as usize] = if let Some(value) = vm.stack.pop() {
+        value
+    } else {
+        return Err(RuntimeError {
+            ty: RuntimeErrorTy::EmptyStack,
+            message: "Attempted to pop from an empty stack".to_string(),
+            backtrace: None,
+        });
+    };
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn spill(vm: &mut Vm, reg: u8, slot: u32) -> Result<()> {
+    trace!("Spilling {} into slot {}", reg, slot);
+
+    let slot = slot as usize;
+    if slot >= MAX_SPILL_SLOTS {
+        return Err(RuntimeError {
+            ty: RuntimeErrorTy::BytecodeError,
+            message: format!(
+                "Attempted to spill into the stack slot {}, but there are only {} stack slots",
+                slot, MAX_SPILL_SLOTS
+            ),
+            backtrace: None,
+        });
+    }
+
+    if slot >= vm.spills.len() {
+        vm.spills.resize_with(slot + 1, || RuntimeValue::None);
+    }
+
+    vm.spills[slot] = vm.registers[reg as usize].clone();
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn reload(vm: &mut Vm, slot: u32, reg: u8) -> Result<()> {
+    trace!("Reloading slot {} into {}", slot, reg);
+
+    vm.registers[reg as usize] = if let Some(value) = vm.spills.get(slot as usize) {
+        value.clone()
+    } else {
+        return Err(RuntimeError {
+            ty: RuntimeErrorTy::MissingValue,
+            message: format!("Attempted to reload the empty stack slot {}", slot),
+            backtrace: None,
+        });
+    };
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn new_object(vm: &mut Vm, reg: u8, members: u32) -> Result<()> {
+    trace!("Allocating an object with {} members into {}", members, reg);
+
+    if members as usize > MAX_OBJECT_MEMBERS {
+        return Err(RuntimeError {
+            ty: RuntimeErrorTy::BytecodeError,
+            message: format!(
+                "Attempted to allocate an object with {} members, but objects can only have {}",
+                members, MAX_OBJECT_MEMBERS
+            ),
+            backtrace: None,
+        });
+    }
+
+    let object = vec![RuntimeValue::Null; members as usize].alloc(&mut vm.gc)?;
+
+    vm.registers[reg as usize] = RuntimeValue::GcObject(object);
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn get_member(vm: &mut Vm, output: u8, object: u8, member: u32) -> Result<()> {
+    trace!("Loading member {} of {} into {}", member, object, output);
+
+    vm.registers[output as usize] = match &vm.registers[object as usize] {
+        RuntimeValue::GcObject(object) => object.member(&vm.gc, member as usize)?,
+        value => {
+            return Err(RuntimeError {
+                ty: RuntimeErrorTy::IncompatibleTypes,
+                message: format!("Values of type '{}' have no members", value.name()),
+                backtrace: None,
+            });
+        }
+    };
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn set_member(vm: &mut Vm, object: u8, value: u8, member: u32) -> Result<()> {
+    trace!("Storing {} into member {} of {}", value, member, object);
+
+    match &vm.registers[object as usize] {
+        RuntimeValue::GcObject(object) => object.set_member(
+            &mut vm.gc,
+            member as usize,
+            vm.registers[value as usize].clone(),
+        )?,
+        value => {
+            return Err(RuntimeError {
+                ty: RuntimeErrorTy::IncompatibleTypes,
+                message: format!("Values of type '{}' have no members", value.name()),
+                backtrace: None,
+            });
+        }
+    }
+    vm.index += Index(1);
+
+    Ok(())
+}
+
 pub fn add(mut vm: &mut Vm, left: u8, right: u8) -> Result<()> {
     trace!("Adding registers {} and {}", left, right);
 
@@ -82,7 +216,83 @@ pub fn mult(mut vm: &mut Vm, left: u8, right: u8) -> Result<()> {
 pub fn div(mut vm: &mut Vm, left: u8, right: u8) -> Result<()> {
     vm.prev_op = vm.registers[left as usize]
         .clone()
-        .div_upflowing(vm.registers[right as usize].clone(), &mut vm.gc)?;
+        .div_or_zero(vm.registers[right as usize].clone(), &mut vm.gc)?;
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn fallible_add(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_op = vm.registers[left as usize]
+        .clone()
+        .add_checked(vm.registers[right as usize].clone(), &mut vm.gc)?
+        .unwrap_or(RuntimeValue::Null);
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn fallible_sub(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_op = vm.registers[left as usize]
+        .clone()
+        .sub_checked(vm.registers[right as usize].clone(), &mut vm.gc)?
+        .unwrap_or(RuntimeValue::Null);
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn fallible_mult(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_op = vm.registers[left as usize]
+        .clone()
+        .mult_checked(vm.registers[right as usize].clone(), &mut vm.gc)?
+        .unwrap_or(RuntimeValue::Null);
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn fallible_div(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_op = vm.registers[left as usize]
+        .clone()
+        .div_checked(vm.registers[right as usize].clone(), &mut vm.gc)?
+        .unwrap_or(RuntimeValue::Null);
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn panicking_add(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_op = vm.registers[left as usize]
+        .clone()
+        .add_panicking(vm.registers[right as usize].clone(), &mut vm.gc)?;
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn panicking_sub(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_op = vm.registers[left as usize]
+        .clone()
+        .sub_panicking(vm.registers[right as usize].clone(), &mut vm.gc)?;
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn panicking_mult(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_op = vm.registers[left as usize]
+        .clone()
+        .mult_panicking(vm.registers[right as usize].clone(), &mut vm.gc)?;
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn panicking_div(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_op = vm.registers[left as usize]
+        .clone()
+        .div_panicking(vm.registers[right as usize].clone(), &mut vm.gc)?;
     vm.index += Index(1);
 
     Ok(())
@@ -100,6 +310,7 @@ pub fn print(vm: &mut Vm, reg: u8) -> Result<()> {
         return Err(RuntimeError {
             ty: RuntimeErrorTy::StdoutError,
             message: "Failed to print to stdout".to_string(),
+            backtrace: None,
         });
     }
 
@@ -118,6 +329,7 @@ pub fn jump(vm: &mut Vm, index: i32) -> Result<()> {
             return Err(RuntimeError {
                 ty: RuntimeErrorTy::InvalidJump,
                 message: "Jump overflowed".to_string(),
+                backtrace: None,
             });
         }
 
@@ -199,24 +411,75 @@ pub fn not_eq(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
     Ok(())
 }
 
-pub fn greater_than(_vm: &mut Vm, _left: u8, _right: u8) -> Result<()> {
-    todo!()
+pub fn greater_than(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_comp = vm.registers[left as usize]
+        .clone()
+        .compare(vm.registers[right as usize].clone(), &vm.gc)?
+        == Some(Ordering::Greater);
+    vm.index += Index(1);
+
+    Ok(())
 }
 
-pub fn less_than(_vm: &mut Vm, _left: u8, _right: u8) -> Result<()> {
-    todo!()
+pub fn less_than(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_comp = vm.registers[left as usize]
+        .clone()
+        .compare(vm.registers[right as usize].clone(), &vm.gc)?
+        == Some(Ordering::Less);
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn greater_eq(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_comp = match vm.registers[left as usize]
+        .clone()
+        .compare(vm.registers[right as usize].clone(), &vm.gc)?
+    {
+        Some(Ordering::Greater | Ordering::Equal) => true,
+        Some(Ordering::Less) | None => false,
+    };
+    vm.index += Index(1);
+
+    Ok(())
+}
+
+pub fn less_eq(vm: &mut Vm, left: u8, right: u8) -> Result<()> {
+    vm.prev_comp = match vm.registers[left as usize]
+        .clone()
+        .compare(vm.registers[right as usize].clone(), &vm.gc)?
+    {
+        Some(Ordering::Less | Ordering::Equal) => true,
+        Some(Ordering::Greater) | None => false,
+    };
+    vm.index += Index(1);
+
+    Ok(())
 }
 
+/// Calls a function
+///
+/// The calling convention is as follows:
+/// - The caller places the function's arguments into the caller registers, starting at register 0.
+///   Any arguments that don't fit into the caller registers are pushed onto the value stack in
+///   reverse order, so that the callee pops them in order
+/// - The callee-saved registers and spilled values are stored in a [`ReturnFrame`] and cleared for
+///   the callee to use
+/// - The callee places its return values into the caller registers, starting at the `RETURN_REGISTER`,
+///   with any excess values pushed onto the value stack in reverse order
+/// - Once the callee returns, the callee-saved registers are restored while the caller registers
+///   are left as the callee left them
 pub fn func(mut vm: &mut Vm, func: u32) -> Result<()> {
     trace!("Jumping to function {}", func);
 
-    let mut registers: [RuntimeValue; crate::NUMBER_REGISTERS - 5] =
+    let mut registers: [RuntimeValue; NUMBER_REGISTERS - NUMBER_CALLER_REGISTERS] =
         array_init::array_init(|_| RuntimeValue::None);
 
-    vm.registers[5..].swap_with_slice(&mut registers[..]);
+    vm.registers[NUMBER_CALLER_REGISTERS..].swap_with_slice(&mut registers[..]);
 
     vm.return_stack.push(ReturnFrame {
         registers,
+        spills: std::mem::take(&mut vm.spills),
         index: vm.index + Index(1),
         function_index: vm.current_func,
         yield_point: None,
@@ -232,6 +495,7 @@ pub fn yield_generator(_vm: &mut Vm) -> Result<()> {
     todo!("Implement Generators/Coroutines")
 }
 
+/// Returns from a function, see [`func`] for the calling convention
 pub fn ret(mut vm: &mut Vm) -> Result<()> {
     trace!("Executing a Return");
 
@@ -241,7 +505,8 @@ pub fn ret(mut vm: &mut Vm) -> Result<()> {
 
         // Set the important stuff from the frame
         vm.index = frame.index;
-        vm.registers[5..].clone_from_slice(&frame.registers[..]);
+        vm.registers[NUMBER_CALLER_REGISTERS..].clone_from_slice(&frame.registers[..]);
+        vm.spills = frame.spills;
         vm.current_func = frame.function_index;
 
     // If there are no further stack frames, then return to main
@@ -257,7 +522,7 @@ pub fn ret(mut vm: &mut Vm) -> Result<()> {
 pub fn collect(vm: &mut Vm) -> Result<()> {
     trace!("Forcing a GC collect");
 
-    vm.gc.collect()?;
+    vm.collect()?;
     vm.index += Index(1);
 
     Ok(())
@@ -285,5 +550,6 @@ pub fn illegal(_vm: &mut Vm) -> Result<()> {
     return Err(RuntimeError {
         ty: RuntimeErrorTy::IllegalInstruction,
         message: "Illegal Instruction".to_string(),
+        backtrace: None,
     });
 }
@@ -1,4 +1,6 @@
-use super::{Register, RuntimeValue, Vm};
+use super::{Backtrace, DebugInfo, ErrorCode, Register, RuntimeValue, Vm};
+use codespan::{FileId, Files};
+use codespan_reporting::diagnostic::{Diagnostic, Label, Severity};
 
 pub mod functions;
 
@@ -14,13 +16,47 @@ pub struct RuntimeError {
     pub ty: RuntimeErrorTy,
     /// The error message
     pub message: String,
+    /// The functions being executed when the error occurred, attached by the [`Vm`]
+    pub backtrace: Option<Backtrace>,
 }
 
 impl RuntimeError {
     /// Prints the formatted error to stdout
     // TODO: Make this fancy, and more detailed
     pub fn emit(&self) {
-        println!("[Crunch Runtime Error: {:?}] {}", self.ty, self.message);
+        println!(
+            "[Crunch Runtime Error {}: {:?}] {}",
+            self.ty.code(),
+            self.ty,
+            self.message
+        );
+
+        if let Some(backtrace) = &self.backtrace {
+            println!("{}", backtrace);
+        }
+
+        println!(
+            "For more information about this error, try `crunch explain {}`",
+            self.ty.code()
+        );
+    }
+
+    /// Creates a diagnostic pointing at the source that raised the error, along with its
+    /// backtrace. Errors that weren't raised by an instruction built from the source have none
+    pub fn diagnostic(&self, debug: &DebugInfo, files: &Files, file: FileId) -> Option<Diagnostic> {
+        let backtrace = self.backtrace.as_ref()?;
+        let frame = backtrace.frames.first()?;
+        let span = debug.span(frame.function, frame.index)?;
+
+        Some(
+            Diagnostic::new(
+                Severity::Error,
+                format!("[Crunch Runtime Error: {:?}] {}", self.ty, self.message),
+                Label::new(file, span, "Raised here".to_string()),
+            )
+            .with_code(self.ty.code().code())
+            .with_notes(vec![backtrace.render(Some((debug, files, file)))]),
+        )
     }
 }
 
@@ -58,6 +94,16 @@ pub enum RuntimeErrorTy {
     IntegerOverflow,
     MissingSymbol,
     JitError,
+    /// A value was popped from an empty stack
+    EmptyStack,
+}
+
+impl RuntimeErrorTy {
+    /// The error code that explains this kind of error
+    #[must_use]
+    pub fn code(self) -> ErrorCode {
+        ErrorCode::from(self)
+    }
 }
 
 /// Instructions for the VM
@@ -70,11 +116,35 @@ pub enum Instruction {
     OpToReg(Register),
     Drop(Register),
     Move(Register, Register),
+    /// Push the value of a register onto the value stack
+    Push(Register),
+    /// Pop the top value of the value stack into a register
+    Pop(Register),
+    /// Store the value of a register into a stack slot of the current function
+    Spill(Register, u32),
+    /// Load the value of a stack slot of the current function into a register
+    Reload(u32, Register),
+    /// Allocate an object with the given number of members into a register, every member starts as `Null`
+    NewObject(Register, u32),
+    /// Load a member of the object in the second register into the first register
+    GetMember(Register, Register, u32),
+    /// Store the value of the second register into a member of the object in the first register
+    SetMember(Register, Register, u32),
 
     Add(Register, Register),
     Sub(Register, Register),
     Mult(Register, Register),
     Div(Register, Register),
+    /// Arithmetic that evaluates to `Null` if it overflows the width of its operands or divides by zero
+    FallibleAdd(Register, Register),
+    FallibleSub(Register, Register),
+    FallibleMult(Register, Register),
+    FallibleDiv(Register, Register),
+    /// Arithmetic that halts the program if it overflows the width of its operands or divides by zero
+    PanickingAdd(Register, Register),
+    PanickingSub(Register, Register),
+    PanickingMult(Register, Register),
+    PanickingDiv(Register, Register),
 
     Print(Register),
 
@@ -91,6 +161,8 @@ pub enum Instruction {
     NotEq(Register, Register),
     GreaterThan(Register, Register),
     LessThan(Register, Register),
+    GreaterEq(Register, Register),
+    LessEq(Register, Register),
 
     Func(u32),
     Yield,
@@ -117,14 +189,33 @@ impl Instruction {
         match self {
             Self::Load(val, reg) => functions::load(vm, val.clone(), **reg)?,
             Self::CompToReg(reg) => functions::comp_to_reg(vm, **reg)?,
-            Self::OpToReg(reg) => functions::comp_to_reg(vm, **reg)?,
+            Self::OpToReg(reg) => functions::op_to_reg(vm, **reg)?,
             Self::Drop(reg) => functions::drop(vm, **reg)?,
             Self::Move(target, source) => functions::mov(vm, **target, **source)?,
+            Self::Push(reg) => functions::push(vm, **reg)?,
+            Self::Pop(reg) => functions::pop(vm, **reg)?,
+            Self::Spill(reg, slot) => functions::spill(vm, **reg, *slot)?,
+            Self::Reload(slot, reg) => functions::reload(vm, *slot, **reg)?,
+            Self::NewObject(reg, members) => functions::new_object(vm, **reg, *members)?,
+            Self::GetMember(output, object, member) => {
+                functions::get_member(vm, **output, **object, *member)?
+            }
+            Self::SetMember(object, value, member) => {
+                functions::set_member(vm, **object, **value, *member)?
+            }
 
             Self::Add(left, right) => functions::add(vm, **left, **right)?,
             Self::Sub(left, right) => functions::sub(vm, **left, **right)?,
             Self::Mult(left, right) => functions::mult(vm, **left, **right)?,
             Self::Div(left, right) => functions::div(vm, **left, **right)?,
+            Self::FallibleAdd(left, right) => functions::fallible_add(vm, **left, **right)?,
+            Self::FallibleSub(left, right) => functions::fallible_sub(vm, **left, **right)?,
+            Self::FallibleMult(left, right) => functions::fallible_mult(vm, **left, **right)?,
+            Self::FallibleDiv(left, right) => functions::fallible_div(vm, **left, **right)?,
+            Self::PanickingAdd(left, right) => functions::panicking_add(vm, **left, **right)?,
+            Self::PanickingSub(left, right) => functions::panicking_sub(vm, **left, **right)?,
+            Self::PanickingMult(left, right) => functions::panicking_mult(vm, **left, **right)?,
+            Self::PanickingDiv(left, right) => functions::panicking_div(vm, **left, **right)?,
 
             Self::Print(reg) => functions::print(vm, **reg)?,
 
@@ -142,6 +233,8 @@ impl Instruction {
             Self::NotEq(left, right) => functions::not_eq(vm, **left, **right)?,
             Self::GreaterThan(left, right) => functions::greater_than(vm, **left, **right)?,
             Self::LessThan(left, right) => functions::less_than(vm, **left, **right)?,
+            Self::GreaterEq(left, right) => functions::greater_eq(vm, **left, **right)?,
+            Self::LessEq(left, right) => functions::less_eq(vm, **left, **right)?,
 
             Self::Func(func) => functions::func(vm, *func)?,
             Self::Yield => functions::yield_generator(vm)?,
@@ -166,11 +259,26 @@ impl Instruction {
             Self::OpToReg(_) => "opr",
             Self::Drop(_) => "drop",
             Self::Move(_, _) => "mov",
+            Self::Push(_) => "push",
+            Self::Pop(_) => "pop",
+            Self::Spill(_, _) => "spill",
+            Self::Reload(_, _) => "reload",
+            Self::NewObject(_, _) => "newobj",
+            Self::GetMember(_, _, _) => "getmem",
+            Self::SetMember(_, _, _) => "setmem",
 
             Self::Add(_, _) => "add",
             Self::Sub(_, _) => "sub",
             Self::Mult(_, _) => "mul",
             Self::Div(_, _) => "div",
+            Self::FallibleAdd(_, _) => "add?",
+            Self::FallibleSub(_, _) => "sub?",
+            Self::FallibleMult(_, _) => "mul?",
+            Self::FallibleDiv(_, _) => "div?",
+            Self::PanickingAdd(_, _) => "add!",
+            Self::PanickingSub(_, _) => "sub!",
+            Self::PanickingMult(_, _) => "mul!",
+            Self::PanickingDiv(_, _) => "div!",
 
             Self::Print(_) => "print",
 
@@ -187,6 +295,8 @@ impl Instruction {
             Self::NotEq(_, _) => "neq",
             Self::GreaterThan(_, _) => "grt",
             Self::LessThan(_, _) => "let",
+            Self::GreaterEq(_, _) => "greq",
+            Self::LessEq(_, _) => "leeq",
 
             Self::Func(_) => "call",
             Self::Yield => "yield",
@@ -199,6 +309,86 @@ impl Instruction {
             Self::NoOp => "nop",
         }
     }
+
+    /// The registers that the instruction operates on, in the same order as [`Instruction::registers_mut`]
+    #[must_use]
+    pub fn registers(&self) -> Vec<Register> {
+        self.clone()
+            .registers_mut()
+            .into_iter()
+            .map(|reg| *reg)
+            .collect()
+    }
+
+    /// The registers that the instruction operates on, a register that is written to always comes first
+    pub fn registers_mut(&mut self) -> Vec<&mut Register> {
+        match self {
+            Self::Load(_, reg)
+            | Self::CompToReg(reg)
+            | Self::OpToReg(reg)
+            | Self::Drop(reg)
+            | Self::Push(reg)
+            | Self::Pop(reg)
+            | Self::Spill(reg, _)
+            | Self::Reload(_, reg)
+            | Self::Print(reg)
+            | Self::Not(reg)
+            | Self::NewObject(reg, _) => vec![reg],
+
+            Self::Move(left, right)
+            | Self::Add(left, right)
+            | Self::Sub(left, right)
+            | Self::Mult(left, right)
+            | Self::Div(left, right)
+            | Self::FallibleAdd(left, right)
+            | Self::FallibleSub(left, right)
+            | Self::FallibleMult(left, right)
+            | Self::FallibleDiv(left, right)
+            | Self::PanickingAdd(left, right)
+            | Self::PanickingSub(left, right)
+            | Self::PanickingMult(left, right)
+            | Self::PanickingDiv(left, right)
+            | Self::And(left, right)
+            | Self::Or(left, right)
+            | Self::Xor(left, right)
+            | Self::Eq(left, right)
+            | Self::NotEq(left, right)
+            | Self::GreaterThan(left, right)
+            | Self::LessThan(left, right)
+            | Self::GreaterEq(left, right)
+            | Self::LessEq(left, right)
+            | Self::GetMember(left, right, _)
+            | Self::SetMember(left, right, _) => vec![left, right],
+
+            Self::Jump(_)
+            | Self::JumpComp(_)
+            | Self::JumpPoint(_)
+            | Self::Func(_)
+            | Self::Yield
+            | Self::Return
+            | Self::Collect
+            | Self::Halt
+            | Self::Illegal
+            | Self::NoOp => Vec::new(),
+        }
+    }
+
+    /// Whether the first register of the instruction is written to instead of read from
+    #[must_use]
+    pub fn writes_register(&self) -> bool {
+        match self {
+            Self::Load(_, _)
+            | Self::CompToReg(_)
+            | Self::OpToReg(_)
+            | Self::Move(_, _)
+            | Self::Pop(_)
+            | Self::Reload(_, _)
+            | Self::NewObject(_, _)
+            | Self::GetMember(_, _, _) => true,
+
+            _ => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -495,6 +685,49 @@ mod tests {
         assert_eq!(vm.index, 5.into());
     }
 
+    #[test]
+    fn stack_ops() {
+        let mut vm = Vm::new(
+            &crate::OptionBuilder::new("./stack_ops").build(),
+            Box::new(stdout()),
+        );
+
+        vm.registers[0] = RuntimeValue::I32(10);
+        vm.registers[1] = RuntimeValue::Bool(true);
+
+        Instruction::Push(0.into()).execute(&mut vm).unwrap();
+        Instruction::Push(1.into()).execute(&mut vm).unwrap();
+        assert_eq!(vm.stack.len(), 2);
+
+        Instruction::Pop(2.into()).execute(&mut vm).unwrap();
+        Instruction::Pop(3.into()).execute(&mut vm).unwrap();
+        assert!(vm.registers[2]
+            .clone()
+            .is_equal(RuntimeValue::Bool(true), &vm.gc)
+            .unwrap());
+        assert!(vm.registers[3]
+            .clone()
+            .is_equal(RuntimeValue::I32(10), &vm.gc)
+            .unwrap());
+
+        let pop = Instruction::Pop(0.into());
+        assert_eq!(
+            pop.execute(&mut vm).err().unwrap().ty,
+            RuntimeErrorTy::EmptyStack
+        );
+
+        Instruction::Spill(0.into(), 3).execute(&mut vm).unwrap();
+        assert_eq!(vm.spills.len(), 4);
+
+        // Untrusted slots can't grow the spills without a limit
+        let spill = Instruction::Spill(0.into(), u32::max_value());
+        assert_eq!(
+            spill.execute(&mut vm).err().unwrap().ty,
+            RuntimeErrorTy::BytecodeError
+        );
+        assert_eq!(vm.spills.len(), 4);
+    }
+
     #[test]
     fn eq_ops() {
         let mut vm = Vm::new(
@@ -519,11 +752,255 @@ mod tests {
         greater_than.execute(&mut vm).unwrap();
         assert_eq!(vm.prev_comp, true);
 
+        let greater_eq = Instruction::GreaterEq(0.into(), 1.into());
+        greater_eq.execute(&mut vm).unwrap();
+        assert_eq!(vm.prev_comp, true);
+
         vm.registers[0] = RuntimeValue::I32(0);
 
         let less_than = Instruction::LessThan(0.into(), 1.into());
         less_than.execute(&mut vm).unwrap();
         assert_eq!(vm.prev_comp, true);
+
+        let less_eq = Instruction::LessEq(0.into(), 1.into());
+        less_eq.execute(&mut vm).unwrap();
+        assert_eq!(vm.prev_comp, true);
+
+        vm.registers[0] = RuntimeValue::Str("apple");
+        vm.registers[1] = RuntimeValue::Str("banana");
+        less_than.execute(&mut vm).unwrap();
+        assert_eq!(vm.prev_comp, true);
+
+        vm.registers[0] = RuntimeValue::Char('b');
+        vm.registers[1] = RuntimeValue::Char('a');
+        greater_than.execute(&mut vm).unwrap();
+        assert_eq!(vm.prev_comp, true);
+
+        vm.registers[0] = RuntimeValue::F64(std::f64::NAN);
+        vm.registers[1] = RuntimeValue::F64(1.0);
+        greater_eq.execute(&mut vm).unwrap();
+        assert_eq!(vm.prev_comp, false);
+        less_eq.execute(&mut vm).unwrap();
+        assert_eq!(vm.prev_comp, false);
+
+        vm.registers[1] = RuntimeValue::Bool(true);
+        assert_eq!(
+            less_than.execute(&mut vm).err().unwrap().ty,
+            RuntimeErrorTy::IncompatibleTypes
+        );
+    }
+
+    #[test]
+    fn float_ops() {
+        let mut vm = Vm::new(
+            &crate::OptionBuilder::new("./float_ops").build(),
+            Box::new(stdout()),
+        );
+
+        vm.registers[0] = RuntimeValue::F64(7.5);
+        vm.registers[1] = RuntimeValue::F64(2.5);
+
+        let ops = [
+            (Instruction::Add(0.into(), 1.into()), 10.0),
+            (Instruction::Sub(0.into(), 1.into()), 5.0),
+            (Instruction::Mult(0.into(), 1.into()), 18.75),
+            (Instruction::Div(0.into(), 1.into()), 3.0),
+        ];
+        for (op, expected) in ops.iter() {
+            op.execute(&mut vm).unwrap();
+            assert!(vm
+                .prev_op
+                .clone()
+                .is_equal(RuntimeValue::F64(*expected), &vm.gc)
+                .unwrap());
+        }
+
+        // Ints are promoted to the float's type
+        vm.registers[0] = RuntimeValue::I32(2);
+        vm.registers[1] = RuntimeValue::F32(0.5);
+
+        let add = Instruction::Add(0.into(), 1.into());
+        add.execute(&mut vm).unwrap();
+        assert!(vm
+            .prev_op
+            .clone()
+            .is_equal(RuntimeValue::F32(2.5), &vm.gc)
+            .unwrap());
+        assert!(matches!(vm.prev_op, RuntimeValue::F32(_)));
+
+        // And `F32`s are promoted to `F64`s
+        vm.registers[0] = RuntimeValue::F64(1.0);
+
+        let less_than = Instruction::LessThan(1.into(), 0.into());
+        less_than.execute(&mut vm).unwrap();
+        assert_eq!(vm.prev_comp, true);
+        add.execute(&mut vm).unwrap();
+        assert!(vm
+            .prev_op
+            .clone()
+            .is_equal(RuntimeValue::F64(1.5), &vm.gc)
+            .unwrap());
+        assert!(matches!(vm.prev_op, RuntimeValue::F64(_)));
+
+        let not = Instruction::Not(0.into());
+        assert_eq!(
+            not.execute(&mut vm).err().unwrap().ty,
+            RuntimeErrorTy::IncompatibleTypes
+        );
+    }
+
+    #[test]
+    fn big_int_ops() {
+        let mut vm = Vm::new(
+            &crate::OptionBuilder::new("./big_int_ops").build(),
+            Box::new(stdout()),
+        );
+
+        // Overflowing an `I128` promotes to a `GcInt`
+        vm.registers[0] = RuntimeValue::I128(i128::max_value());
+        vm.registers[1] = RuntimeValue::I32(1);
+
+        let add = Instruction::Add(0.into(), 1.into());
+        add.execute(&mut vm).unwrap();
+        assert!(matches!(vm.prev_op, RuntimeValue::GcInt(_)));
+        assert_eq!(
+            vm.prev_op.to_string(&vm.gc).unwrap(),
+            "170141183460469231731687303715884105728"
+        );
+
+        // And results that fit are demoted back to a fixed width
+        vm.registers[0] = vm.prev_op.clone();
+        let sub = Instruction::Sub(0.into(), 1.into());
+        sub.execute(&mut vm).unwrap();
+        assert!(vm
+            .prev_op
+            .clone()
+            .is_equal(RuntimeValue::I128(i128::max_value()), &vm.gc)
+            .unwrap());
+        assert!(matches!(vm.prev_op, RuntimeValue::I128(_)));
+
+        // Unsigned integers can't go negative
+        vm.registers[0] = RuntimeValue::U32(1);
+        vm.registers[1] = RuntimeValue::U32(2);
+        assert_eq!(
+            sub.execute(&mut vm).err().unwrap().ty,
+            RuntimeErrorTy::IntegerOverflow
+        );
+
+        // Dividing an integer by zero results in zero
+        vm.registers[0] = RuntimeValue::I32(1);
+        vm.registers[1] = RuntimeValue::I32(0);
+        let div = Instruction::Div(0.into(), 1.into());
+        div.execute(&mut vm).unwrap();
+        assert!(matches!(vm.prev_op, RuntimeValue::I32(0)));
+    }
+
+    #[test]
+    fn fallible_ops() {
+        let mut vm = Vm::new(
+            &crate::OptionBuilder::new("./fallible_ops").build(),
+            Box::new(stdout()),
+        );
+
+        vm.registers[0] = RuntimeValue::I32(i32::max_value());
+        vm.registers[1] = RuntimeValue::I32(1);
+        Instruction::FallibleAdd(0.into(), 1.into())
+            .execute(&mut vm)
+            .unwrap();
+        assert_eq!(vm.prev_op, RuntimeValue::Null);
+
+        Instruction::FallibleSub(0.into(), 1.into())
+            .execute(&mut vm)
+            .unwrap();
+        assert!(matches!(vm.prev_op, RuntimeValue::I32(value) if value == i32::max_value() - 1));
+
+        vm.registers[0] = RuntimeValue::U64(u64::max_value());
+        vm.registers[1] = RuntimeValue::U64(2);
+        Instruction::FallibleMult(0.into(), 1.into())
+            .execute(&mut vm)
+            .unwrap();
+        assert_eq!(vm.prev_op, RuntimeValue::Null);
+
+        vm.registers[0] = RuntimeValue::I32(10);
+        vm.registers[1] = RuntimeValue::I32(0);
+        Instruction::FallibleDiv(0.into(), 1.into())
+            .execute(&mut vm)
+            .unwrap();
+        assert_eq!(vm.prev_op, RuntimeValue::Null);
+
+        vm.registers[1] = RuntimeValue::I32(5);
+        Instruction::FallibleDiv(0.into(), 1.into())
+            .execute(&mut vm)
+            .unwrap();
+        assert!(matches!(vm.prev_op, RuntimeValue::I32(2)));
+
+        vm.registers[0] = RuntimeValue::F64(1.0);
+        vm.registers[1] = RuntimeValue::F64(0.0);
+        Instruction::FallibleDiv(0.into(), 1.into())
+            .execute(&mut vm)
+            .unwrap();
+        assert_eq!(vm.prev_op, RuntimeValue::Null);
+    }
+
+    #[test]
+    fn panicking_ops() {
+        let mut vm = Vm::new(
+            &crate::OptionBuilder::new("./panicking_ops").build(),
+            Box::new(stdout()),
+        );
+
+        vm.registers[0] = RuntimeValue::I32(i32::max_value());
+        vm.registers[1] = RuntimeValue::I32(1);
+        assert_eq!(
+            Instruction::PanickingAdd(0.into(), 1.into())
+                .execute(&mut vm)
+                .err()
+                .unwrap()
+                .ty,
+            RuntimeErrorTy::IntegerOverflow
+        );
+
+        Instruction::PanickingSub(0.into(), 1.into())
+            .execute(&mut vm)
+            .unwrap();
+        assert!(matches!(vm.prev_op, RuntimeValue::I32(value) if value == i32::max_value() - 1));
+
+        vm.registers[1] = RuntimeValue::I32(0);
+        assert_eq!(
+            Instruction::PanickingDiv(0.into(), 1.into())
+                .execute(&mut vm)
+                .err()
+                .unwrap()
+                .ty,
+            RuntimeErrorTy::DivideByZero
+        );
+    }
+
+    #[test]
+    fn object_ops() {
+        let mut vm = Vm::new(
+            &crate::OptionBuilder::new("./object_ops").build(),
+            Box::new(stdout()),
+        );
+
+        vm.registers[1] = RuntimeValue::I32(10);
+        Instruction::NewObject(0.into(), 2)
+            .execute(&mut vm)
+            .unwrap();
+        Instruction::SetMember(0.into(), 1.into(), 1)
+            .execute(&mut vm)
+            .unwrap();
+        Instruction::GetMember(2.into(), 0.into(), 1)
+            .execute(&mut vm)
+            .unwrap();
+        assert!(matches!(vm.registers[2], RuntimeValue::I32(10)));
+
+        // Untrusted member counts can't allocate without a limit
+        let new_object = Instruction::NewObject(0.into(), u32::max_value());
+        assert_eq!(
+            new_object.execute(&mut vm).err().unwrap().ty,
+            RuntimeErrorTy::BytecodeError
+        );
     }
 
     #[test]
@@ -645,6 +1122,48 @@ mod tests {
                 assert!(vm.prev_op.is_equal(RuntimeValue::I32(!int), &vm.gc).unwrap());
             }
 
+            #[test]
+            fn big_multiply(left in any::<i128>(), right in any::<i128>()) {
+                use num_bigint::BigInt;
+
+                let mut vm = Vm::new(
+                    &crate::OptionBuilder::new("./big_multiply").build(),
+                    Box::new(stdout()),
+                );
+
+                vm.registers[0] = RuntimeValue::I128(left);
+                vm.registers[1] = RuntimeValue::I128(right);
+
+                let mult = Instruction::Mult(0.into(), 1.into());
+                mult.execute(&mut vm).unwrap();
+
+                let expected = RuntimeValue::from_big_int(BigInt::from(left) * BigInt::from(right), &mut vm.gc).unwrap();
+                prop_assert!(vm.prev_op.clone().is_equal(expected, &vm.gc).unwrap());
+            }
+
+            #[test]
+            fn comparisons(left in any::<i64>(), right in any::<i64>()) {
+                let mut vm = Vm::new(
+                    &crate::OptionBuilder::new("./comparison_ops").build(),
+                    Box::new(stdout()),
+                );
+
+                vm.registers[0] = RuntimeValue::I64(left);
+                vm.registers[1] = RuntimeValue::I64(right);
+
+                let comparisons = [
+                    (Instruction::GreaterThan(0.into(), 1.into()), left > right),
+                    (Instruction::LessThan(0.into(), 1.into()), left < right),
+                    (Instruction::GreaterEq(0.into(), 1.into()), left >= right),
+                    (Instruction::LessEq(0.into(), 1.into()), left <= right),
+                ];
+
+                for (comparison, expected) in comparisons.iter() {
+                    comparison.execute(&mut vm).unwrap();
+                    prop_assert_eq!(vm.prev_comp, *expected);
+                }
+            }
+
             #[test]
             fn add(left in 0_i32..i32::max_value(), right in 0_i32..i32::max_value()) {
                 let mut vm = Vm::new(
@@ -1,9 +1,9 @@
 use crate::{
-    code_builder::{CodeBuilder, FunctionContext},
+    code_builder::{CodeBuilder, FunctionContext, MangleStatus, TypeContext, VirtualRegister},
     instruction::Result,
     instruction::{RuntimeError, RuntimeErrorTy},
     parser::*,
-    Instruction, Options, Register, RuntimeValue,
+    DebugInfo, Instruction, Options, RuntimeValue, NUMBER_CALLER_REGISTERS, RETURN_REGISTER,
 };
 use std::{collections::HashMap, path::PathBuf};
 use string_interner::{StringInterner, Sym};
@@ -29,6 +29,10 @@ pub struct Interpreter {
     pub options: InterpOptions,
     pub func_index: usize,
     pub builder: CodeBuilder,
+    /// The declared return types of every known function
+    pub returns: HashMap<Sym, Type>,
+    /// The types of the current function's variables, where they're known
+    locals: HashMap<Sym, Type>,
 }
 
 impl Interpreter {
@@ -40,6 +44,8 @@ impl Interpreter {
             options: InterpOptions::from(options),
             func_index: 0,
             builder: CodeBuilder::new(),
+            returns: HashMap::new(),
+            locals: HashMap::new(),
         }
     }
 
@@ -51,47 +57,146 @@ impl Interpreter {
             options: InterpOptions::from(options),
             func_index: 0,
             builder: CodeBuilder::from_interner(interner),
+            returns: HashMap::new(),
+            locals: HashMap::new(),
         }
     }
 
-    /// Interpret the contained ast and return the instructions
-    pub fn interpret<'a>(mut self, ast: Vec<Program>) -> Result<Vec<Vec<Instruction>>> {
+    /// Interpret the contained ast and return the instructions, along with the debug info that
+    /// maps them back to the ast
+    pub fn interpret<'a>(
+        mut self,
+        ast: Vec<Program>,
+    ) -> Result<(Vec<Vec<Instruction>>, DebugInfo)> {
         self.interpret_module(ast)?;
-        let functions = self.builder.build()?;
+        let (functions, debug) = self.builder.build()?;
 
         trace!("Interp Output: {:?}", functions);
 
-        Ok(functions)
+        Ok((functions, debug))
     }
 
     fn interpret_module<'a>(&mut self, mut ast: Vec<Program>) -> Result<()> {
-        while let Some(node) = ast.pop() {
+        // Collect the return types of all functions and the layouts of all types beforehand,
+        // since calls need to know how many values their callee returns and types can be used
+        // before they're declared
+        for node in &mut ast {
             match node {
                 Program::FunctionDecl(func) => {
-                    // Interpret the function
-                    let (name, index) = self.interp_func(func)?;
+                    // Functions with generics are instances of a generic function, which are
+                    // named after the concrete types they were instantiated with
+                    if !func.generics.is_empty() {
+                        func.name = self.builder.instance(func.name, &func.generics);
+                    }
 
-                    // Will contain the newly created function
-                    let mut func = Vec::new();
+                    self.returns.insert(func.name, func.returns.clone());
+                }
+                Program::TypeDecl(decl) => self.declare_type(decl),
+                Program::Import(_) => {}
+            }
+        }
 
-                    // Switch the current function and the function just created
-                    std::mem::swap(&mut self.current_function, &mut func);
+        while let Some(node) = ast.pop() {
+            match node {
+                Program::FunctionDecl(func) => self.interpret_function(func)?,
+                Program::TypeDecl(decl) => self.interpret_type(decl)?,
+                Program::Import(import) => self.interpret_import(import)?,
+            }
+        }
 
-                    // Insert the function
-                    self.functions.insert(name, (func, index));
-                }
+        Ok(())
+    }
 
-                Program::Import(import) => {
-                    self.interpret_import(import)?;
-                }
+    fn interpret_function(&mut self, func: FunctionDecl) -> Result<()> {
+        // Interpret the function
+        let (name, index) = self.interp_func(func)?;
+
+        // Will contain the newly created function
+        let mut func = Vec::new();
+
+        // Switch the current function and the function just created
+        std::mem::swap(&mut self.current_function, &mut func);
+
+        // Insert the function
+        self.functions.insert(name, (func, index));
+
+        Ok(())
+    }
+
+    /// Declares the members of a type and the mangled names of its methods
+    fn declare_type(&mut self, decl: &TypeDecl) {
+        let mut ty = TypeContext::new(decl.name);
+        ty.members = decl
+            .members
+            .iter()
+            .map(|(name, member)| (*name, self.resolve_self(member.clone(), decl.name)))
+            .collect();
+
+        let type_name = self.resolve(decl.name);
+        for method in &decl.methods {
+            let method_name = self.resolve(method.name);
+            let mangled = self
+                .builder
+                .mangle(&[&type_name, &method_name], MangleStatus::Function);
+            let mangled = self.builder.intern(mangled);
+            self.builder
+                .name_function(mangled, format!("{}.{}", type_name, method_name));
+
+            let takes_self = method
+                .arguments
+                .first()
+                .map_or(false, |(name, _ty)| self.resolve(*name) == "self");
+
+            ty.methods.insert(method.name, (mangled, takes_self));
+            self.returns.insert(
+                mangled,
+                self.resolve_self(method.returns.clone(), decl.name),
+            );
+        }
+
+        self.builder.declare_type(ty);
+    }
 
-                _ => todo!("Implement all Program-level nodes"),
+    /// Interprets the methods of a type as functions under their mangled names
+    fn interpret_type(&mut self, decl: TypeDecl) -> Result<()> {
+        for mut method in decl.methods {
+            method.name = self.builder.get_type(decl.name)?.methods[&method.name].0;
+            for (_name, ty) in &mut method.arguments {
+                *ty = self.resolve_self(ty.clone(), decl.name);
             }
+            method.returns = self.resolve_self(method.returns, decl.name);
+
+            self.interpret_function(method)?;
         }
 
         Ok(())
     }
 
+    /// Replaces `Self` with the type it refers to
+    fn resolve_self(&self, ty: Type, name: Sym) -> Type {
+        match ty {
+            Type::Custom(sym) if Some(sym) == self.builder.interner.get("Self") => {
+                Type::Custom(name)
+            }
+            Type::Tuple(types) => Type::Tuple(
+                types
+                    .into_iter()
+                    .map(|ty| self.resolve_self(ty, name))
+                    .collect(),
+            ),
+            Type::Nullable(ty) => Type::Nullable(Box::new(self.resolve_self(*ty, name))),
+            ty => ty,
+        }
+    }
+
+    fn resolve(&self, sym: Sym) -> String {
+        self.builder
+            .interner
+            .resolve(sym)
+            .unwrap_or("<unknown>")
+            .to_string()
+    }
+
     fn interpret_import<'a>(&mut self, import: Import) -> Result<()> {
         match import.source {
             ImportSource::File(relative_path) => {
@@ -114,6 +219,7 @@ impl Interpreter {
                                     "The file '{}' does not exist",
                                     relative_path.display()
                                 ),
+                                backtrace: None,
                             });
                         }
                     };
@@ -126,6 +232,7 @@ impl Interpreter {
                         return Err(RuntimeError {
                             ty: RuntimeErrorTy::FileError,
                             message: format!("Cannot read the file '{}'", relative_path.display()),
+                            backtrace: None,
                         });
                     }
 
@@ -143,6 +250,7 @@ impl Interpreter {
                         return Err(RuntimeError {
                             ty: RuntimeErrorTy::CompilationError,
                             message: format!("The dependency '{}' failed to compile", file_name),
+                            backtrace: None,
                         });
                     }
                 };
@@ -169,10 +277,10 @@ impl Interpreter {
         ctx: &mut FunctionContext,
         binding_val: BindingVal<'a>,
         binding_name: impl Into<Option<Sym>>,
-    ) -> Result<Register> {
+    ) -> Result<VirtualRegister> {
         match binding_val {
             BindingVal::Literal(literal) => {
-                let addr = ctx.reserve_reg(binding_name)?;
+                let addr = ctx.reserve_reg(binding_name);
 
                 ctx.inst_load(addr, literal.val.into());
 
@@ -184,7 +292,7 @@ impl Interpreter {
                     let (left, right): (RuntimeValue, RuntimeValue) =
                         (left.val.into(), right.val.into());
                     let (left_reg, right_reg) =
-                        (ctx.reserve_reg(binding_name)?, ctx.reserve_reg(None)?);
+                        (ctx.reserve_reg(binding_name), ctx.reserve_reg(None));
 
                     // TODO: Compile-time evaluation
                     ctx.inst_load(left_reg, left).inst_load(right_reg, right);
@@ -201,14 +309,14 @@ impl Interpreter {
                 }
 
                 (BinOpSide::Literal(left), BinOpSide::Variable(right)) => {
-                    let left_reg = ctx.reserve_reg(binding_name)?;
+                    let left_reg = ctx.reserve_reg(binding_name);
                     ctx.inst_load(left_reg, left.val.into());
 
                     let right_ident = builder.intern(&*right.name);
                     let (right_reg, faulted) = if let Ok(reg) = ctx.get_cached_reg(right_ident) {
                         (reg, false)
                     } else if self.options.fault_tolerant {
-                        let reg = ctx.reserve_reg(right_ident)?;
+                        let reg = ctx.reserve_reg(right_ident);
 
                         ctx.inst_load(reg, RuntimeValue::None);
 
@@ -218,6 +326,7 @@ impl Interpreter {
                         return Err(RuntimeError {
                             ty: RuntimeErrorTy::NullVar,
                             message: format!("The variable {:?} does not exist", &*right.name),
+                            backtrace: None,
                         });
                     };
 
@@ -228,7 +337,7 @@ impl Interpreter {
                             ctx.inst_drop(left_reg).free_reg(right_reg);
                         }
                     } else {
-                        let left_reg = ctx.reserve_reg(right_ident)?;
+                        let left_reg = ctx.reserve_reg(right_ident);
 
                         match bin_op.op {
                             Op::Add => ctx.inst_add(left_reg, right_reg),
@@ -249,7 +358,7 @@ impl Interpreter {
                     let left_reg = if let Ok(reg) = ctx.get_cached_reg(left_ident) {
                         reg
                     } else if self.options.fault_tolerant {
-                        let reg = ctx.reserve_reg(left_ident)?;
+                        let reg = ctx.reserve_reg(left_ident);
                         ctx.inst_load(reg, RuntimeValue::None);
 
                         reg
@@ -258,15 +367,16 @@ impl Interpreter {
                         return Err(RuntimeError {
                             ty: RuntimeErrorTy::NullVar,
                             message: format!("The variable {:?} does not exist", &*left.name),
+                            backtrace: None,
                         });
                     };
 
-                    let right_reg = ctx.reserve_reg(None)?;
+                    let right_reg = ctx.reserve_reg(None);
 
                     ctx.inst_load(right_reg, right.val.into())
                         .inst_add(left_reg, right_reg);
 
-                    let output = ctx.reserve_reg(binding_name)?;
+                    let output = ctx.reserve_reg(binding_name);
                     ctx.inst_drop(right_reg).inst_op_to_reg(output);
 
                     Ok(output)
@@ -284,10 +394,21 @@ impl Interpreter {
         std::mem::swap(&mut builder, &mut self.builder);
 
         let func_name = func.name;
+        self.locals.clear();
         builder.function(func_name, |builder, ctx| {
-            // TODO: Accept more than 5 arguments
-            for (arg_name, _arg_type) in func.arguments.into_iter().take(5) {
-                ctx.reserve_caller_reg(arg_name)?;
+            ctx.set_span(func.span);
+
+            // Move the arguments out of the caller registers and off of the stack so that they
+            // survive any function calls
+            for (index, (arg_name, arg_type)) in func.arguments.into_iter().enumerate() {
+                let arg = ctx.reserve_reg(arg_name);
+                self.locals.insert(arg_name, arg_type);
+
+                if index < NUMBER_CALLER_REGISTERS {
+                    ctx.inst_mov(arg, index as u8);
+                } else {
+                    ctx.inst_pop(arg);
+                }
             }
 
             // For each expression in the function, evaluate it into instructions
@@ -319,10 +440,23 @@ impl Interpreter {
         builder: &mut CodeBuilder,
         ctx: &mut FunctionContext,
         expr: Expr,
-    ) -> Result<Register> {
+    ) -> Result<VirtualRegister> {
+        let parent = ctx.set_span(expr.span);
+        let output = self.expr_kind(builder, ctx, expr.kind);
+        ctx.set_span(parent);
+
+        output
+    }
+
+    fn expr_kind(
+        &mut self,
+        builder: &mut CodeBuilder,
+        ctx: &mut FunctionContext,
+        expr: ExprKind,
+    ) -> Result<VirtualRegister> {
         match expr {
-            Expr::Literal(literal) => {
-                let addr = ctx.reserve_reg(None)?;
+            ExprKind::Literal(literal) => {
+                let addr = ctx.reserve_reg(None);
 
                 let value = match literal {
                     Literal::String(sym) => RuntimeValue::Str(Box::leak(
@@ -334,44 +468,70 @@ impl Interpreter {
                             .into_boxed_str(),
                     )),
                     Literal::Integer(int) => RuntimeValue::I32(int),
+                    Literal::Float(float) => RuntimeValue::F64(float),
                     Literal::Boolean(boolean) => RuntimeValue::Bool(boolean),
+                    Literal::Null => RuntimeValue::Null,
                 };
 
                 ctx.inst_load(addr, value);
 
                 Ok(addr)
             }
-            Expr::Range(_range) => todo!("What even do I do here?"),
-            Expr::Comparison(comparison) => {
+            ExprKind::Range(_range) => todo!("What even do I do here?"),
+            ExprKind::Comparison(comparison) => {
                 let (left, right) = (
                     self.expr(builder, ctx, *comparison.left)?,
                     self.expr(builder, ctx, *comparison.right)?,
                 );
 
+                let output = ctx.reserve_reg(None);
+
                 match comparison.comparison {
                     Comparator::Equal => ctx.inst_eq(left, right),
                     Comparator::NotEqual => ctx.inst_not_eq(left, right),
-                    Comparator::LessEqual => todo!("Make this instruction"),
-                    Comparator::GreaterEqual => todo!("Make this instruction"),
-                    Comparator::Less => todo!("Make this instruction"),
-                    Comparator::Greater => todo!("Make this instruction"),
+                    Comparator::LessEqual => ctx.inst_less_eq(left, right),
+                    Comparator::GreaterEqual => ctx.inst_greater_eq(left, right),
+                    Comparator::Less => ctx.inst_less_than(left, right),
+                    Comparator::Greater => ctx.inst_greater_than(left, right),
                 };
+                ctx.inst_comp_to_reg(output);
 
-                todo!("Do I return a register?")
+                for operand in [left, right].iter().copied() {
+                    if ctx.is_temp(operand) {
+                        ctx.inst_drop(operand);
+                    }
+                }
+
+                Ok(output)
             }
-            Expr::BinaryOperation(bin_op) => {
+            ExprKind::BinaryOperation(bin_op) => {
                 let (left, right) = (
                     self.expr(builder, ctx, *bin_op.left)?,
                     self.expr(builder, ctx, *bin_op.right)?,
                 );
-                let output = ctx.reserve_reg(None)?;
+                let output = ctx.reserve_reg(None);
 
-                // TODO: Handle different operation types
                 match bin_op.op {
-                    (BinaryOp::Plus, _ty) => ctx.inst_add(left, right),
-                    (BinaryOp::Minus, _ty) => ctx.inst_sub(left, right),
-                    (BinaryOp::Mult, _ty) => ctx.inst_mult(left, right),
-                    (BinaryOp::Div, _ty) => ctx.inst_div(left, right),
+                    (BinaryOp::Plus, OperandType::Normal) => ctx.inst_add(left, right),
+                    (BinaryOp::Minus, OperandType::Normal) => ctx.inst_sub(left, right),
+                    (BinaryOp::Mult, OperandType::Normal) => ctx.inst_mult(left, right),
+                    (BinaryOp::Div, OperandType::Normal) => ctx.inst_div(left, right),
+
+                    (BinaryOp::Plus, OperandType::Fallible) => ctx.inst_fallible_add(left, right),
+                    (BinaryOp::Minus, OperandType::Fallible) => ctx.inst_fallible_sub(left, right),
+                    (BinaryOp::Mult, OperandType::Fallible) => ctx.inst_fallible_mult(left, right),
+                    (BinaryOp::Div, OperandType::Fallible) => ctx.inst_fallible_div(left, right),
+
+                    (BinaryOp::Plus, OperandType::Panicking) => ctx.inst_panicking_add(left, right),
+                    (BinaryOp::Minus, OperandType::Panicking) => {
+                        ctx.inst_panicking_sub(left, right)
+                    }
+                    (BinaryOp::Mult, OperandType::Panicking) => {
+                        ctx.inst_panicking_mult(left, right)
+                    }
+                    (BinaryOp::Div, OperandType::Panicking) => ctx.inst_panicking_div(left, right),
+
+                    // The parser only gives arithmetic operators a failure mode
                     (BinaryOp::And, _ty) => ctx.inst_and(left, right),
                     (BinaryOp::Or, _ty) => ctx.inst_or(left, right),
                     (BinaryOp::Xor, _ty) => ctx.inst_xor(left, right),
@@ -380,20 +540,338 @@ impl Interpreter {
 
                 Ok(output)
             }
-            Expr::Ident(sym) => ctx.get_cached_reg(sym),
-            Expr::Expr(expr) => self.expr(builder, ctx, *expr),
+            ExprKind::UnaryOperation(unary) => {
+                let operand = self.expr(builder, ctx, *unary.expr)?;
+                let output = ctx.reserve_reg(None);
+
+                match unary.op {
+                    // Negation is subtraction from zero, which promotes to the operand's type
+                    UnaryOp::Negate => {
+                        let zero = ctx.reserve_reg(None);
+                        ctx.inst_load(zero, RuntimeValue::I32(0));
+                        ctx.inst_sub(zero, operand);
+                        ctx.inst_drop(zero);
+                    }
+                    UnaryOp::Not => {
+                        ctx.inst_not(operand);
+                    }
+                }
+                ctx.inst_op_to_reg(output);
+
+                if ctx.is_temp(operand) {
+                    ctx.inst_drop(operand);
+                }
+
+                Ok(output)
+            }
+            ExprKind::Ident(sym) => ctx.get_cached_reg(sym),
+            ExprKind::Expr(expr) => self.expr(builder, ctx, *expr),
+
+            ExprKind::FunctionCall(func_call) => {
+                let outputs = self.function_call(builder, ctx, func_call, 1)?;
+
+                Ok(outputs[0])
+            }
+            ExprKind::MethodCall(method_call) => {
+                let func_call = self.method_call(builder, method_call)?;
+                let outputs = self.function_call(builder, ctx, func_call, 1)?;
+
+                Ok(outputs[0])
+            }
+
+            ExprKind::StructLiteral(literal) => self.struct_literal(builder, ctx, literal),
+            ExprKind::FieldAccess(access) => {
+                let (member, _ty) = self.member(builder, &access.object, access.member)?;
+                let object = self.expr(builder, ctx, *access.object)?;
+
+                let output = ctx.reserve_reg(None);
+                ctx.inst_get_member(output, object, member);
+                if ctx.is_temp(object) {
+                    ctx.inst_drop(object);
+                }
+
+                Ok(output)
+            }
+        }
+    }
+
+    /// The type that an expression evaluates to, if it's known before the program is run
+    fn expr_type(&self, builder: &CodeBuilder, expr: &Expr) -> Option<Type> {
+        match &expr.kind {
+            ExprKind::Literal(Literal::String(_)) => Some(Type::String),
+            ExprKind::Literal(Literal::Integer(_)) => Some(Type::Int),
+            ExprKind::Literal(Literal::Float(_)) => Some(Type::Float),
+            ExprKind::Literal(Literal::Boolean(_)) | ExprKind::Comparison(_) => Some(Type::Bool),
+            ExprKind::Ident(sym) => self.locals.get(sym).cloned(),
+            ExprKind::FunctionCall(func_call) => self
+                .callee(builder, func_call)
+                .ok()
+                .and_then(|func| self.returns.get(&func).cloned()),
+            ExprKind::MethodCall(method_call) => self
+                .method(builder, method_call)
+                .ok()
+                .and_then(|(func, _takes_self)| self.returns.get(&func).cloned()),
+            ExprKind::StructLiteral(literal) => Some(Type::Custom(literal.name)),
+            ExprKind::FieldAccess(access) => self
+                .member(builder, &access.object, access.member)
+                .ok()
+                .map(|(_member, ty)| ty),
+            ExprKind::Expr(expr) => self.expr_type(builder, expr),
+            ExprKind::UnaryOperation(unary) => self.expr_type(builder, &unary.expr),
+            ExprKind::Literal(Literal::Null)
+            | ExprKind::Range(_)
+            | ExprKind::BinaryOperation(_) => None,
+        }
+    }
+
+    /// The declared type of the object that an expression evaluates to
+    fn object_type<'b>(&self, builder: &'b CodeBuilder, object: &Expr) -> Result<&'b TypeContext> {
+        if let Some(Type::Custom(name)) = self.expr_type(builder, object) {
+            builder.get_type(name)
+        } else {
+            error!("Attempted to use a member of a value with an unknown type");
+            Err(RuntimeError {
+                ty: RuntimeErrorTy::CompilationError,
+                message: "Members can only be used on objects of a known type".to_string(),
+                backtrace: None,
+            })
+        }
+    }
+
+    /// Finds the index and type of a member of an object
+    fn member(&self, builder: &CodeBuilder, object: &Expr, member: Sym) -> Result<(u32, Type)> {
+        let ty = self.object_type(builder, object)?;
+
+        if let Some((index, member_type)) = ty.member(member) {
+            Ok((index, member_type.clone()))
+        } else {
+            Err(RuntimeError {
+                ty: RuntimeErrorTy::CompilationError,
+                message: format!(
+                    "The type `{}` has no member `{}`",
+                    builder.interner.resolve(ty.name).unwrap_or("<unknown>"),
+                    builder.interner.resolve(member).unwrap_or("<unknown>"),
+                ),
+                backtrace: None,
+            })
+        }
+    }
+
+    /// Finds the mangled name of a called method or associated function, and whether the receiver
+    /// is passed to it as `self`
+    fn method(&self, builder: &CodeBuilder, method_call: &MethodCall) -> Result<(Sym, bool)> {
+        let (ty, on_object) = match &method_call.receiver.kind {
+            // Calling a function on the type itself calls an associated function
+            ExprKind::Ident(name)
+                if !self.locals.contains_key(name) && builder.get_type(*name).is_ok() =>
+            {
+                (builder.get_type(*name)?, false)
+            }
+            _ => (self.object_type(builder, &method_call.receiver)?, true),
+        };
+
+        let (type_name, method_name) = (
+            builder.interner.resolve(ty.name).unwrap_or("<unknown>"),
+            builder
+                .interner
+                .resolve(method_call.name)
+                .unwrap_or("<unknown>"),
+        );
+
+        let message = match ty.methods.get(&method_call.name) {
+            Some(&(func, takes_self)) if takes_self == on_object => return Ok((func, takes_self)),
+            Some((_func, true)) => format!(
+                "`{}` is a method of `{}` and must be called on an object",
+                method_name, type_name,
+            ),
+            Some((_func, false)) => format!(
+                "`{}` is an associated function and must be called as `{}.{}`",
+                method_name, type_name, method_name,
+            ),
+            None => format!("The type `{}` has no method `{}`", type_name, method_name),
+        };
+
+        Err(RuntimeError {
+            ty: RuntimeErrorTy::CompilationError,
+            message,
+            backtrace: None,
+        })
+    }
+
+    /// Turns a method call into a call of its mangled function, passing the receiver as `self` if
+    /// it's a method
+    fn method_call(&self, builder: &CodeBuilder, method_call: MethodCall) -> Result<FunctionCall> {
+        let (name, takes_self) = self.method(builder, &method_call)?;
+
+        let mut arguments = Vec::with_capacity(method_call.arguments.len() + 1);
+        if takes_self {
+            arguments.push(*method_call.receiver);
+        }
+        arguments.extend(method_call.arguments);
+
+        Ok(FunctionCall {
+            name,
+            generics: Vec::new(),
+            arguments,
+        })
+    }
+
+    /// Allocates an object and fills in every one of its members
+    fn struct_literal(
+        &mut self,
+        builder: &mut CodeBuilder,
+        ctx: &mut FunctionContext,
+        literal: StructLiteral,
+    ) -> Result<VirtualRegister> {
+        let ty = builder.get_type(literal.name)?.clone();
+        let resolve = |sym| builder.interner.resolve(sym).unwrap_or("<unknown>");
+
+        let mut members = Vec::with_capacity(literal.fields.len());
+        for &(member, _) in &literal.fields {
+            let message = match ty.member(member) {
+                Some((index, _ty)) if !members.contains(&index) => {
+                    members.push(index);
+                    continue;
+                }
+                Some(_) => format!("The member `{}` was given more than once", resolve(member)),
+                None => format!(
+                    "The type `{}` has no member `{}`",
+                    resolve(ty.name),
+                    resolve(member)
+                ),
+            };
+
+            return Err(RuntimeError {
+                ty: RuntimeErrorTy::CompilationError,
+                message,
+                backtrace: None,
+            });
+        }
+
+        if members.len() != ty.members.len() {
+            let missing = ty
+                .members
+                .iter()
+                .enumerate()
+                .filter(|(index, _member)| !members.contains(&(*index as u32)))
+                .map(|(_index, (member, _ty))| format!("`{}`", resolve(*member)))
+                .collect::<Vec<_>>();
+
+            return Err(RuntimeError {
+                ty: RuntimeErrorTy::CompilationError,
+                message: format!(
+                    "The members {} of `{}` were not given",
+                    missing.join(", "),
+                    resolve(ty.name)
+                ),
+                backtrace: None,
+            });
+        }
+
+        let object = ctx.reserve_reg(None);
+        ctx.inst_new_object(object, ty.members.len() as u32);
+
+        for (member, (_name, expr)) in members.into_iter().zip(literal.fields) {
+            let value = self.expr(builder, ctx, expr)?;
+
+            ctx.inst_set_member(object, value, member);
+            if ctx.is_temp(value) {
+                ctx.inst_drop(value);
+            }
+        }
+
+        Ok(object)
+    }
+
+    /// The function that a call calls, which is an instance of a generic function if the call
+    /// was given generics
+    fn callee(&self, builder: &CodeBuilder, func_call: &FunctionCall) -> Result<Sym> {
+        if func_call.generics.is_empty() {
+            Ok(func_call.name)
+        } else {
+            builder.get_instance(func_call.name, &func_call.generics)
+        }
+    }
+
+    /// Calls a function, returning the registers holding the first `outputs` values it returned
+    fn function_call(
+        &mut self,
+        builder: &mut CodeBuilder,
+        ctx: &mut FunctionContext,
+        mut func_call: FunctionCall,
+        outputs: usize,
+    ) -> Result<Vec<VirtualRegister>> {
+        func_call.name = self.callee(builder, &func_call)?;
+
+        // Functions that aren't known ahead of time are assumed to return what is asked of them
+        let returns = match self.returns.get(&func_call.name) {
+            Some(Type::Unit) => 0,
+            Some(Type::Tuple(types)) => types.len(),
+            Some(_) => 1,
+            None => outputs,
+        };
+
+        if outputs > returns {
+            return Err(RuntimeError {
+                ty: RuntimeErrorTy::CompilationError,
+                message: format!(
+                    "The function `{}` returns {} values, but {} were expected",
+                    builder
+                        .interner
+                        .resolve(func_call.name)
+                        .unwrap_or("<unknown>"),
+                    returns,
+                    outputs,
+                ),
+                backtrace: None,
+            });
+        }
+
+        // Evaluate every argument before loading any of them, so that calls within the
+        // arguments can't clobber the caller registers
+        let mut arguments = Vec::with_capacity(func_call.arguments.len());
+        for expr in func_call.arguments {
+            arguments.push(self.expr(builder, ctx, expr)?);
+        }
+
+        // Excess arguments are pushed in reverse so that the callee pops them in order
+        for &argument in arguments.iter().skip(NUMBER_CALLER_REGISTERS).rev() {
+            ctx.inst_push(argument);
+        }
+
+        for (index, argument) in arguments.into_iter().enumerate() {
+            if index < NUMBER_CALLER_REGISTERS {
+                ctx.inst_mov(index as u8, argument);
+            }
+
+            if ctx.is_temp(argument) {
+                ctx.free_reg(argument);
+            }
+        }
 
-            Expr::FunctionCall(func_call) => {
-                let mut caller_registers = Vec::with_capacity(func_call.arguments.len());
-                for expr in func_call.arguments {
-                    caller_registers.push(self.expr(builder, ctx, expr)?);
+        ctx.inst_func_call(func_call.name);
+
+        let mut registers = Vec::with_capacity(outputs);
+        for index in 0..returns {
+            if index < outputs {
+                let output = ctx.reserve_reg(None);
+
+                if index < NUMBER_CALLER_REGISTERS {
+                    ctx.inst_mov(output, RETURN_REGISTER + index as u8);
+                } else {
+                    ctx.inst_pop(output);
                 }
 
-                ctx.inst_func_call(func_call.name);
+                registers.push(output);
 
-                todo!("Resolve function's symbol, get return type(s) and acknowledge the returns")
+            // Unused values that were returned on the stack still have to be popped off of it
+            } else if index >= NUMBER_CALLER_REGISTERS {
+                let discard = ctx.reserve_reg(None);
+                ctx.inst_pop(discard).inst_drop(discard);
             }
         }
+
+        Ok(registers)
     }
 
     fn statement(
@@ -401,38 +879,266 @@ impl Interpreter {
         statement: Statement,
         builder: &mut CodeBuilder,
         ctx: &mut FunctionContext,
+    ) -> Result<()> {
+        let parent = ctx.set_span(statement.span);
+        let result = self.statement_kind(statement.kind, builder, ctx);
+        ctx.set_span(parent);
+
+        result
+    }
+
+    fn statement_kind(
+        &mut self,
+        statement: StatementKind,
+        builder: &mut CodeBuilder,
+        ctx: &mut FunctionContext,
     ) -> Result<()> {
         match statement {
-            Statement::Assign(assign) => {
+            StatementKind::Assign(assign) => {
                 let reg = ctx.get_cached_reg(assign.var)?;
                 let loaded = self.expr(builder, ctx, assign.expr)?;
 
-                ctx.inst_mov(reg, loaded).inst_drop(loaded);
+                ctx.inst_mov(reg, loaded);
+                if ctx.is_temp(loaded) {
+                    ctx.inst_drop(loaded);
+                }
+            }
+
+            StatementKind::While(while_loop) => {
+                let (loop_start, loop_body, loop_end, loop_exit) = (
+                    builder.next_jump_id(),
+                    builder.next_jump_id(),
+                    builder.next_jump_id(),
+                    builder.next_jump_id(),
+                );
+
+                let true_reg = ctx.reserve_reg(None);
+                ctx.inst_load(true_reg, RuntimeValue::Bool(true));
+
+                // Re-evaluate the condition on every iteration, entering the body if it's true and
+                // falling through to the `then` clause once it isn't
+                ctx.inst_jump_point(loop_start);
+                let condition = self.expr(builder, ctx, while_loop.condition)?;
+                ctx.inst_eq(condition, true_reg)
+                    .inst_jump_comp(loop_body)
+                    .inst_jump(loop_end)
+                    .inst_jump_point(loop_body);
+
+                ctx.push_loop(loop_start, loop_exit);
+                for statement in while_loop.body {
+                    self.statement(statement, builder, ctx)?;
+                }
+                ctx.pop_loop();
+
+                ctx.inst_jump(loop_start).inst_jump_point(loop_end);
+
+                // The `then` clause is only reached when the loop wasn't broken out of
+                if let Some(then) = while_loop.then {
+                    for statement in then.body {
+                        self.statement(statement, builder, ctx)?;
+                    }
+                }
+
+                ctx.inst_jump_point(loop_exit).inst_drop(true_reg);
             }
 
-            Statement::While(_while_loop) => todo!(),
-            Statement::Loop(_loop_loop) => todo!(),
-            Statement::For(_for_loop) => todo!(),
+            StatementKind::Loop(loop_loop) => {
+                let (loop_start, loop_exit) = (builder.next_jump_id(), builder.next_jump_id());
+
+                ctx.inst_jump_point(loop_start);
 
-            Statement::VarDecl(var_decl) => {
-                let reg = ctx.reserve_reg(var_decl.name)?;
-                let loaded = self.expr(builder, ctx, var_decl.expr)?;
+                ctx.push_loop(loop_start, loop_exit);
+                for statement in loop_loop.body {
+                    self.statement(statement, builder, ctx)?;
+                }
+                ctx.pop_loop();
 
-                ctx.inst_mov(reg, loaded).inst_drop(loaded);
+                ctx.inst_jump(loop_start).inst_jump_point(loop_exit);
             }
 
-            Statement::Return(_ret) => todo!(),
-            Statement::Continue => todo!(),
-            Statement::Break => todo!(),
-            Statement::Expr(expr) => {
+            StatementKind::For(for_loop) => {
+                let range = if let ExprKind::Range(range) = for_loop.range.kind {
+                    range
+                } else {
+                    error!("Attempted to iterate over a non-range value");
+                    return Err(RuntimeError {
+                        ty: RuntimeErrorTy::CompilationError,
+                        message: "Only ranges can be iterated over".to_string(),
+                        backtrace: None,
+                    });
+                };
+
+                let (loop_cond, loop_body, loop_next, loop_end, loop_exit) = (
+                    builder.next_jump_id(),
+                    builder.next_jump_id(),
+                    builder.next_jump_id(),
+                    builder.next_jump_id(),
+                    builder.next_jump_id(),
+                );
+
+                // The element starts at the beginning of the range, and the end of it is only evaluated once
+                let element = ctx.reserve_reg(for_loop.element);
+                let start = self.expr(builder, ctx, *range.start)?;
+                ctx.inst_mov(element, start);
+                let end = self.expr(builder, ctx, *range.end)?;
+
+                let step = ctx.reserve_reg(None);
+                ctx.inst_load(step, RuntimeValue::I32(1));
+
+                ctx.inst_jump_point(loop_cond)
+                    .inst_less_than(element, end)
+                    .inst_jump_comp(loop_body)
+                    .inst_jump(loop_end)
+                    .inst_jump_point(loop_body);
+
+                ctx.push_loop(loop_next, loop_exit);
+                for statement in for_loop.body {
+                    self.statement(statement, builder, ctx)?;
+                }
+                ctx.pop_loop();
+
+                // Step the element forward, `continue` jumps here so that the element is always incremented
+                ctx.inst_jump_point(loop_next)
+                    .inst_add(element, step)
+                    .inst_op_to_reg(element)
+                    .inst_jump(loop_cond)
+                    .inst_jump_point(loop_end);
+
+                if let Some(then) = for_loop.then {
+                    for statement in then.body {
+                        self.statement(statement, builder, ctx)?;
+                    }
+                }
+
+                ctx.inst_jump_point(loop_exit)
+                    .inst_drop(step)
+                    .inst_drop(element);
+            }
+
+            StatementKind::FieldAssign(assign) => {
+                let (member, _ty) = self.member(builder, &assign.object, assign.member)?;
+                let object = self.expr(builder, ctx, assign.object)?;
+                let value = self.expr(builder, ctx, assign.expr)?;
+
+                ctx.inst_set_member(object, value, member);
+                for operand in [object, value].iter().copied() {
+                    if ctx.is_temp(operand) {
+                        ctx.inst_drop(operand);
+                    }
+                }
+            }
+
+            StatementKind::VarDecl(var_decl) => {
+                if let [name] = var_decl.names[..] {
+                    let ty = match var_decl.ty {
+                        Type::Infer => self.expr_type(builder, &var_decl.expr),
+                        ty => Some(ty),
+                    };
+
+                    let reg = ctx.reserve_reg(name);
+                    let loaded = self.expr(builder, ctx, var_decl.expr)?;
+
+                    ctx.inst_mov(reg, loaded);
+                    if ctx.is_temp(loaded) {
+                        ctx.inst_drop(loaded);
+                    }
+
+                    if let Some(ty) = ty {
+                        self.locals.insert(name, ty);
+                    } else {
+                        self.locals.remove(&name);
+                    }
+                } else {
+                    let func_call = if let ExprKind::FunctionCall(func_call) = var_decl.expr.kind {
+                        func_call
+                    } else {
+                        return Err(RuntimeError {
+                            ty: RuntimeErrorTy::CompilationError,
+                            message: "Only function calls can be destructured".to_string(),
+                            backtrace: None,
+                        });
+                    };
+
+                    let types = match self.returns.get(&self.callee(builder, &func_call)?) {
+                        Some(Type::Tuple(types)) => types.clone(),
+                        _ => Vec::new(),
+                    };
+
+                    let outputs =
+                        self.function_call(builder, ctx, func_call, var_decl.names.len())?;
+                    for (index, (&name, &output)) in var_decl.names.iter().zip(&outputs).enumerate()
+                    {
+                        let reg = ctx.reserve_reg(name);
+                        ctx.inst_mov(reg, output);
+
+                        if let Some(ty) = types.get(index) {
+                            self.locals.insert(name, ty.clone());
+                        } else {
+                            self.locals.remove(&name);
+                        }
+                    }
+
+                    for output in outputs {
+                        ctx.inst_drop(output);
+                    }
+                }
+            }
+
+            StatementKind::Return(ret) => {
+                // Evaluate every value before loading any of them, so that calls within the
+                // values can't clobber the caller registers
+                let mut values = Vec::with_capacity(ret.exprs.len());
+                for expr in ret.exprs {
+                    values.push(self.expr(builder, ctx, expr)?);
+                }
+
+                // Excess values are pushed in reverse so that the caller pops them in order
+                for &value in values.iter().skip(NUMBER_CALLER_REGISTERS).rev() {
+                    ctx.inst_push(value);
+                }
+
+                for (index, value) in values.into_iter().enumerate() {
+                    if index < NUMBER_CALLER_REGISTERS {
+                        ctx.inst_mov(RETURN_REGISTER + index as u8, value);
+                    }
+
+                    if ctx.is_temp(value) {
+                        ctx.free_reg(value);
+                    }
+                }
+
+                ctx.inst_return();
+            }
+            StatementKind::Continue => {
+                let labels = ctx.current_loop()?;
+                ctx.inst_jump(labels.continue_id);
+            }
+            StatementKind::Break => {
+                let labels = ctx.current_loop()?;
+                ctx.inst_jump(labels.break_id);
+            }
+            StatementKind::Expr(Expr {
+                kind: ExprKind::FunctionCall(func_call),
+                ..
+            }) => {
+                self.function_call(builder, ctx, func_call, 0)?;
+            }
+            StatementKind::Expr(Expr {
+                kind: ExprKind::MethodCall(method_call),
+                ..
+            }) => {
+                let func_call = self.method_call(builder, method_call)?;
+                self.function_call(builder, ctx, func_call, 0)?;
+            }
+            StatementKind::Expr(expr) => {
                 self.expr(builder, ctx, expr)?;
             }
-            Statement::Empty => {}
+            StatementKind::Empty => {}
 
-            Statement::Conditional(conditional) => {
+            StatementKind::Conditional(conditional) => {
                 let endif = builder.next_jump_id();
 
-                let true_reg = ctx.reserve_reg(None)?;
+                let true_reg = ctx.reserve_reg(None);
                 ctx.inst_load(true_reg, RuntimeValue::Bool(true));
 
                 let mut bodies: Vec<
@@ -445,11 +1151,15 @@ impl Interpreter {
                     >,
                 > = Vec::new();
 
+                // Test each condition in order, jumping to the body of the first one that's true
                 for If { condition, body } in conditional._if {
-                    self.expr(builder, ctx, condition)?;
+                    let condition = self.expr(builder, ctx, condition)?;
                     let block_start = builder.next_jump_id();
 
-                    ctx.inst_jump_comp(block_start);
+                    ctx.inst_eq(condition, true_reg).inst_jump_comp(block_start);
+                    if ctx.is_temp(condition) {
+                        ctx.inst_drop(condition);
+                    }
 
                     bodies.push(Box::new(move |interp, builder, ctx| {
                         ctx.inst_jump_point(block_start);
@@ -479,6 +1189,9 @@ impl Interpreter {
 
                         Ok(())
                     }));
+                } else {
+                    // None of the conditions were true and there's nothing else to do
+                    ctx.inst_jump(endif);
                 }
 
                 for func in bodies {
@@ -498,7 +1211,7 @@ impl Interpreter {
         ident_literal: IdentLiteral<'a>,
         builder: &mut CodeBuilder,
         ctx: &mut FunctionContext,
-    ) -> Result<Register> {
+    ) -> Result<VirtualRegister> {
         match ident_literal {
             IdentLiteral::Literal(literal) => {
                 let reg = ctx.reserve_caller_reg(None)?;
@@ -516,6 +1229,7 @@ impl Interpreter {
                     return Err(RuntimeError {
                         ty: RuntimeErrorTy::NullVar,
                         message: format!("The variable {:?} does not exist", variable.name),
+                        backtrace: None,
                     });
                 };
 
@@ -525,3 +1239,913 @@ impl Interpreter {
     }
     */
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AllocId, Collectable, Decoder, Encoder, OptionBuilder, Vm};
+    use codespan::{Files, Span};
+
+    fn run(interner: StringInterner<Sym>, body: Vec<Statement>) -> Result<Vm> {
+        let options = OptionBuilder::new("./interpreter_test").build();
+        let mut interner = interner;
+
+        let main = Program::FunctionDecl(FunctionDecl {
+            visibility: Visibility::Library,
+            name: interner.get_or_intern("main"),
+            generics: Vec::new(),
+            arguments: Vec::new(),
+            returns: Type::default(),
+            body,
+            span: Span::default(),
+        });
+
+        let (functions, _debug) =
+            Interpreter::from_interner(&options, interner).interpret(vec![main])?;
+
+        let mut vm = Vm::new(&options, Box::new(Vec::<u8>::new()));
+        vm.execute(functions)?;
+
+        Ok(vm)
+    }
+
+    fn compile_source(source: &str) -> Result<(Vec<Vec<Instruction>>, DebugInfo)> {
+        let options = OptionBuilder::new("./interpreter_test").build();
+
+        let mut parser = Parser::new(None, source);
+        let (ast, _diagnostics) = parser.parse().expect("The source should parse");
+
+        Interpreter::from_interner(&options, parser.interner).interpret(ast)
+    }
+
+    fn run_functions(functions: Vec<Vec<Instruction>>) -> Result<Vm> {
+        let options = OptionBuilder::new("./interpreter_test").build();
+
+        let mut vm = Vm::new(&options, Box::new(Vec::<u8>::new()));
+        vm.execute(functions)?;
+
+        Ok(vm)
+    }
+
+    fn run_source(source: &str) -> Result<Vm> {
+        run_functions(compile_source(source)?.0)
+    }
+
+    fn run_source_with(source: &str, options: &Options) -> Result<Vm> {
+        let mut vm = Vm::new(options, Box::new(Vec::<u8>::new()));
+        vm.execute(compile_source(source)?.0)?;
+
+        Ok(vm)
+    }
+
+    fn expr(kind: ExprKind) -> Expr {
+        Expr::new(kind, Span::default())
+    }
+
+    fn statement(kind: StatementKind) -> Statement {
+        Statement::new(kind, Span::default())
+    }
+
+    fn ident(name: Sym) -> Expr {
+        expr(ExprKind::Ident(name))
+    }
+
+    fn int(int: i32) -> Expr {
+        expr(ExprKind::Literal(Literal::Integer(int)))
+    }
+
+    fn boolean(boolean: bool) -> Expr {
+        expr(ExprKind::Literal(Literal::Boolean(boolean)))
+    }
+
+    fn let_var(name: Sym, expr: Expr) -> Statement {
+        statement(StatementKind::VarDecl(VarDecl {
+            names: vec![name],
+            ty: Type::Infer,
+            expr,
+        }))
+    }
+
+    fn assign(var: Sym, expr: Expr) -> Statement {
+        statement(StatementKind::Assign(Assign {
+            var,
+            expr,
+            ty: AssignType::Normal,
+        }))
+    }
+
+    fn add(left: Expr, right: Expr) -> Expr {
+        expr(ExprKind::BinaryOperation(BinaryOperation {
+            left: Box::new(left),
+            op: (BinaryOp::Plus, OperandType::Normal),
+            right: Box::new(right),
+        }))
+    }
+
+    fn range(start: Expr, end: Expr) -> Expr {
+        expr(ExprKind::Range(Range {
+            start: Box::new(start),
+            end: Box::new(end),
+        }))
+    }
+
+    fn compare(left: Expr, comparison: Comparator, right: Expr) -> Expr {
+        expr(ExprKind::Comparison(Comparison {
+            left: Box::new(left),
+            comparison,
+            right: Box::new(right),
+        }))
+    }
+
+    fn ret(exprs: Vec<Expr>) -> Statement {
+        statement(StatementKind::Return(Return { exprs }))
+    }
+
+    fn assert_reg(vm: &Vm, reg: usize, value: RuntimeValue) {
+        assert!(
+            vm.registers[reg]
+                .clone()
+                .is_equal(value.clone(), &vm.gc)
+                .unwrap(),
+            "register {} is {:?}, expected {:?}",
+            reg,
+            vm.registers[reg],
+            value
+        );
+    }
+
+    #[test]
+    fn for_loop() {
+        let mut interner = StringInterner::new();
+        let (total, i) = (interner.get_or_intern("total"), interner.get_or_intern("i"));
+
+        // let total = 0
+        // for i in 0..10
+        //     total = total + i
+        // end
+        // return total
+        let vm = run(
+            interner,
+            vec![
+                let_var(total, int(0)),
+                statement(StatementKind::For(For {
+                    element: i,
+                    range: range(int(0), int(10)),
+                    body: vec![assign(total, add(ident(total), ident(i)))],
+                    then: None,
+                })),
+                ret(vec![ident(total)]),
+            ],
+        )
+        .unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::I32(45));
+    }
+
+    #[test]
+    fn for_loop_source() {
+        const CODE: &str = "
+fn main()
+    let total = 0
+    for i in 0..10
+        total = total + i
+    end
+    for index in 2 + 3..8
+        total = total + index
+    end
+    return total
+end
+";
+
+        let vm = run_source(CODE).unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::I32(63));
+    }
+
+    #[test]
+    fn while_then() {
+        let mut interner = StringInterner::new();
+        let (running, finished) = (
+            interner.get_or_intern("running"),
+            interner.get_or_intern("finished"),
+        );
+
+        // let running = true
+        // let finished = false
+        // while running
+        //     running = false
+        // then
+        //     finished = true
+        // end
+        // return running, finished
+        let vm = run(
+            interner,
+            vec![
+                let_var(running, boolean(true)),
+                let_var(finished, boolean(false)),
+                statement(StatementKind::While(While {
+                    condition: ident(running),
+                    body: vec![assign(running, boolean(false))],
+                    then: Some(Else {
+                        body: vec![assign(finished, boolean(true))],
+                    }),
+                })),
+                ret(vec![ident(running), ident(finished)]),
+            ],
+        )
+        .unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::Bool(false));
+        assert_reg(&vm, 1, RuntimeValue::Bool(true));
+    }
+
+    #[test]
+    fn break_skips_then() {
+        let mut interner = StringInterner::new();
+        let finished = interner.get_or_intern("finished");
+
+        // let finished = false
+        // while true
+        //     break
+        // then
+        //     finished = true
+        // end
+        // return finished
+        let vm = run(
+            interner,
+            vec![
+                let_var(finished, boolean(false)),
+                statement(StatementKind::While(While {
+                    condition: boolean(true),
+                    body: vec![statement(StatementKind::Break)],
+                    then: Some(Else {
+                        body: vec![assign(finished, boolean(true))],
+                    }),
+                })),
+                ret(vec![ident(finished)]),
+            ],
+        )
+        .unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::Bool(false));
+    }
+
+    #[test]
+    fn nested_break_continue() {
+        let mut interner = StringInterner::new();
+        let (total, i) = (interner.get_or_intern("total"), interner.get_or_intern("i"));
+
+        // let total = 0
+        // for i in 0..3
+        //     loop
+        //         total = total + 1
+        //         break
+        //     end
+        //     continue
+        //     total = total + 100
+        // end
+        // return total
+        let vm = run(
+            interner,
+            vec![
+                let_var(total, int(0)),
+                statement(StatementKind::For(For {
+                    element: i,
+                    range: range(int(0), int(3)),
+                    body: vec![
+                        statement(StatementKind::Loop(Loop {
+                            body: vec![
+                                assign(total, add(ident(total), int(1))),
+                                statement(StatementKind::Break),
+                            ],
+                        })),
+                        statement(StatementKind::Continue),
+                        assign(total, add(ident(total), int(100))),
+                    ],
+                    then: None,
+                })),
+                ret(vec![ident(total)]),
+            ],
+        )
+        .unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::I32(3));
+    }
+
+    #[test]
+    fn break_outside_loop() {
+        let err = run(StringInterner::new(), vec![statement(StatementKind::Break)]).unwrap_err();
+        assert_eq!(err.ty, RuntimeErrorTy::CompilationError);
+    }
+
+    #[test]
+    fn function_returns() {
+        const CODE: &str = "
+fn second(first: int, second: int) -> int
+    return second
+end
+
+fn main()
+    let x = second(1, 2)
+    let y = second(second(3, 4), second(5, 6))
+    nothing()
+    return x, y
+end
+
+fn nothing()
+    return
+end
+";
+
+        let vm = run_source(CODE).unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::I32(2));
+        assert_reg(&vm, 1, RuntimeValue::I32(6));
+    }
+
+    #[test]
+    fn many_arguments() {
+        const CODE: &str = "
+fn seventh(a: int, b: int, c: int, d: int, e: int, f: int, g: int) -> int
+    return g
+end
+
+fn sixth(a: int, b: int, c: int, d: int, e: int, f: int, g: int) -> int
+    return f
+end
+
+fn main()
+    let x = seventh(1, 2, 3, 4, 5, 6, 7)
+    let y = sixth(1, 2, 3, 4, 5, 6, 7)
+    return x, y
+end
+";
+
+        let vm = run_source(CODE).unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::I32(7));
+        assert_reg(&vm, 1, RuntimeValue::I32(6));
+        assert!(vm.stack.is_empty());
+    }
+
+    #[test]
+    fn multiple_returns() {
+        const CODE: &str = "
+fn reverse(a: int, b: int, c: int, d: int, e: int, f: int, g: int) -> (int, int, int, int, int, int, int)
+    return g, f, e, d, c, b, a
+end
+
+fn main()
+    let a, b, c, d, e, f, g = reverse(1, 2, 3, 4, 5, 6, 7)
+    reverse(1, 2, 3, 4, 5, 6, 7)
+    let first, second = reverse(1, 2, 3, 4, 5, 6, 7)
+    return a, d, g, first, second
+end
+";
+
+        let vm = run_source(CODE).unwrap();
+
+        for (reg, value) in [7, 4, 1, 7, 6].iter().enumerate() {
+            assert_reg(&vm, reg, RuntimeValue::I32(*value));
+        }
+        // Any unused values must still be popped off of the stack
+        assert!(vm.stack.is_empty());
+    }
+
+    #[test]
+    fn register_spilling() {
+        // Keep more variables alive than there are registers by passing all of them to a function
+        let names = (0..40).map(|i| format!("v{}", i)).collect::<Vec<_>>();
+
+        let mut code = format!(
+            "fn pick({}) -> (int, int, int)\n    return v0, v20, v39\nend\n\nfn main()\n",
+            names
+                .iter()
+                .map(|name| format!("{}: int", name))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        for (i, name) in names.iter().enumerate() {
+            code.push_str(&format!("    let {} = {}\n", name, i));
+        }
+        code.push_str(&format!(
+            "    let x, y, z = pick({})\n    return x, y, z\nend\n",
+            names.join(", "),
+        ));
+
+        let vm = run_source(&code).unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::I32(0));
+        assert_reg(&vm, 1, RuntimeValue::I32(20));
+        assert_reg(&vm, 2, RuntimeValue::I32(39));
+        assert!(!vm.spills.is_empty());
+    }
+
+    #[test]
+    fn comparison_values() {
+        // return 1 < 2, 2 <= 2, 1 > 2, 1 >= 2, 1 == 1, 1 != 1
+        let vm = run(
+            StringInterner::new(),
+            vec![ret(vec![
+                compare(int(1), Comparator::Less, int(2)),
+                compare(int(2), Comparator::LessEqual, int(2)),
+                compare(int(1), Comparator::Greater, int(2)),
+                compare(int(1), Comparator::GreaterEqual, int(2)),
+                compare(int(1), Comparator::Equal, int(1)),
+                compare(int(1), Comparator::NotEqual, int(1)),
+            ])],
+        )
+        .unwrap();
+
+        for (reg, value) in [true, true, false, false, true].iter().enumerate() {
+            assert_reg(&vm, reg, RuntimeValue::Bool(*value));
+        }
+        // The last value doesn't fit in the caller registers
+        assert_eq!(vm.stack.len(), 1);
+        assert!(!vm.stack[0]
+            .clone()
+            .is_equal(RuntimeValue::Bool(true), &vm.gc)
+            .unwrap());
+    }
+
+    #[test]
+    fn conditional_comparisons() {
+        // let picked = 0
+        // if x < 5
+        //     picked = 1
+        // else if x >= 10
+        //     picked = 2
+        // else
+        //     picked = 3
+        // end
+        // return picked
+        let conditional = |x_value: i32, with_else: bool| {
+            let mut interner = StringInterner::new();
+            let (x, picked) = (
+                interner.get_or_intern("x"),
+                interner.get_or_intern("picked"),
+            );
+
+            run(
+                interner,
+                vec![
+                    let_var(x, int(x_value)),
+                    let_var(picked, int(0)),
+                    statement(StatementKind::Conditional(Conditional {
+                        _if: vec![
+                            If {
+                                condition: compare(ident(x), Comparator::Less, int(5)),
+                                body: vec![assign(picked, int(1))],
+                            },
+                            If {
+                                condition: compare(ident(x), Comparator::GreaterEqual, int(10)),
+                                body: vec![assign(picked, int(2))],
+                            },
+                        ],
+                        _else: if with_else {
+                            Some(Else {
+                                body: vec![assign(picked, int(3))],
+                            })
+                        } else {
+                            None
+                        },
+                    })),
+                    ret(vec![ident(picked)]),
+                ],
+            )
+            .unwrap()
+        };
+
+        assert_reg(&conditional(1, true), 0, RuntimeValue::I32(1));
+        assert_reg(&conditional(10, true), 0, RuntimeValue::I32(2));
+        assert_reg(&conditional(7, true), 0, RuntimeValue::I32(3));
+        // Nothing matches and there's no else, so nothing should run
+        assert_reg(&conditional(7, false), 0, RuntimeValue::I32(0));
+    }
+
+    #[test]
+    fn float_literals() {
+        let vm = run_source("fn main()\n    let x = 1.5\n    return x\nend\n").unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::F64(1.5));
+    }
+
+    #[test]
+    fn deterministic_builds() {
+        const CODE: &str = "
+fn first() -> int
+    return second() + 1
+end
+
+fn second() -> int
+    return 2
+end
+
+fn unused() -> int
+    return 3
+end
+
+fn also_unused() -> int
+    return 4
+end
+
+fn main()
+    return first()
+end
+";
+
+        let build = || {
+            Encoder::new(compile_source(CODE).unwrap().0)
+                .encode()
+                .unwrap()
+        };
+        let bytes = build();
+        for _ in 0..10 {
+            assert_eq!(build(), bytes);
+        }
+    }
+
+    #[test]
+    fn bytecode_round_trip() {
+        const CODE: &str = "
+fn pick(a: int, b: float) -> (int, float)
+    if true
+        return a, b
+    end
+    return 0, 0.0
+end
+
+fn main()
+    let x, y = pick(3, 1.5)
+    let s = \"built\"
+    return x, y, s
+end
+";
+
+        let (functions, _debug) = compile_source(CODE).unwrap();
+        let bytes = Encoder::new(functions.clone()).encode().unwrap();
+        let (main, mut decoded) = Decoder::new(&bytes).decode().unwrap();
+        decoded.insert(0, main);
+
+        let source_vm = run_functions(functions).unwrap();
+        let vm = run_functions(decoded).unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::I32(3));
+        for reg in 0..3 {
+            assert_reg(&vm, reg, source_vm.registers[reg].clone());
+        }
+    }
+
+    #[test]
+    fn type_declarations() {
+        const CODE: &str = "
+type Syven
+    name: str
+    age: int
+
+    fn new(name: str, age: int) -> Self
+        return Syven {
+            name: name,
+            age: age,
+        }
+    end
+
+    fn age(self) -> int
+        return self.age
+    end
+
+    fn birthday(self)
+        self.age = 23
+    end
+end
+
+type Pair
+    inner: Syven
+    value: int
+end
+
+fn main()
+    let syv = Syven.new(\"Syven\", 22)
+    let before = syv.age()
+    syv.birthday()
+    let pair = Pair { inner: syv, value: 10 }
+    return before, pair.inner.age, syv.name, pair.value
+end
+";
+
+        let vm = run_source(CODE).unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::I32(22));
+        assert_reg(&vm, 1, RuntimeValue::I32(23));
+        assert_reg(&vm, 2, RuntimeValue::Str("Syven"));
+        assert_reg(&vm, 3, RuntimeValue::I32(10));
+    }
+
+    #[test]
+    fn type_errors() {
+        const TYPE: &str = "
+type Point
+    x: int
+    y: int
+
+    fn origin() -> Self
+        return Point { x: 0, y: 0 }
+    end
+
+    fn x(self) -> int
+        return self.x
+    end
+end
+";
+
+        let compile = |main: &str| {
+            compile_source(&format!("{}\nfn main()\n{}\nend\n", TYPE, main)).unwrap_err()
+        };
+
+        let errors = [
+            "    let p = Point { x: 1 }",
+            "    let p = Point { x: 1, y: 2, z: 3 }",
+            "    let p = Point { x: 1, x: 2, y: 3 }",
+            "    let p = Point.origin()\n    return p.z",
+            "    let p = Point.x()",
+            "    let p = Point.origin()\n    let q = p.origin()",
+            "    let p = Nothing { x: 1 }",
+        ];
+        for main in errors.iter() {
+            assert_eq!(
+                compile(main).ty,
+                RuntimeErrorTy::CompilationError,
+                "{}",
+                main
+            );
+        }
+    }
+
+    #[test]
+    fn operator_precedence() {
+        const CODE: &str = "
+fn main() -> (int, int, bool, bool, float)
+    let product = 1 + 2 * 3 - 4
+    let grouped = (1 + 2) * -3
+    let ordered = 1 + 1 < 3 and not (1 >= 2 or 3 != 3)
+    let equal = 2 * 3 == 6 xor true
+    let negated = -(1.5 * 2)
+    return product, grouped, ordered, equal, negated
+end
+";
+
+        let options = OptionBuilder::new("./interpreter_test").build();
+        let mut parser = Parser::new(None, CODE);
+        let (mut ast, _diagnostics) = parser.parse().expect("The source should parse");
+        TypeChecker::new(&parser)
+            .check(&mut ast)
+            .expect("The source should type check");
+
+        let (functions, _debug) = Interpreter::from_interner(&options, parser.interner)
+            .interpret(ast)
+            .unwrap();
+
+        let vm = run_functions(functions).unwrap();
+        assert_reg(&vm, 0, RuntimeValue::I32(3));
+        assert_reg(&vm, 1, RuntimeValue::I32(-9));
+        assert_reg(&vm, 2, RuntimeValue::Bool(true));
+        assert_reg(&vm, 3, RuntimeValue::Bool(false));
+        assert_reg(&vm, 4, RuntimeValue::F64(-3.0));
+    }
+
+    #[test]
+    fn generic_functions() {
+        const CODE: &str = "
+fn identity<T>(value: T) -> T
+    return value
+end
+
+fn wrap<T>(value: T) -> T
+    let inner = identity(value)
+    return inner
+end
+
+fn first(value, other) -> any
+    return value
+end
+
+fn main() -> (int, str, str, int)
+    let number = identity(1)
+    let text = wrap(\"text\")
+    let given = identity<str>(\"given\")
+    let picked: int = first(2, \"ignored\")
+    return number, text, given, picked
+end
+";
+
+        let options = OptionBuilder::new("./interpreter_test").build();
+        let mut parser = Parser::new(None, CODE);
+        let (mut ast, _diagnostics) = parser.parse().expect("The source should parse");
+        TypeChecker::new(&parser)
+            .check(&mut ast)
+            .expect("The source should type check");
+
+        let (functions, _debug) = Interpreter::from_interner(&options, parser.interner)
+            .interpret(ast)
+            .unwrap();
+        // `main`, `identity<int>`, `identity<str>`, `wrap<str>` and `first<int, str>`
+        assert_eq!(functions.len(), 5);
+
+        let vm = run_functions(functions).unwrap();
+        assert_reg(&vm, 0, RuntimeValue::I32(1));
+        assert_reg(&vm, 1, RuntimeValue::Str("text"));
+        assert_reg(&vm, 2, RuntimeValue::Str("given"));
+        assert_reg(&vm, 3, RuntimeValue::I32(2));
+    }
+
+    #[test]
+    fn operand_types() {
+        const CODE: &str = "
+fn main()
+    let zero = 10 / 0
+    let checked = 10 /? 0
+    let halved = 7 /? 2
+    let overflowed = 2147483647 +? 1
+    let grown = 2147483647 + 1
+    return zero, checked, halved, overflowed, grown
+end
+";
+
+        let vm = run_source(CODE).unwrap();
+        assert_reg(&vm, 0, RuntimeValue::I32(0));
+        assert_reg(&vm, 1, RuntimeValue::Null);
+        assert_reg(&vm, 2, RuntimeValue::I32(3));
+        assert_reg(&vm, 3, RuntimeValue::Null);
+        assert_reg(&vm, 4, RuntimeValue::I64(2_147_483_648));
+
+        let err = run_source("fn main()\n    let x = 10 /! 0\nend\n").unwrap_err();
+        assert_eq!(err.ty, RuntimeErrorTy::DivideByZero);
+        let err = run_source("fn main()\n    let x = 2147483647 *! 2\nend\n").unwrap_err();
+        assert_eq!(err.ty, RuntimeErrorTy::IntegerOverflow);
+    }
+
+    #[test]
+    fn runtime_error_spans() {
+        const CODE: &str = "
+fn divide(x: int, y: int) -> int
+    return x /! y
+end
+
+fn main()
+    let quotient = divide(10, 0)
+    return quotient
+end
+";
+
+        let (functions, debug) = compile_source(CODE).unwrap();
+
+        let options = OptionBuilder::new("./interpreter_test").build();
+        let mut vm = Vm::new(&options, Box::new(Vec::<u8>::new()));
+        let err = vm.execute(functions).unwrap_err();
+        assert_eq!(err.ty, RuntimeErrorTy::DivideByZero);
+
+        let span = debug
+            .span(vm.current_func, vm.index)
+            .expect("The failing instruction should have a span");
+        let mut files = Files::new();
+        let file = files.add("runtime_error_spans", CODE);
+        assert_eq!(files.source_slice(file, span).unwrap(), "x /! y");
+    }
+
+    #[test]
+    fn backtraces() {
+        const CODE: &str = "
+type Fraction
+    top: int
+    bottom: int
+
+    fn value(self) -> int
+        return self.top /! self.bottom
+    end
+end
+
+fn evaluate(top: int) -> int
+    let fraction = Fraction { top: top, bottom: 0 }
+    return fraction.value()
+end
+
+fn main()
+    let value = evaluate(1)
+    return value
+end
+";
+
+        let (functions, debug) = compile_source(CODE).unwrap();
+        let err = run_functions(functions).unwrap_err();
+        let backtrace = err
+            .backtrace
+            .expect("Errors from the vm should have a backtrace");
+
+        let mut files = Files::new();
+        let file = files.add("backtraces", CODE);
+        let frames = backtrace
+            .frames
+            .iter()
+            .map(|frame| {
+                let span = debug.span(frame.function, frame.index).unwrap();
+                (
+                    debug.name(frame.function).unwrap(),
+                    files.source_slice(file, span).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            frames,
+            vec![
+                ("Fraction.value", "self.top /! self.bottom"),
+                ("evaluate", "fraction.value()"),
+                ("main", "evaluate(1)"),
+            ]
+        );
+
+        let rendered = backtrace.render(Some((&debug, &files, file)));
+        assert!(rendered.contains("0: Fraction.value at instruction"));
+        assert!(rendered.contains("at backtraces:7:16\n        return self.top /! self.bottom"));
+    }
+
+    #[test]
+    fn missing_main() {
+        let options = OptionBuilder::new("./interpreter_test").build();
+        let mut interner = StringInterner::new();
+
+        let not_main = Program::FunctionDecl(FunctionDecl {
+            visibility: Visibility::Library,
+            name: interner.get_or_intern("not_main"),
+            generics: Vec::new(),
+            arguments: Vec::new(),
+            returns: Type::default(),
+            body: vec![statement(StatementKind::Empty)],
+            span: Span::default(),
+        });
+
+        let err = Interpreter::from_interner(&options, interner)
+            .interpret(vec![not_main])
+            .unwrap_err();
+        assert_eq!(err.ty, RuntimeErrorTy::MissingMain);
+    }
+
+    #[test]
+    fn gc_roots() {
+        const CODE: &str = "
+type Named
+    name: str
+    count: int
+end
+
+fn named(count: int) -> Named
+    let name = \"item\" + \"s\"
+    return Named { name: name, count: count }
+end
+
+fn main()
+    let first = named(1)
+    let total = 0
+    let i = 0
+    while i < 50
+        let other = named(i)
+        total = total + other.count
+        i = i + 1
+    end
+    return first.name, first.count, total
+end
+";
+
+        // Collect at every safepoint, so anything that isn't rooted by the vm gets overwritten
+        let options = OptionBuilder::new("./gc_roots")
+            .burn_gc(true)
+            .overwrite_heap(true)
+            .verify_heap(true)
+            .heap_size(128)
+            .build();
+        let vm = run_source_with(CODE, &options).unwrap();
+
+        assert_reg(&vm, 0, RuntimeValue::Str("items"));
+        assert_reg(&vm, 1, RuntimeValue::I32(1));
+        assert_reg(&vm, 2, RuntimeValue::I32(1225));
+    }
+
+    #[test]
+    fn gc_errors_have_backtraces() {
+        let options = OptionBuilder::new("./gc_errors_have_backtraces")
+            .burn_gc(true)
+            .verify_heap(true)
+            .build();
+        let mut vm = Vm::new(&options, Box::new(Vec::<u8>::new()));
+
+        // A rooted object pointing at a nonexistent one fails verification at the first safepoint
+        let parent = 1_usize.alloc(&mut vm.gc).unwrap();
+        parent.root(&mut vm.gc).unwrap();
+        vm.gc.add_child(parent.id(), AllocId::new(1000)).unwrap();
+
+        let (functions, _debug) = compile_source("fn main()\n    return 1\nend\n").unwrap();
+        let err = vm.execute(functions).unwrap_err();
+
+        assert_eq!(err.ty, RuntimeErrorTy::GcError);
+        assert!(err.backtrace.is_some(), "{:?}", err);
+    }
+}
@@ -8,6 +8,7 @@ pub extern "win64" fn load(vm: *mut Vm, val: *const RuntimeValue, reg: u8) -> us
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -27,6 +28,7 @@ pub extern "win64" fn add(vm: *mut Vm, left: u8, right: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -46,6 +48,7 @@ pub extern "win64" fn sub(vm: *mut Vm, left: u8, right: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -65,6 +68,7 @@ pub extern "win64" fn mult(vm: *mut Vm, left: u8, right: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -84,6 +88,7 @@ pub extern "win64" fn div(vm: *mut Vm, left: u8, right: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -95,6 +100,166 @@ pub extern "win64" fn div(vm: *mut Vm, left: u8, right: u8) -> usize {
     }
 }
 
+pub extern "win64" fn fallible_add(vm: *mut Vm, left: u8, right: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::fallible_add(vm, left, right) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn fallible_sub(vm: *mut Vm, left: u8, right: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::fallible_sub(vm, left, right) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn fallible_mult(vm: *mut Vm, left: u8, right: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::fallible_mult(vm, left, right) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn fallible_div(vm: *mut Vm, left: u8, right: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::fallible_div(vm, left, right) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn panicking_add(vm: *mut Vm, left: u8, right: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::panicking_add(vm, left, right) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn panicking_sub(vm: *mut Vm, left: u8, right: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::panicking_sub(vm, left, right) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn panicking_mult(vm: *mut Vm, left: u8, right: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::panicking_mult(vm, left, right) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn panicking_div(vm: *mut Vm, left: u8, right: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::panicking_div(vm, left, right) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
 pub extern "win64" fn comp_to_reg(vm: *mut Vm, reg: u8) -> usize {
     unsafe {
         let vm = if let Some(vm) = vm.as_mut() {
@@ -103,6 +268,7 @@ pub extern "win64" fn comp_to_reg(vm: *mut Vm, reg: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -122,6 +288,7 @@ pub extern "win64" fn op_to_reg(vm: *mut Vm, reg: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -141,6 +308,7 @@ pub extern "win64" fn drop(vm: *mut Vm, reg: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -160,6 +328,7 @@ pub extern "win64" fn mov(vm: *mut Vm, input: u8, output: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -171,6 +340,146 @@ pub extern "win64" fn mov(vm: *mut Vm, input: u8, output: u8) -> usize {
     }
 }
 
+pub extern "win64" fn push(vm: *mut Vm, reg: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::push(vm, reg) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn pop(vm: *mut Vm, reg: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::pop(vm, reg) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn spill(vm: *mut Vm, reg: u8, slot: u32) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::spill(vm, reg, slot) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn reload(vm: *mut Vm, slot: u32, reg: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::reload(vm, slot, reg) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn new_object(vm: *mut Vm, reg: u8, members: u32) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::new_object(vm, reg, members) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn get_member(vm: *mut Vm, output: u8, object: u8, member: u32) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::get_member(vm, output, object, member) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn set_member(vm: *mut Vm, object: u8, value: u8, member: u32) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::set_member(vm, object, value, member) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
 pub extern "win64" fn print(vm: *mut Vm, reg: u8) -> usize {
     unsafe {
         let vm = if let Some(vm) = vm.as_mut() {
@@ -179,6 +488,7 @@ pub extern "win64" fn print(vm: *mut Vm, reg: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -198,6 +508,7 @@ pub extern "win64" fn jump(vm: *mut Vm, index: i32) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -217,6 +528,7 @@ pub extern "win64" fn jump_comp(vm: *mut Vm, index: i32) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -236,6 +548,7 @@ pub extern "win64" fn and(vm: *mut Vm, left: u8, right: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -255,6 +568,7 @@ pub extern "win64" fn or(vm: *mut Vm, left: u8, right: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -274,6 +588,7 @@ pub extern "win64" fn xor(vm: *mut Vm, left: u8, right: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -293,6 +608,7 @@ pub extern "win64" fn not(vm: *mut Vm, reg: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -312,6 +628,7 @@ pub extern "win64" fn eq(vm: *mut Vm, left: u8, right: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -331,6 +648,7 @@ pub extern "win64" fn not_eq(vm: *mut Vm, left: u8, right: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -350,6 +668,7 @@ pub extern "win64" fn greater_than(vm: *mut Vm, left: u8, right: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -369,6 +688,7 @@ pub extern "win64" fn less_than(vm: *mut Vm, left: u8, right: u8) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -380,6 +700,46 @@ pub extern "win64" fn less_than(vm: *mut Vm, left: u8, right: u8) -> usize {
     }
 }
 
+pub extern "win64" fn greater_eq(vm: *mut Vm, left: u8, right: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::greater_eq(vm, left, right) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
+pub extern "win64" fn less_eq(vm: *mut Vm, left: u8, right: u8) -> usize {
+    unsafe {
+        let vm = if let Some(vm) = vm.as_mut() {
+            vm
+        } else {
+            let err = Box::leak(Box::new(RuntimeError {
+                ty: RuntimeErrorTy::JitError,
+                message: "The VM pointer is null".to_string(),
+                backtrace: None,
+            }));
+            return err as *const RuntimeError as usize;
+        };
+
+        match crate::instruction::functions::less_eq(vm, left, right) {
+            Ok(_) => 0,
+            Err(err) => Box::into_raw(Box::new(err)) as usize,
+        }
+    }
+}
+
 pub extern "win64" fn func(vm: *mut Vm, func: u32) -> usize {
     unsafe {
         let vm = if let Some(vm) = vm.as_mut() {
@@ -388,6 +748,7 @@ pub extern "win64" fn func(vm: *mut Vm, func: u32) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -407,6 +768,7 @@ pub extern "win64" fn yield_generator(vm: *mut Vm) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -426,6 +788,7 @@ pub extern "win64" fn ret(vm: *mut Vm) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -445,6 +808,7 @@ pub extern "win64" fn collect(vm: *mut Vm) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -464,6 +828,7 @@ pub extern "win64" fn halt(vm: *mut Vm) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -483,6 +848,7 @@ pub extern "win64" fn no_op(vm: *mut Vm) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -502,6 +868,7 @@ pub extern "win64" fn illegal(vm: *mut Vm) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
@@ -521,6 +888,7 @@ pub extern "win64" fn jump_point(vm: *mut Vm) -> usize {
             let err = Box::leak(Box::new(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "The VM pointer is null".to_string(),
+                backtrace: None,
             }));
             return err as *const RuntimeError as usize;
         };
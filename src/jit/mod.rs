@@ -156,6 +156,55 @@ impl<'a> Jit<'a> {
                         ;; call!(asm, externals::mov)
                     );
                 }
+                Instruction::Push(reg) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **reg as _
+                        ;; call!(asm, externals::push)
+                    );
+                }
+                Instruction::Pop(reg) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **reg as _
+                        ;; call!(asm, externals::pop)
+                    );
+                }
+                Instruction::Spill(reg, slot) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **reg as _
+                        ; mov r8, *slot as _
+                        ;; call!(asm, externals::spill)
+                    );
+                }
+                Instruction::Reload(slot, reg) => {
+                    dynasm!(asm
+                        ; mov rdx, *slot as _
+                        ; mov r8, BYTE **reg as _
+                        ;; call!(asm, externals::reload)
+                    );
+                }
+                Instruction::NewObject(reg, members) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **reg as _
+                        ; mov r8, *members as _
+                        ;; call!(asm, externals::new_object)
+                    );
+                }
+                Instruction::GetMember(output, object, member) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **output as _
+                        ; mov r8, BYTE **object as _
+                        ; mov r9, *member as _
+                        ;; call!(asm, externals::get_member)
+                    );
+                }
+                Instruction::SetMember(object, value, member) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **object as _
+                        ; mov r8, BYTE **value as _
+                        ; mov r9, *member as _
+                        ;; call!(asm, externals::set_member)
+                    );
+                }
 
                 Instruction::Add(left, right) => {
                     dynasm!(asm
@@ -185,6 +234,62 @@ impl<'a> Jit<'a> {
                         ;; call!(asm, externals::div)
                     );
                 }
+                Instruction::FallibleAdd(left, right) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **left as _
+                        ; mov r8, BYTE **right as _
+                        ;; call!(asm, externals::fallible_add)
+                    );
+                }
+                Instruction::FallibleSub(left, right) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **left as _
+                        ; mov r8, BYTE **right as _
+                        ;; call!(asm, externals::fallible_sub)
+                    );
+                }
+                Instruction::FallibleMult(left, right) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **left as _
+                        ; mov r8, BYTE **right as _
+                        ;; call!(asm, externals::fallible_mult)
+                    );
+                }
+                Instruction::FallibleDiv(left, right) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **left as _
+                        ; mov r8, BYTE **right as _
+                        ;; call!(asm, externals::fallible_div)
+                    );
+                }
+                Instruction::PanickingAdd(left, right) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **left as _
+                        ; mov r8, BYTE **right as _
+                        ;; call!(asm, externals::panicking_add)
+                    );
+                }
+                Instruction::PanickingSub(left, right) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **left as _
+                        ; mov r8, BYTE **right as _
+                        ;; call!(asm, externals::panicking_sub)
+                    );
+                }
+                Instruction::PanickingMult(left, right) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **left as _
+                        ; mov r8, BYTE **right as _
+                        ;; call!(asm, externals::panicking_mult)
+                    );
+                }
+                Instruction::PanickingDiv(left, right) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **left as _
+                        ; mov r8, BYTE **right as _
+                        ;; call!(asm, externals::panicking_div)
+                    );
+                }
 
                 Instruction::Print(reg) => {
                     dynasm!(asm
@@ -328,6 +433,20 @@ impl<'a> Jit<'a> {
                         ;; call!(asm, externals::less_than)
                     );
                 }
+                Instruction::GreaterEq(left, right) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **left as _
+                        ; mov r8, BYTE **right as _
+                        ;; call!(asm, externals::greater_eq)
+                    );
+                }
+                Instruction::LessEq(left, right) => {
+                    dynasm!(asm
+                        ; mov rdx, BYTE **left as _
+                        ; mov r8, BYTE **right as _
+                        ;; call!(asm, externals::less_eq)
+                    );
+                }
 
                 Instruction::Func(func) => {
                     dynasm!(asm
@@ -361,6 +480,7 @@ impl<'a> Jit<'a> {
             return Err(RuntimeError {
                 ty: RuntimeErrorTy::JitError,
                 message: "JIT miss-compilation, leftover loops".to_string(),
+                backtrace: None,
             });
         }
 
@@ -73,7 +73,7 @@
 //! :: Functions that do not specify a return type default to `void`
 //! fn main()
 //!     let name: str = "Syven"
-//!     let age: int = null
+//!     let age: nullable<int> = null
 //!     age = 22
 //!
 //!     hello(name, age)
@@ -144,16 +144,31 @@
 
 /// The number of available registers for the VM
 const NUMBER_REGISTERS: usize = 32;
+/// The number of registers used to pass arguments into a function, these are not saved across function calls
+const NUMBER_CALLER_REGISTERS: usize = 5;
+/// The register that a function's return value is passed back to the caller in
+const RETURN_REGISTER: u8 = 0;
+/// The number of stack slots that each function can spill registers into
+const MAX_SPILL_SLOTS: usize = 1 << 16;
+/// The number of members that an object can have
+const MAX_OBJECT_MEMBERS: usize = 1 << 16;
 
 #[macro_use]
 extern crate log;
 
+/// Register allocation
+mod allocator;
+/// Assembling and disassembling bytecode
 mod assembler;
 /// Encoding and decoding bytecode
 mod bytecode;
 mod code_builder;
 /// The main Crunch interface
 mod crunch;
+/// Mapping compiled programs back to their source
+mod debug;
+/// Error codes and their explanations
+mod error_code;
 /// The Garbage Collector
 mod gc;
 /// Instruction definitions and executions
@@ -174,7 +189,10 @@ mod value;
 mod vm;
 
 pub use crate::crunch::Crunch;
+pub use assembler::*;
 pub use bytecode::*;
+pub use debug::{Backtrace, DebugInfo, Frame, FunctionInfo};
+pub use error_code::ErrorCode;
 pub use gc::*;
 pub use instruction::*;
 pub use newtypes::*;
@@ -199,11 +217,24 @@ pub struct Options {
     /// Allows some runtime errors to be ignored
     #[structopt(long = "--fault-tolerant")]
     pub fault_tolerant: bool,
-    /// Overwrites the heap on a side swap
+    /// Poisons all free heap memory after every collection
     #[structopt(long = "--overwrite-heap")]
     pub overwrite_heap: bool,
+    /// Checks that the heap is consistent after every collection, implies `--overwrite-heap`
+    #[structopt(long = "--verify-heap")]
+    pub verify_heap: bool,
+    /// The initial size of the nursery, in bytes
     #[structopt(long = "--heap-size", default_value = "1024")]
     pub heap_size: usize,
+    /// The size that the heap won't be shrunk below, in bytes
+    #[structopt(long = "--min-heap", default_value = "5120")]
+    pub min_heap: usize,
+    /// The size that the heap won't be grown past, in bytes
+    #[structopt(long = "--max-heap", default_value = "1073741824")]
+    pub max_heap: usize,
+    /// The number of nursery collections an object must survive before it's promoted
+    #[structopt(long = "--promotion-age", default_value = "2")]
+    pub promotion_age: usize,
 }
 
 // TODO: Document the option builder
@@ -215,7 +246,11 @@ pub struct OptionBuilder {
     debug_log: bool,
     fault_tolerant: bool,
     overwrite_heap: bool,
+    verify_heap: bool,
     heap_size: usize,
+    min_heap: usize,
+    max_heap: usize,
+    promotion_age: usize,
 }
 
 impl OptionBuilder {
@@ -226,7 +261,11 @@ impl OptionBuilder {
             debug_log: false,
             fault_tolerant: false,
             overwrite_heap: false,
+            verify_heap: false,
             heap_size: 1024,
+            min_heap: 5120,
+            max_heap: 1 << 30,
+            promotion_age: 2,
         }
     }
 
@@ -254,12 +293,36 @@ impl OptionBuilder {
         self
     }
 
+    #[must_use]
+    pub const fn verify_heap(mut self, b: bool) -> Self {
+        self.verify_heap = b;
+        self
+    }
+
     #[must_use]
     pub const fn heap_size(mut self, heap_size: usize) -> Self {
         self.heap_size = heap_size;
         self
     }
 
+    #[must_use]
+    pub const fn min_heap(mut self, min_heap: usize) -> Self {
+        self.min_heap = min_heap;
+        self
+    }
+
+    #[must_use]
+    pub const fn max_heap(mut self, max_heap: usize) -> Self {
+        self.max_heap = max_heap;
+        self
+    }
+
+    #[must_use]
+    pub const fn promotion_age(mut self, promotion_age: usize) -> Self {
+        self.promotion_age = promotion_age;
+        self
+    }
+
     // Cannot make destructors const fns
     #[allow(clippy::missing_const_for_fn)]
     #[must_use]
@@ -270,16 +333,21 @@ impl OptionBuilder {
             debug_log: self.debug_log,
             fault_tolerant: self.fault_tolerant,
             overwrite_heap: self.overwrite_heap,
+            verify_heap: self.verify_heap,
             heap_size: self.heap_size,
+            min_heap: self.min_heap,
+            max_heap: self.max_heap,
+            promotion_age: self.promotion_age,
         }
     }
 }
 
-// TODO: Add disassembly and maybe a GC view? Debugging capabilities?
+// TODO: Add a GC view? Debugging capabilities?
 #[derive(structopt::StructOpt, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ReplOutput {
     Ast,
     Bytecode,
+    Disassembly,
     None,
 }
 
@@ -294,6 +362,7 @@ impl From<&str> for ReplOutput {
         match &*string.to_lowercase() {
             "ast" => Self::Ast,
             "bytecode" => Self::Bytecode,
+            "disassembly" | "disasm" => Self::Disassembly,
             "none" | _ => Self::None,
         }
     }
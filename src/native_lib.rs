@@ -22,6 +22,7 @@ impl FFIHandler {
                 return Err(RuntimeError {
                     ty: RuntimeErrorTy::MissingFile,
                     message: format!("The dynamic library {:?} cannot be found", name),
+                    backtrace: None,
                 });
             }
         };
@@ -49,6 +50,7 @@ impl FFIHandler {
             return Err(RuntimeError {
                 ty: RuntimeErrorTy::MissingFile,
                 message: "The requested Dynamic Library is not loaded".to_string(),
+                backtrace: None,
             });
         };
 
@@ -64,6 +66,7 @@ impl FFIHandler {
                     ty: RuntimeErrorTy::MissingFile, // MissingFunction?
                     message: "The requested Dynamic Library does not have the requested function"
                         .to_string(),
+                    backtrace: None,
                 });
             }
         };
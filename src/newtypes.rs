@@ -86,7 +86,7 @@ util_ops! {
 }
 
 /// The id of a currently allocated object
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct AllocId(pub usize);
 
@@ -122,9 +122,10 @@ impl std::fmt::Debug for Register {
 pub struct Bytecode<'a>(&'a [u8]);
 
 impl<'a> Bytecode<'a> {
+    /// Verifies that the bytes are well-formed bytecode, see [`verify`](crate::verify)
     #[inline]
-    pub fn validate(bytes: &'a [u8]) -> Result<Self, &'static str> {
-        // TODO: Write this
+    pub fn validate(bytes: &'a [u8]) -> Result<Self, crate::VerifyError> {
+        crate::verify(bytes)?;
 
         Ok(Self(bytes))
     }
@@ -1,5 +1,6 @@
+use codespan::Span;
 use std::path::PathBuf;
-use string_interner::Sym;
+use string_interner::{StringInterner, Sym};
 
 #[derive(Debug, Clone)]
 pub enum Either<L, R> {
@@ -22,6 +23,28 @@ pub struct FunctionDecl {
     pub arguments: Vec<(Sym, Type)>,
     pub returns: Type,
     pub body: Vec<Statement>,
+    pub span: Span,
+}
+
+impl FunctionDecl {
+    // Creates an instance of a generic function where every generic parameter has been replaced
+    // with a concrete type. The instance keeps its concrete types in its generics
+    pub fn instantiate(&self, concrete: &[Type]) -> Self {
+        let mut instance = self.clone();
+        instance.generics = concrete.to_vec();
+        instance.arguments = self
+            .arguments
+            .iter()
+            .map(|(name, ty)| (*name, ty.substitute(&self.generics, concrete)))
+            .collect();
+        instance.returns = self.returns.substitute(&self.generics, concrete);
+
+        for statement in &mut instance.body {
+            statement.substitute(&self.generics, concrete);
+        }
+
+        instance
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -33,7 +56,7 @@ pub struct TypeDecl {
     pub methods: Vec<FunctionDecl>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
     Int,
     Float,
@@ -43,6 +66,8 @@ pub enum Type {
     Infer,
     Any,
     Custom(Sym),
+    Tuple(Vec<Type>),
+    Nullable(Box<Type>),
 }
 
 impl Default for Type {
@@ -51,6 +76,48 @@ impl Default for Type {
     }
 }
 
+impl Type {
+    pub fn name(&self, interner: &StringInterner<Sym>) -> String {
+        match self {
+            Self::Int => "int".to_string(),
+            Self::Float => "float".to_string(),
+            Self::String => "str".to_string(),
+            Self::Bool => "bool".to_string(),
+            Self::Unit => "unit".to_string(),
+            Self::Infer => "_".to_string(),
+            Self::Any => "any".to_string(),
+            Self::Custom(sym) => interner.resolve(*sym).unwrap_or("<unknown>").to_string(),
+            Self::Tuple(types) => format!(
+                "({})",
+                types
+                    .iter()
+                    .map(|ty| ty.name(interner))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Nullable(ty) => format!("nullable<{}>", ty.name(interner)),
+        }
+    }
+
+    // Replaces every generic parameter with the matching concrete type
+    pub fn substitute(&self, generics: &[Type], concrete: &[Type]) -> Self {
+        if let Some(index) = generics.iter().position(|generic| generic == self) {
+            return concrete[index].clone();
+        }
+
+        match self {
+            Self::Tuple(types) => Self::Tuple(
+                types
+                    .iter()
+                    .map(|ty| ty.substitute(generics, concrete))
+                    .collect(),
+            ),
+            Self::Nullable(ty) => Self::Nullable(Box::new(ty.substitute(generics, concrete))),
+            ty => ty.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Import {
     pub source: ImportSource,
@@ -80,7 +147,7 @@ pub enum Visibility {
 
 #[derive(Debug, Clone)]
 pub struct VarDecl {
-    pub name: Sym,
+    pub names: Vec<Sym>,
     pub ty: Type,
     pub expr: Expr,
 }
@@ -92,6 +159,13 @@ pub struct Assign {
     pub ty: AssignType,
 }
 
+#[derive(Debug, Clone)]
+pub struct FieldAssign {
+    pub object: Expr,
+    pub member: Sym,
+    pub expr: Expr,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum AssignType {
     Normal,
@@ -105,6 +179,25 @@ pub struct FunctionCall {
     pub arguments: Vec<Expr>,
 }
 
+#[derive(Debug, Clone)]
+pub struct MethodCall {
+    pub receiver: Box<Expr>,
+    pub name: Sym,
+    pub arguments: Vec<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructLiteral {
+    pub name: Sym,
+    pub fields: Vec<(Sym, Expr)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldAccess {
+    pub object: Box<Expr>,
+    pub member: Sym,
+}
+
 #[derive(Debug, Clone)]
 pub struct Conditional {
     pub _if: Vec<If>,
@@ -156,14 +249,26 @@ pub struct Comparison {
     pub right: Box<Expr>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
+pub struct UnaryOperation {
+    pub op: UnaryOp,
+    pub expr: Box<Expr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Negate,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OperandType {
     Normal,
     Fallible,
     Panicking,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BinaryOp {
     Plus,
     Minus,
@@ -174,7 +279,7 @@ pub enum BinaryOp {
     And,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Comparator {
     Equal,
     NotEqual,
@@ -188,38 +293,69 @@ pub enum Comparator {
 pub enum Literal {
     String(Sym),
     Integer(i32),
+    Float(f64),
     Boolean(bool),
+    Null,
 }
 
 #[derive(Debug, Clone)]
 pub struct Range {
-    start: Box<Expr>,
-    end: Box<Expr>,
+    pub start: Box<Expr>,
+    pub end: Box<Expr>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Return {
-    pub expr: Expr,
+    pub exprs: Vec<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub const fn new(kind: ExprKind, span: Span) -> Self {
+        Self { kind, span }
+    }
 }
 
 #[derive(Debug, Clone)]
-pub enum Expr {
+pub enum ExprKind {
     Literal(Literal),
     Range(Range),
     Comparison(Comparison),
     BinaryOperation(BinaryOperation),
+    UnaryOperation(UnaryOperation),
     FunctionCall(FunctionCall),
+    MethodCall(MethodCall),
+    StructLiteral(StructLiteral),
+    FieldAccess(FieldAccess),
     Ident(Sym),
     Expr(Box<Expr>),
 }
 
 #[derive(Debug, Clone)]
-pub enum Statement {
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+impl Statement {
+    pub const fn new(kind: StatementKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum StatementKind {
     Conditional(Conditional),
     While(While),
     Loop(Loop),
     For(For),
     Assign(Assign),
+    FieldAssign(FieldAssign),
     VarDecl(VarDecl),
     Return(Return),
     Continue,
@@ -227,3 +363,98 @@ pub enum Statement {
     Expr(Expr),
     Empty,
 }
+
+impl Statement {
+    fn substitute(&mut self, generics: &[Type], concrete: &[Type]) {
+        let body = |body: &mut Vec<Statement>| {
+            for statement in body {
+                statement.substitute(generics, concrete);
+            }
+        };
+
+        match &mut self.kind {
+            StatementKind::Conditional(conditional) => {
+                for branch in &mut conditional._if {
+                    branch.condition.substitute(generics, concrete);
+                    body(&mut branch.body);
+                }
+                if let Some(otherwise) = &mut conditional._else {
+                    body(&mut otherwise.body);
+                }
+            }
+            StatementKind::While(while_loop) => {
+                while_loop.condition.substitute(generics, concrete);
+                body(&mut while_loop.body);
+                if let Some(then) = &mut while_loop.then {
+                    body(&mut then.body);
+                }
+            }
+            StatementKind::Loop(loop_loop) => body(&mut loop_loop.body),
+            StatementKind::For(for_loop) => {
+                for_loop.range.substitute(generics, concrete);
+                body(&mut for_loop.body);
+                if let Some(then) = &mut for_loop.then {
+                    body(&mut then.body);
+                }
+            }
+            StatementKind::Assign(assign) => assign.expr.substitute(generics, concrete),
+            StatementKind::FieldAssign(assign) => {
+                assign.object.substitute(generics, concrete);
+                assign.expr.substitute(generics, concrete);
+            }
+            StatementKind::VarDecl(var_decl) => {
+                var_decl.ty = var_decl.ty.substitute(generics, concrete);
+                var_decl.expr.substitute(generics, concrete);
+            }
+            StatementKind::Return(ret) => {
+                for expr in &mut ret.exprs {
+                    expr.substitute(generics, concrete);
+                }
+            }
+            StatementKind::Expr(expr) => expr.substitute(generics, concrete),
+            StatementKind::Continue | StatementKind::Break | StatementKind::Empty => {}
+        }
+    }
+}
+
+impl Expr {
+    fn substitute(&mut self, generics: &[Type], concrete: &[Type]) {
+        match &mut self.kind {
+            ExprKind::Range(range) => {
+                range.start.substitute(generics, concrete);
+                range.end.substitute(generics, concrete);
+            }
+            ExprKind::Comparison(comparison) => {
+                comparison.left.substitute(generics, concrete);
+                comparison.right.substitute(generics, concrete);
+            }
+            ExprKind::BinaryOperation(bin_op) => {
+                bin_op.left.substitute(generics, concrete);
+                bin_op.right.substitute(generics, concrete);
+            }
+            ExprKind::UnaryOperation(unary) => unary.expr.substitute(generics, concrete),
+            ExprKind::FunctionCall(func_call) => {
+                for generic in &mut func_call.generics {
+                    *generic = generic.substitute(generics, concrete);
+                }
+                for argument in &mut func_call.arguments {
+                    argument.substitute(generics, concrete);
+                }
+            }
+            ExprKind::MethodCall(method_call) => {
+                method_call.receiver.substitute(generics, concrete);
+                for argument in &mut method_call.arguments {
+                    argument.substitute(generics, concrete);
+                }
+            }
+            ExprKind::StructLiteral(literal) => {
+                for (_member, expr) in &mut literal.fields {
+                    expr.substitute(generics, concrete);
+                }
+            }
+            ExprKind::FieldAccess(access) => access.object.substitute(generics, concrete),
+            ExprKind::Expr(expr) => expr.substitute(generics, concrete),
+            ExprKind::Literal(_) | ExprKind::Ident(_) => {}
+        }
+    }
+}
@@ -1,11 +1,14 @@
 mod ast;
 mod token;
+mod typecheck;
 
 pub use ast::*;
+pub use typecheck::TypeChecker;
 
-use codespan::{FileId, Files};
+use crate::ErrorCode;
+use codespan::{FileId, Files, Span};
 use codespan_reporting::diagnostic::{Diagnostic, Label, Severity};
-use std::{char, collections::VecDeque, panic::Location};
+use std::{char, collections::VecDeque, convert::TryFrom, panic::Location};
 use string_interner::{StringInterner, Sym};
 use token::*;
 
@@ -22,6 +25,10 @@ pub struct Parser<'a> {
     diagnostics: Vec<Diagnostic>,
     error: bool,
     indent_level: usize,
+    /// Where the lexed source starts within the file, since leading whitespace is trimmed off
+    offset: u32,
+    /// The span of the last token consumed that isn't a newline
+    last_span: Span,
     pub interner: StringInterner<Sym>,
 }
 
@@ -33,7 +40,9 @@ impl<'a> Parser<'a> {
         // to break. Smallest reproducible set:
         // "\r\nfn main()\r\n\t@print \"Welp.\\n\"\r\n"
         // TODO: Fix it, I don't like it lying around
-        let mut token_stream = TokenStream::new(input.trim_start(), true);
+        let trimmed = input.trim_start();
+        let offset = (input.len() - trimmed.len()) as u32;
+        let mut token_stream = TokenStream::new(trimmed, true);
 
         let next = None;
         let peek = token_stream.next_token();
@@ -58,6 +67,8 @@ impl<'a> Parser<'a> {
             diagnostics: Vec::new(),
             error: false,
             indent_level: 0,
+            offset,
+            last_span: Span::new(offset, offset),
             interner: StringInterner::new(),
         }
     }
@@ -65,7 +76,8 @@ impl<'a> Parser<'a> {
     pub fn parse(
         &mut self,
     ) -> std::result::Result<(Vec<Program>, Vec<Diagnostic>), Vec<Diagnostic>> {
-        const TOP_LEVEL_TOKENS: [TokenType; 2] = [TokenType::Import, TokenType::Function];
+        const TOP_LEVEL_TOKENS: [TokenType; 3] =
+            [TokenType::Import, TokenType::Function, TokenType::Type];
 
         let mut ast = Vec::new();
         let mut visibility = None;
@@ -98,6 +110,21 @@ impl<'a> Parser<'a> {
                     ));
                 }
 
+                TokenType::Type => {
+                    info!("Top Level Loop: Type");
+
+                    ast.push(Program::TypeDecl(
+                        match self.type_declaration(visibility.take()) {
+                            Ok(node) => node,
+                            Err(err) => {
+                                self.error = true;
+                                self.diagnostics.push(err);
+                                continue;
+                            }
+                        },
+                    ));
+                }
+
                 TokenType::Import => {
                     info!("Top Level Loop: Import");
                     ast.push(Program::Import(match self.parse_import() {
@@ -126,15 +153,18 @@ impl<'a> Parser<'a> {
                         line!(),
                         column!(),
                     );
-                    self.diagnostics.push(Diagnostic::new(
-                        Severity::Error,
-                        "Invalid token",
-                        Label::new(
-                            self.files[0],
-                            token.range.0 as u32..token.range.1 as u32,
-                            format!("{:?} is not a valid token", token.source),
-                        ),
-                    ));
+                    self.diagnostics.push(
+                        Diagnostic::new(
+                            Severity::Error,
+                            "Invalid token",
+                            Label::new(
+                                self.files[0],
+                                self.span(token),
+                                format!("{:?} is not a valid token", token.source),
+                            ),
+                        )
+                        .with_code(ErrorCode::InvalidToken.code()),
+                    );
                     if self.next().is_err() {
                         break;
                     }
@@ -148,23 +178,26 @@ impl<'a> Parser<'a> {
                         column!(),
                         &t
                     );
-                    self.diagnostics.push(Diagnostic::new(
-                        Severity::Error,
-                        "Invalid top-level token",
-                        Label::new(
-                            self.files[0],
-                            token.range.0 as u32..token.range.1 as u32,
-                            format!(
-                                "Found {:?}, expected one of {}",
-                                token.ty,
-                                TOP_LEVEL_TOKENS
-                                    .iter()
-                                    .map(|t| format!("'{}'", t))
-                                    .collect::<Vec<String>>()
-                                    .join(", "),
+                    self.diagnostics.push(
+                        Diagnostic::new(
+                            Severity::Error,
+                            "Invalid top-level token",
+                            Label::new(
+                                self.files[0],
+                                self.span(token),
+                                format!(
+                                    "Found {:?}, expected one of {}",
+                                    token.ty,
+                                    TOP_LEVEL_TOKENS
+                                        .iter()
+                                        .map(|t| format!("'{}'", t))
+                                        .collect::<Vec<String>>()
+                                        .join(", "),
+                                ),
                             ),
-                        ),
-                    ));
+                        )
+                        .with_code(ErrorCode::InvalidTopLevelToken.code()),
+                    );
                     if self.next().is_err() {
                         break;
                     }
@@ -267,7 +300,8 @@ impl<'a> Parser<'a> {
                             "You must expose something when using the `exposing` keyword"
                                 .to_string(),
                         ),
-                    ));
+                    )
+                    .with_code(ErrorCode::EmptyExposing.code()));
                 }
             }
 
@@ -333,7 +367,8 @@ impl<'a> Parser<'a> {
                                 self.codespan.source_span(self.files[0]),
                                 "You must expose something when using the `exposing` keyword",
                             ),
-                        ));
+                        )
+                        .with_code(ErrorCode::EmptyExposing.code()));
                     }
                 } else {
                     self.eat(TokenType::Newline)?;
@@ -383,6 +418,8 @@ impl<'a> Parser<'a> {
         let mut generics = Vec::new();
 
         if self.peek()?.ty == TokenType::LeftCaret {
+            self.eat(TokenType::LeftCaret)?;
+
             while self.peek()?.ty != TokenType::RightCaret {
                 generics.push(self.parse_type()?);
 
@@ -402,7 +439,8 @@ impl<'a> Parser<'a> {
                         self.codespan.source_span(self.files[0]),
                         "Missing a comma between generic parameters",
                     ),
-                ));
+                )
+                .with_code(ErrorCode::MissingGenericComma.code()));
             }
 
             self.eat(TokenType::RightCaret)?;
@@ -413,6 +451,7 @@ impl<'a> Parser<'a> {
 
     fn loop_loop(&mut self) -> Result<Loop> {
         self.eat(TokenType::Loop)?;
+        self.eat(TokenType::Newline)?;
         let body = self.body()?;
         self.eat(TokenType::EndBlock)?;
 
@@ -439,6 +478,7 @@ impl<'a> Parser<'a> {
         self.eat(TokenType::Newline)?;
         let body = self.body()?;
         let then = self.then()?;
+        self.eat(TokenType::EndBlock)?;
 
         Ok(While {
             condition,
@@ -451,9 +491,34 @@ impl<'a> Parser<'a> {
         self.eat(TokenType::For)?;
         let element = self.eat(TokenType::Ident)?;
         let element = self.intern(element.source);
-        let range = self.expr()?;
+
+        // `in` is matched by its source, since keyword tokens would split identifiers like `index`
+        let token = self.next()?;
+        if token.ty != TokenType::Ident || token.source != "in" {
+            self.error = true;
+            return Err(Diagnostic::new(
+                Severity::Error,
+                format!("Unexpected Token: Expected 'in', found '{}'", token.ty),
+                Label::new(self.files[0], self.span(token), "Expected in".to_string()),
+            )
+            .with_code(ErrorCode::UnexpectedToken.code()));
+        }
+
+        let start = self.expr()?;
+        self.eat(TokenType::DotDot)?;
+        let end = self.expr()?;
+        let span = start.span.merge(end.span);
+        let range = Expr::new(
+            ExprKind::Range(Range {
+                start: Box::new(start),
+                end: Box::new(end),
+            }),
+            span,
+        );
+        self.eat(TokenType::Newline)?;
         let body = self.body()?;
         let then = self.then()?;
+        self.eat(TokenType::EndBlock)?;
 
         Ok(For {
             element,
@@ -465,12 +530,31 @@ impl<'a> Parser<'a> {
 
     fn variable_decl(&mut self) -> Result<VarDecl> {
         self.eat(TokenType::Let)?;
-        let name = self.eat(TokenType::Ident)?;
-        let name = self.intern(name.source);
-        let ty = self.parse_type()?;
+
+        let mut names = Vec::new();
+        loop {
+            let name = self.eat(TokenType::Ident)?;
+            names.push(self.intern(name.source));
+
+            if self.peek()?.ty == TokenType::Comma {
+                self.eat(TokenType::Comma)?;
+            } else {
+                break;
+            }
+        }
+
+        let ty = if self.peek()?.ty == TokenType::Colon {
+            self.eat(TokenType::Colon)?;
+            self.parse_type()?
+        } else {
+            Type::Infer
+        };
+
+        self.eat(TokenType::Equal)?;
         let expr = self.expr()?;
+        self.eat(TokenType::Newline)?;
 
-        Ok(VarDecl { name, ty, expr })
+        Ok(VarDecl { names, ty, expr })
     }
 
     fn optionally_typed_argument(&mut self) -> Result<Vec<(Sym, Type)>> {
@@ -497,13 +581,7 @@ impl<'a> Parser<'a> {
 
         let mut params = Vec::new();
         while self.peek()?.ty != TokenType::RightParen {
-            params.push(if self.peek()?.ty == TokenType::Colon {
-                self.eat(TokenType::Colon)?;
-
-                self.expr()?
-            } else {
-                break;
-            });
+            params.push(self.expr()?);
 
             if self.peek()?.ty == TokenType::Comma {
                 self.eat(TokenType::Comma)?;
@@ -548,41 +626,350 @@ impl<'a> Parser<'a> {
             let ident = self.eat(TokenType::Ident)?;
             self.intern(ident.source)
         };
-        let expr = self.expr()?;
         let ty = self.assign_type()?;
+        let expr = self.expr()?;
+        self.eat(TokenType::Newline)?;
 
         Ok(Assign { var, expr, ty })
     }
 
     fn expr(&mut self) -> Result<Expr> {
-        let expr = match self.peek()?.ty {
+        self.expr_precedence(Precedence::None)
+    }
+
+    /// Parses an expression whose infix operators all bind tighter than `precedence`
+    fn expr_precedence(&mut self, precedence: Precedence) -> Result<Expr> {
+        let mut expr = self.prefix_expr()?;
+
+        // The last operator of this chain, used to reject chains that can be read multiple ways
+        let mut last: Option<(Infix, Token<'a>)> = None;
+        while let Some(infix) = self.infix()? {
+            if infix.precedence() <= precedence {
+                break;
+            }
+
+            let operator = self.next()?;
+            if let Some((last, last_operator)) = last {
+                self.ambiguous_chain((last, last_operator), (infix, operator))?;
+            }
+
+            let infix = match infix {
+                Infix::Binary(op, _) if infix.precedence() != Precedence::Logical => {
+                    Infix::Binary(op, self.operand_type(operator)?)
+                }
+                infix => infix,
+            };
+            let right = Box::new(self.expr_precedence(infix.precedence())?);
+
+            let span = expr.span.merge(right.span);
+            let kind = match infix {
+                Infix::Binary(op, ty) => ExprKind::BinaryOperation(BinaryOperation {
+                    left: Box::new(expr),
+                    op: (op, ty),
+                    right,
+                }),
+                Infix::Comparison(comparison) => ExprKind::Comparison(Comparison {
+                    left: Box::new(expr),
+                    comparison,
+                    right,
+                }),
+            };
+            expr = Expr::new(kind, span);
+            last = Some((infix, operator));
+        }
+
+        Ok(expr)
+    }
+
+    fn prefix_expr(&mut self) -> Result<Expr> {
+        let token = self.peek()?;
+        let op = match token.ty {
+            TokenType::Minus => UnaryOp::Negate,
+            TokenType::Ident if token.source == "not" => UnaryOp::Not,
+            _ => return self.primary_expr(),
+        };
+        self.next()?;
+        let start = self.span(token);
+
+        // Negative integers are read along with their sign, since `-2147483648` only fits in an
+        // `int` once it's negative
+        if op == UnaryOp::Negate && self.peek()?.ty == TokenType::Int {
+            let int = self.next()?;
+            let literal = self.integer(int, true)?;
+
+            return Ok(Expr::new(ExprKind::Literal(literal), self.span_from(start)));
+        }
+
+        let expr = self.expr_precedence(Precedence::Prefix)?;
+        let span = start.merge(expr.span);
+
+        let kind = match (op, expr.kind) {
+            // Negative literals are folded so that they can be loaded directly
+            (UnaryOp::Negate, ExprKind::Literal(Literal::Float(float))) => {
+                ExprKind::Literal(Literal::Float(-float))
+            }
+            (op, kind) => ExprKind::UnaryOperation(UnaryOperation {
+                op,
+                expr: Box::new(Expr::new(kind, expr.span)),
+            }),
+        };
+
+        Ok(Expr::new(kind, span))
+    }
+
+    fn primary_expr(&mut self) -> Result<Expr> {
+        let token = self.peek()?;
+        let start = self.span(token);
+        let kind = match token.ty {
             TokenType::LeftParen => {
                 self.eat(TokenType::LeftParen)?;
                 let expr = self.expr()?;
                 self.eat(TokenType::RightParen)?;
-                Expr::Expr(Box::new(expr))
+                ExprKind::Expr(Box::new(expr))
             }
 
             TokenType::Ident => {
                 let ident = self.eat(TokenType::Ident)?;
+                if ident.source == "null" {
+                    return Ok(Expr::new(ExprKind::Literal(Literal::Null), start));
+                }
+                let ident = self.intern(ident.source);
 
-                if self.peek()?.ty == TokenType::LeftParen {
-                    let ident = self.intern(ident.source);
-                    Expr::FunctionCall(self.function_call(ident)?)
-                } else {
-                    Expr::Ident(self.intern(ident.source))
+                match self.peek()?.ty {
+                    TokenType::LeftParen => ExprKind::FunctionCall(self.function_call(ident)?),
+                    TokenType::LeftCaret if self.is_generic_call() => {
+                        ExprKind::FunctionCall(self.function_call(ident)?)
+                    }
+                    TokenType::LeftBracket => ExprKind::StructLiteral(self.struct_literal(ident)?),
+                    _ => ExprKind::Ident(ident),
                 }
             }
 
-            TokenType::String | TokenType::Int | TokenType::Bool => {
-                Expr::Literal(self.parse_literal()?)
+            TokenType::String | TokenType::Int | TokenType::Float | TokenType::Bool => {
+                ExprKind::Literal(self.parse_literal()?)
+            }
+            TokenType::Bang | TokenType::QuestionMark => return Err(self.misplaced_modifier()?),
+            _ => return Err(self.expected_expression(token)),
+        };
+
+        let expr = Expr::new(kind, self.span_from(start));
+        self.member_access(expr)
+    }
+
+    /// Peeks at the infix operator that continues an expression, if there is one
+    fn infix(&mut self) -> Result<Option<Infix>> {
+        let token = self.peek()?;
+
+        let infix = match token.ty {
+            TokenType::Plus => Infix::Binary(BinaryOp::Plus, OperandType::Normal),
+            TokenType::Minus => Infix::Binary(BinaryOp::Minus, OperandType::Normal),
+            TokenType::Star => Infix::Binary(BinaryOp::Mult, OperandType::Normal),
+            TokenType::Divide => Infix::Binary(BinaryOp::Div, OperandType::Normal),
+            TokenType::Ident if token.source == "and" => {
+                Infix::Binary(BinaryOp::And, OperandType::Normal)
+            }
+            TokenType::Ident if token.source == "or" => {
+                Infix::Binary(BinaryOp::Or, OperandType::Normal)
+            }
+            TokenType::Ident if token.source == "xor" => {
+                Infix::Binary(BinaryOp::Xor, OperandType::Normal)
             }
-            _ => todo!("Implement the rest of the expressions"),
+
+            TokenType::IsEqual => Infix::Comparison(Comparator::Equal),
+            TokenType::NotEqual => Infix::Comparison(Comparator::NotEqual),
+            TokenType::LessEqual => Infix::Comparison(Comparator::LessEqual),
+            TokenType::GreaterEqual => Infix::Comparison(Comparator::GreaterEqual),
+            TokenType::LeftCaret => Infix::Comparison(Comparator::Less),
+            TokenType::RightCaret => Infix::Comparison(Comparator::Greater),
+
+            _ => return Ok(None),
+        };
+
+        Ok(Some(infix))
+    }
+
+    /// Parses the `?` or `!` directly after an arithmetic operator that makes it fallible or
+    /// panicking, e.g. `+?` or `/!`
+    fn operand_type(&mut self, operator: Token<'a>) -> Result<OperandType> {
+        let next = self.peek()?;
+        if next.range.0 != operator.range.1 {
+            return Ok(OperandType::Normal);
+        }
+
+        Ok(match next.ty {
+            TokenType::QuestionMark => {
+                self.eat(TokenType::QuestionMark)?;
+                OperandType::Fallible
+            }
+            TokenType::Bang => {
+                self.eat(TokenType::Bang)?;
+                OperandType::Panicking
+            }
+            _ => OperandType::Normal,
+        })
+    }
+
+    /// Errors on chains of operators that don't have an obvious reading, like `a < b < c` or
+    /// `a and b or c`
+    fn ambiguous_chain(
+        &mut self,
+        (last, last_operator): (Infix, Token<'a>),
+        (infix, operator): (Infix, Token<'a>),
+    ) -> Result<()> {
+        let (message, note) = match (last, infix) {
+            (Infix::Comparison(_), Infix::Comparison(_)) => (
+                "Comparisons can't be chained",
+                "Join the comparisons with `and` or add parentheses",
+            ),
+            (Infix::Binary(last, _), Infix::Binary(op, _))
+                if last != op && infix.precedence() == Precedence::Logical =>
+            {
+                (
+                    "Logical operators can't be mixed without parentheses",
+                    "Add parentheses to show which operator applies first",
+                )
+            }
+            _ => return Ok(()),
+        };
+
+        self.error = true;
+        Err(Diagnostic::new(
+            Severity::Error,
+            message,
+            Label::new(
+                self.files[0],
+                self.span(operator),
+                format!("`{}` follows `{}`", operator.source, last_operator.source),
+            ),
+        )
+        .with_code(ErrorCode::AmbiguousOperators.code())
+        .with_notes(vec![note.to_string()]))
+    }
+
+    /// Reports a `!` or `?` that starts an expression, which are only valid after an operator
+    fn misplaced_modifier(&mut self) -> Result<Diagnostic> {
+        let token = self.next()?;
+        let note = if token.ty == TokenType::Bang {
+            "Use `not` to negate a value, `!` only marks an operator as panicking, as in `a +! b`"
+        } else {
+            "`?` only marks an operator as fallible, as in `a +? b`"
         };
 
+        self.error = true;
+        Ok(Diagnostic::new(
+            Severity::Error,
+            format!("Expressions can't start with `{}`", token.source),
+            Label::new(
+                self.files[0],
+                self.span(token),
+                format!("`{}` must follow an operator", token.source),
+            ),
+        )
+        .with_code(ErrorCode::UnexpectedToken.code())
+        .with_notes(vec![note.to_string()]))
+    }
+
+    /// The error for a token that can't start an expression, which is left for the caller
+    fn expected_expression(&mut self, token: Token<'_>) -> Diagnostic {
+        self.error = true;
+        Diagnostic::new(
+            Severity::Error,
+            format!("Expected an expression, found '{}'", token.ty),
+            Label::new(
+                self.files[0],
+                self.span(token),
+                "Expected an expression".to_string(),
+            ),
+        )
+        .with_code(ErrorCode::UnexpectedToken.code())
+    }
+
+    /// Whether the `<` after an identifier opens the generics of a function call rather than
+    /// being a comparison, found by parsing ahead and then rewinding. `a < b > (c)` is read as a
+    /// call, the comparison would be an invalid chain anyway
+    fn is_generic_call(&mut self) -> bool {
+        let (token_stream, next, peek, error, last_span) = (
+            self.token_stream.clone(),
+            self.next,
+            self.peek,
+            self.error,
+            self.last_span,
+        );
+
+        let is_call = self.generics().is_ok()
+            && matches!(self.peek(), Ok(token) if token.ty == TokenType::LeftParen);
+
+        self.token_stream = token_stream;
+        self.next = next;
+        self.peek = peek;
+        self.error = error;
+        self.last_span = last_span;
+
+        is_call
+    }
+
+    /// Parses the member accesses and method calls chained onto an expression
+    fn member_access(&mut self, mut expr: Expr) -> Result<Expr> {
+        while self.peek()?.ty == TokenType::Dot {
+            self.eat(TokenType::Dot)?;
+
+            let member = self.eat(TokenType::Ident)?;
+            let member = self.intern(member.source);
+
+            let start = expr.span;
+            let kind = if self.peek()?.ty == TokenType::LeftParen {
+                ExprKind::MethodCall(MethodCall {
+                    receiver: Box::new(expr),
+                    name: member,
+                    arguments: self.function_call_arguments()?,
+                })
+            } else {
+                ExprKind::FieldAccess(FieldAccess {
+                    object: Box::new(expr),
+                    member,
+                })
+            };
+            expr = Expr::new(kind, self.span_from(start));
+        }
+
         Ok(expr)
     }
 
+    fn struct_literal(&mut self, name: Sym) -> Result<StructLiteral> {
+        self.eat(TokenType::LeftBracket)?;
+
+        // The members of a literal can be spread over multiple lines
+        let mut fields = Vec::new();
+        loop {
+            while self.peek()?.ty == TokenType::Newline {
+                self.eat(TokenType::Newline)?;
+            }
+
+            if self.peek()?.ty == TokenType::RightBracket {
+                break;
+            }
+
+            let member = self.eat(TokenType::Ident)?;
+            let member = self.intern(member.source);
+            self.eat(TokenType::Colon)?;
+            fields.push((member, self.expr()?));
+
+            if self.peek()?.ty == TokenType::Comma {
+                self.eat(TokenType::Comma)?;
+            } else {
+                while self.peek()?.ty == TokenType::Newline {
+                    self.eat(TokenType::Newline)?;
+                }
+
+                break;
+            }
+        }
+
+        self.eat(TokenType::RightBracket)?;
+
+        Ok(StructLiteral { name, fields })
+    }
+
     fn conditional_clause(&mut self) -> Result<Either<If, Vec<Statement>>> {
         #[derive(Debug, Copy, Clone, PartialEq, Eq)]
         enum CondType {
@@ -647,33 +1034,98 @@ impl<'a> Parser<'a> {
         let mut statements = Vec::new();
 
         loop {
-            let statement = match self.peek()?.ty {
-                TokenType::If => Statement::Conditional(self.conditional()?),
-                TokenType::While => Statement::While(self.while_loop()?),
-                TokenType::Loop => Statement::Loop(self.loop_loop()?),
-                TokenType::For => Statement::For(self.for_loop()?),
-                TokenType::Let => Statement::VarDecl(self.variable_decl()?),
+            let token = self.peek()?;
+            let start = self.span(token);
+            let kind = match token.ty {
+                TokenType::Newline => {
+                    self.eat(TokenType::Newline)?;
+                    continue;
+                }
+
+                TokenType::If => StatementKind::Conditional(self.conditional()?),
+                TokenType::While => StatementKind::While(self.while_loop()?),
+                TokenType::Loop => StatementKind::Loop(self.loop_loop()?),
+                TokenType::For => StatementKind::For(self.for_loop()?),
+                TokenType::Let => StatementKind::VarDecl(self.variable_decl()?),
                 TokenType::Ident => {
                     let ident = self.eat(TokenType::Ident)?;
                     let ident = self.intern(ident.source);
 
                     match self.peek()?.ty {
-                        TokenType::LeftParen => {
-                            Statement::Expr(Expr::FunctionCall(self.function_call(ident)?))
+                        TokenType::LeftParen | TokenType::LeftCaret => {
+                            let call = self.function_call(ident)?;
+                            let call =
+                                Expr::new(ExprKind::FunctionCall(call), self.span_from(start));
+                            self.eat(TokenType::Newline)?;
+
+                            StatementKind::Expr(call)
+                        }
+                        TokenType::Equal => StatementKind::Assign(self.assign(ident)?),
+                        TokenType::Dot => {
+                            let expr =
+                                self.member_access(Expr::new(ExprKind::Ident(ident), start))?;
+
+                            let statement = match expr.kind {
+                                ExprKind::FieldAccess(access)
+                                    if self.peek()?.ty == TokenType::Equal =>
+                                {
+                                    self.eat(TokenType::Equal)?;
+
+                                    StatementKind::FieldAssign(FieldAssign {
+                                        object: *access.object,
+                                        member: access.member,
+                                        expr: self.expr()?,
+                                    })
+                                }
+                                kind => StatementKind::Expr(Expr::new(kind, expr.span)),
+                            };
+                            self.eat(TokenType::Newline)?;
+
+                            statement
                         }
-                        TokenType::Equal => Statement::Assign(self.assign(ident)?),
                         _ => todo!("Write the error"),
                     }
                 }
-                TokenType::Return => Statement::Return(Return { expr: self.expr()? }),
-                TokenType::Continue => Statement::Continue,
-                TokenType::Break => Statement::Break,
-                TokenType::Empty => Statement::Empty,
+                TokenType::Return => {
+                    self.eat(TokenType::Return)?;
+
+                    let mut exprs = Vec::new();
+                    while self.peek()?.ty != TokenType::Newline {
+                        exprs.push(self.expr()?);
+
+                        if self.peek()?.ty == TokenType::Comma {
+                            self.eat(TokenType::Comma)?;
+                        } else {
+                            break;
+                        }
+                    }
+                    self.eat(TokenType::Newline)?;
+
+                    StatementKind::Return(Return { exprs })
+                }
+                TokenType::Continue => {
+                    self.eat(TokenType::Continue)?;
+                    self.eat(TokenType::Newline)?;
+
+                    StatementKind::Continue
+                }
+                TokenType::Break => {
+                    self.eat(TokenType::Break)?;
+                    self.eat(TokenType::Newline)?;
+
+                    StatementKind::Break
+                }
+                TokenType::Empty => {
+                    self.eat(TokenType::Empty)?;
+                    self.eat(TokenType::Newline)?;
+
+                    StatementKind::Empty
+                }
                 // TODO: ( Expr '\n' )
                 _ => break,
             };
 
-            statements.push(statement);
+            statements.push(Statement::new(kind, self.span_from(start)));
         }
 
         Ok(statements)
@@ -682,13 +1134,16 @@ impl<'a> Parser<'a> {
     fn function_declaration(&mut self, visibility: Option<Visibility>) -> Result<FunctionDecl> {
         info!("Parsing Function");
 
-        self.eat(TokenType::Function)?;
+        let token = self.eat(TokenType::Function)?;
+        let start = self.span(token);
 
         let name = self.eat(TokenType::Ident)?;
         let name = self.intern(name.source);
-        let generics = self.generics()?;
-        let arguments = self.function_arguments()?;
+        let mut generics = self.generics()?;
+        let arguments = self.function_arguments(&mut generics)?;
         let returns = self.function_return()?;
+        // Functions are pointed at by their signature rather than their entire body
+        let span = self.span_from(start);
 
         self.eat(TokenType::Newline)?;
 
@@ -705,15 +1160,87 @@ impl<'a> Parser<'a> {
             arguments,
             returns,
             body,
+            span,
+        })
+    }
+
+    fn type_declaration(&mut self, visibility: Option<Visibility>) -> Result<TypeDecl> {
+        info!("Parsing Type Declaration");
+
+        self.eat(TokenType::Type)?;
+
+        let name = self.eat(TokenType::Ident)?;
+        let name = self.intern(name.source);
+        let generics = self.generics()?;
+
+        self.eat(TokenType::Newline)?;
+
+        let (mut members, mut methods) = (Vec::new(), Vec::new());
+        loop {
+            match self.peek()?.ty {
+                TokenType::Newline => {
+                    self.eat(TokenType::Newline)?;
+                }
+                TokenType::Ident => {
+                    members.push(self.parse_typed_argument()?);
+                    self.eat(TokenType::Newline)?;
+                }
+                TokenType::Function => methods.push(self.function_declaration(None)?),
+                _ => break,
+            }
+        }
+
+        self.eat(TokenType::EndBlock)?;
+
+        info!("Finished parsing Type Declaration");
+
+        Ok(TypeDecl {
+            visibility: visibility.unwrap_or(Visibility::Library),
+            name,
+            generics,
+            members,
+            methods,
         })
     }
 
-    fn function_arguments(&mut self) -> Result<Vec<(Sym, Type)>> {
+    /// Parses the arguments of a function declaration. Arguments without a type are given a
+    /// generic type of their own, which is added to `generics`
+    fn function_arguments(&mut self, generics: &mut Vec<Type>) -> Result<Vec<(Sym, Type)>> {
         self.eat(TokenType::LeftParen)?;
 
         let mut params = Vec::new();
         while self.peek()?.ty != TokenType::RightParen {
-            params.push(self.parse_typed_argument()?);
+            // Methods take `self` as their first argument, which is of the type they belong to
+            // unless it's given a type
+            if params.is_empty() && self.peek()?.source == "self" {
+                let name = self.eat(TokenType::Ident)?;
+                let name = self.intern(name.source);
+
+                let ty = if self.peek()?.ty == TokenType::Colon {
+                    self.eat(TokenType::Colon)?;
+                    self.parse_type()?
+                } else {
+                    Type::Custom(self.intern("Self"))
+                };
+
+                params.push((name, ty));
+            } else {
+                let name = self.eat(TokenType::Ident)?;
+
+                let ty = if self.peek()?.ty == TokenType::Colon {
+                    self.eat(TokenType::Colon)?;
+                    self.parse_type()?
+                } else {
+                    // The generic can't clash with a user's type, since identifiers can't
+                    // contain spaces
+                    let generic = Type::Custom(self.intern(&format!("typeof {}", name.source)));
+                    generics.push(generic.clone());
+
+                    generic
+                };
+
+                params.push((self.intern(name.source), ty));
+            }
 
             if self.peek()?.ty == TokenType::Comma {
                 self.eat(TokenType::Comma)?;
@@ -744,7 +1271,8 @@ impl<'a> Parser<'a> {
         let token = self.next()?;
 
         let literal = match token.ty {
-            TokenType::Int => Literal::Integer(token.source.parse().unwrap()),
+            TokenType::Int => self.integer(token, false)?,
+            TokenType::Float => Literal::Float(token.source.parse().unwrap()),
             TokenType::String => Literal::String({
                 let string = &self.escape_string(&(&*token.source)[1..token.source.len() - 1])?;
                 self.intern(string)
@@ -758,6 +1286,36 @@ impl<'a> Parser<'a> {
         Ok(literal)
     }
 
+    /// Reads an integer literal, which has to fit in an `int` once its sign is applied
+    fn integer(&mut self, token: Token<'_>, negative: bool) -> Result<Literal> {
+        let int = token
+            .source
+            .parse::<i64>()
+            .ok()
+            .map(|int| if negative { -int } else { int })
+            .and_then(|int| i32::try_from(int).ok());
+
+        if let Some(int) = int {
+            Ok(Literal::Integer(int))
+        } else {
+            self.error = true;
+            Err(Diagnostic::new(
+                Severity::Error,
+                format!(
+                    "The integer `{}{}` doesn't fit in an `int`",
+                    if negative { "-" } else { "" },
+                    token.source
+                ),
+                Label::new(
+                    self.files[0],
+                    self.span(token),
+                    format!("Integers must be from {} to {}", i32::MIN, i32::MAX),
+                ),
+            )
+            .with_code(ErrorCode::LiteralOverflow.code()))
+        }
+    }
+
     #[inline]
     fn parse_typed_argument(&mut self) -> Result<(Sym, Type)> {
         info!("Parsing Named Parameter");
@@ -777,12 +1335,40 @@ impl<'a> Parser<'a> {
     fn parse_type(&mut self) -> Result<Type> {
         info!("Parsing Type");
 
+        if self.peek()?.ty == TokenType::LeftParen {
+            self.eat(TokenType::LeftParen)?;
+
+            let mut types = Vec::new();
+            while self.peek()?.ty != TokenType::RightParen {
+                types.push(self.parse_type()?);
+
+                if self.peek()?.ty == TokenType::Comma {
+                    self.eat(TokenType::Comma)?;
+                } else {
+                    break;
+                }
+            }
+            self.eat(TokenType::RightParen)?;
+
+            info!("Finished parsing Type");
+
+            return Ok(Type::Tuple(types));
+        }
+
         let ty = match self.eat(TokenType::Ident)?.source {
             "unit" => Type::Unit,
             "str" => Type::String,
             "int" => Type::Int,
+            "float" => Type::Float,
             "bool" => Type::Bool,
             "any" => Type::Any,
+            "nullable" => {
+                self.eat(TokenType::LeftCaret)?;
+                let ty = self.parse_type()?;
+                self.eat(TokenType::RightCaret)?;
+
+                Type::Nullable(Box::new(ty))
+            }
             custom => Type::Custom(self.intern(custom)),
         };
 
@@ -794,6 +1380,19 @@ impl<'a> Parser<'a> {
 
 /// Parsing utilities
 impl<'a> Parser<'a> {
+    /// The span of a token within the source file
+    #[inline]
+    fn span(&self, token: Token<'_>) -> Span {
+        Span::new(token.range.0 + self.offset, token.range.1 + self.offset)
+    }
+
+    /// The span from the start of `start` to the end of the last token consumed, not counting
+    /// newlines
+    #[inline]
+    fn span_from(&self, start: Span) -> Span {
+        start.merge(self.last_span)
+    }
+
     #[inline]
     #[track_caller]
     fn next(&mut self) -> Result<Token<'a>> {
@@ -804,6 +1403,10 @@ impl<'a> Parser<'a> {
         self.next = next;
 
         if let Some(next) = next {
+            if next.ty != TokenType::Newline {
+                self.last_span = self.span(next);
+            }
+
             trace!(
                 "[{} {}:{}] Got token: {:?}",
                 loc.file(),
@@ -830,7 +1433,8 @@ impl<'a> Parser<'a> {
                     },
                     "Unexpected End Of File".to_string(),
                 ),
-            ))
+            )
+            .with_code(ErrorCode::UnexpectedEof.code()))
         }
     }
 
@@ -866,7 +1470,8 @@ impl<'a> Parser<'a> {
                     },
                     "Unexpected End Of File".to_string(),
                 ),
-            ))
+            )
+            .with_code(ErrorCode::UnexpectedEof.code()))
         }
     }
 
@@ -904,10 +1509,11 @@ impl<'a> Parser<'a> {
                 ),
                 Label::new(
                     self.files[0],
-                    token.range.0 as u32..token.range.1 as u32,
+                    self.span(token),
                     format!("Expected {}", expected),
                 ),
-            ))
+            )
+            .with_code(ErrorCode::UnexpectedToken.code()))
         }
     }
 
@@ -940,10 +1546,11 @@ impl<'a> Parser<'a> {
                 ),
                 Label::new(
                     self.files[0],
-                    self.codespan.source_span(self.files[0]),
+                    self.span(token),
                     format!("Unexpected Token: {:?}", token.ty),
                 ),
-            ))
+            )
+            .with_code(ErrorCode::UnexpectedToken.code()))
         }
     }
 
@@ -961,7 +1568,8 @@ impl<'a> Parser<'a> {
                 self.codespan.source_span(self.files[0]),
                 "Invalid Escape String".to_string(),
             ),
-        );
+        )
+        .with_code(ErrorCode::InvalidEscape.code());
 
         while let Some(c) = queue.pop_front() {
             if c != '\\' {
@@ -1005,6 +1613,34 @@ impl<'a> Parser<'a> {
     }
 }
 
+/// How tightly infix operators bind, from the loosest to the tightest
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    None,
+    Logical,
+    Comparison,
+    Sum,
+    Product,
+    Prefix,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Infix {
+    Binary(BinaryOp, OperandType),
+    Comparison(Comparator),
+}
+
+impl Infix {
+    fn precedence(self) -> Precedence {
+        match self {
+            Self::Binary(BinaryOp::And | BinaryOp::Or | BinaryOp::Xor, _) => Precedence::Logical,
+            Self::Comparison(_) => Precedence::Comparison,
+            Self::Binary(BinaryOp::Plus | BinaryOp::Minus, _) => Precedence::Sum,
+            Self::Binary(BinaryOp::Mult | BinaryOp::Div, _) => Precedence::Product,
+        }
+    }
+}
+
 fn unescape_unicode(queue: &mut VecDeque<char>) -> Option<char> {
     trace!("Parsing Unicode Escape");
     let mut s = String::with_capacity(4);
@@ -1152,7 +1788,7 @@ mod tests {
                     match crate::interpreter::Interpreter::from_interner(&options, parser.interner)
                         .interpret(ast.0.clone())
                     {
-                        Ok(interp) => interp,
+                        Ok((interp, _debug)) => interp,
                         Err(err) => {
                             err.emit();
                             panic!("Runtime error while compiling");
@@ -1183,4 +1819,274 @@ mod tests {
             }
         }
     }
+
+    /// Renders an expression with every operation wrapped in parentheses
+    fn render(expr: &Expr, interner: &StringInterner<Sym>) -> String {
+        let operand = |ty| match ty {
+            OperandType::Normal => "",
+            OperandType::Fallible => "?",
+            OperandType::Panicking => "!",
+        };
+
+        match &expr.kind {
+            ExprKind::BinaryOperation(bin_op) => {
+                let op = match bin_op.op.0 {
+                    BinaryOp::Plus => "+",
+                    BinaryOp::Minus => "-",
+                    BinaryOp::Mult => "*",
+                    BinaryOp::Div => "/",
+                    BinaryOp::And => "and",
+                    BinaryOp::Or => "or",
+                    BinaryOp::Xor => "xor",
+                };
+
+                format!(
+                    "({} {}{} {})",
+                    render(&bin_op.left, interner),
+                    op,
+                    operand(bin_op.op.1),
+                    render(&bin_op.right, interner)
+                )
+            }
+            ExprKind::Comparison(comparison) => {
+                let comparator = match comparison.comparison {
+                    Comparator::Equal => "==",
+                    Comparator::NotEqual => "!=",
+                    Comparator::LessEqual => "<=",
+                    Comparator::GreaterEqual => ">=",
+                    Comparator::Less => "<",
+                    Comparator::Greater => ">",
+                };
+
+                format!(
+                    "({} {} {})",
+                    render(&comparison.left, interner),
+                    comparator,
+                    render(&comparison.right, interner)
+                )
+            }
+            ExprKind::UnaryOperation(unary) => match unary.op {
+                UnaryOp::Negate => format!("(-{})", render(&unary.expr, interner)),
+                UnaryOp::Not => format!("(not {})", render(&unary.expr, interner)),
+            },
+            ExprKind::FunctionCall(func_call) => format!(
+                "{}{}({})",
+                interner.resolve(func_call.name).unwrap(),
+                if func_call.generics.is_empty() {
+                    String::new()
+                } else {
+                    format!("<{}>", func_call.generics.len())
+                },
+                func_call
+                    .arguments
+                    .iter()
+                    .map(|argument| render(argument, interner))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ExprKind::Literal(Literal::Integer(int)) => int.to_string(),
+            ExprKind::Literal(Literal::Float(float)) => float.to_string(),
+            ExprKind::Literal(Literal::Boolean(boolean)) => boolean.to_string(),
+            ExprKind::Ident(ident) => interner.resolve(*ident).unwrap().to_string(),
+            ExprKind::Expr(expr) => render(expr, interner),
+            kind => panic!("Unexpected expression {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn operator_precedence() {
+        const CODE: &str = "
+fn main()
+    let a = 1 + 2 * 3 - 4 / 5
+    let b = not x and y < -z
+    let c = identity<int>(1) < 2 or x
+    let d = x +? y *! z -? 1
+    let e = -(x + 1) >= 2 * -1.5
+    let f = x < y
+    let g = x != y xor x <= (y > z)
+end
+";
+
+        let mut parser = Parser::new(None, CODE);
+        let (ast, _diagnostics) = parser.parse().expect("The source should parse");
+
+        let exprs = match &ast[0] {
+            Program::FunctionDecl(func) => func
+                .body
+                .iter()
+                .map(|statement| match &statement.kind {
+                    StatementKind::VarDecl(decl) => render(&decl.expr, &parser.interner),
+                    kind => panic!("Unexpected statement {:?}", kind),
+                })
+                .collect::<Vec<_>>(),
+            program => panic!("Unexpected program {:?}", program),
+        };
+
+        assert_eq!(
+            exprs,
+            [
+                "((1 + (2 * 3)) - (4 / 5))",
+                "((not x) and (y < (-z)))",
+                "((identity<1>(1) < 2) or x)",
+                "((x +? (y *! z)) -? 1)",
+                "((-(x + 1)) >= (2 * -1.5))",
+                "(x < y)",
+                "((x != y) xor (x <= (y > z)))",
+            ]
+        );
+    }
+
+    #[test]
+    fn integer_literals() {
+        const CODE: &str = "
+fn main()
+    let a = -2147483648
+    let b = 2147483647
+    let c = -1 * 2
+    let d = --2
+end
+";
+
+        let mut parser = Parser::new(None, CODE);
+        let (ast, _diagnostics) = parser.parse().expect("The source should parse");
+
+        let exprs = match &ast[0] {
+            Program::FunctionDecl(func) => func
+                .body
+                .iter()
+                .map(|statement| match &statement.kind {
+                    StatementKind::VarDecl(decl) => render(&decl.expr, &parser.interner),
+                    kind => panic!("Unexpected statement {:?}", kind),
+                })
+                .collect::<Vec<_>>(),
+            program => panic!("Unexpected program {:?}", program),
+        };
+
+        assert_eq!(exprs, ["-2147483648", "2147483647", "(-1 * 2)", "(--2)"]);
+
+        for (int, message) in &[
+            (
+                "2147483648",
+                "The integer `2147483648` doesn't fit in an `int`",
+            ),
+            (
+                "-2147483649",
+                "The integer `-2147483649` doesn't fit in an `int`",
+            ),
+            (
+                "99999999999999999999",
+                "The integer `99999999999999999999` doesn't fit in an `int`",
+            ),
+        ] {
+            let code = format!("fn main()\n    let a = {}\nend\n", int);
+            let mut parser = Parser::new(None, &code);
+
+            let diagnostics = parser.parse().unwrap_err();
+            assert_eq!(diagnostics[0].message, *message);
+            assert_eq!(diagnostics[0].code.as_deref(), Some("E0009"));
+        }
+    }
+
+    #[test]
+    fn spans() {
+        const CODE: &str = "
+fn double(x: int) -> int
+    let y = x +! (2 * x)
+    return y
+end
+";
+
+        let mut parser = Parser::new(None, CODE);
+        let (ast, _diagnostics) = parser.parse().expect("The source should parse");
+        let source = |span| parser.codespan.source_slice(parser.files[0], span).unwrap();
+
+        let func = match &ast[0] {
+            Program::FunctionDecl(func) => func,
+            program => panic!("Unexpected program {:?}", program),
+        };
+        assert_eq!(source(func.span), "fn double(x: int) -> int");
+        assert_eq!(source(func.body[0].span), "let y = x +! (2 * x)");
+        assert_eq!(source(func.body[1].span), "return y");
+
+        let expr = match &func.body[0].kind {
+            StatementKind::VarDecl(decl) => &decl.expr,
+            kind => panic!("Unexpected statement {:?}", kind),
+        };
+        assert_eq!(source(expr.span), "x +! (2 * x)");
+        match &expr.kind {
+            ExprKind::BinaryOperation(bin_op) => {
+                assert_eq!(source(bin_op.left.span), "x");
+                assert_eq!(source(bin_op.right.span), "(2 * x)");
+            }
+            kind => panic!("Unexpected expression {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn ambiguous_operators() {
+        let errors = [
+            ("x < y < z", "Comparisons can't be chained"),
+            ("x == y + 1 != z", "Comparisons can't be chained"),
+            (
+                "x and y or z",
+                "Logical operators can't be mixed without parentheses",
+            ),
+            (
+                "x or y < z xor z",
+                "Logical operators can't be mixed without parentheses",
+            ),
+        ];
+
+        for (expr, message) in &errors {
+            let code = format!("fn main()\n    let a = {}\nend\n", expr);
+            let mut parser = Parser::new(None, &code);
+
+            let diagnostics = parser.parse().unwrap_err();
+            assert!(
+                diagnostics
+                    .iter()
+                    .any(|diagnostic| diagnostic.message == *message
+                        && diagnostic.code.as_deref() == Some("E0005")),
+                "`{}` should error with `{}`, got {:?}",
+                expr,
+                message,
+                diagnostics
+            );
+        }
+
+        for expr in &["x and y and z", "(x and y) or z", "x < y and y < z"] {
+            let code = format!("fn main()\n    let a = {}\nend\n", expr);
+            let mut parser = Parser::new(None, &code);
+
+            assert!(parser.parse().is_ok(), "`{}` should parse", expr);
+        }
+    }
+
+    #[test]
+    fn misplaced_modifiers() {
+        for (statement, message) in &[
+            ("let a = !y", "Expressions can't start with `!`"),
+            ("let a = ?y", "Expressions can't start with `?`"),
+            ("let a = x + !y", "Expressions can't start with `!`"),
+            ("let a = * 3", "Expected an expression, found '*'"),
+            ("let a =", "Expected an expression, found 'Newline'"),
+            ("return )", "Expected an expression, found ')'"),
+            ("return 1 +", "Expected an expression, found 'Newline'"),
+        ] {
+            let code = format!("fn main()\n    {}\nend\n", statement);
+            let mut parser = Parser::new(None, &code);
+
+            let diagnostics = parser.parse().unwrap_err();
+            assert!(
+                diagnostics
+                    .iter()
+                    .any(|diagnostic| diagnostic.message == *message
+                        && diagnostic.code.as_deref() == Some("E0007")),
+                "`{}` should error with `{}`, got {:?}",
+                statement,
+                message,
+                diagnostics
+            );
+        }
+    }
 }
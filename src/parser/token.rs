@@ -46,10 +46,12 @@ pub enum TokenType {
     LeftBracket,
     #[token = "}"]
     RightBracket,
-    #[regex = "[^ \t\n\r\"\'!@#$%\\^&*()-+=,.<>/?;:\\[\\]{}\\\\|`~]+"]
+    #[regex = "[^ \t\n\r\"\'!@#$%\\^&*()\\-+=,.<>/?;:\\[\\]{}\\\\|`~]+"]
     Ident,
     #[regex = "[1234567890]+"]
     Int,
+    #[regex = "[1234567890]+\\.[1234567890]+"]
+    Float,
     #[regex = "\"[^\"]*\""]
     #[regex = "'[^']*'"]
     String,
@@ -107,6 +109,22 @@ pub enum TokenType {
     Continue,
     #[token = "break"]
     Break,
+    #[token = "type"]
+    Type,
+    #[token = "."]
+    Dot,
+    #[token = ".."]
+    DotDot,
+    #[token = "!="]
+    NotEqual,
+    #[token = "<="]
+    LessEqual,
+    #[token = ">="]
+    GreaterEqual,
+    #[token = "?"]
+    QuestionMark,
+    #[token = "!"]
+    Bang,
 }
 
 impl std::fmt::Display for TokenType {
@@ -136,6 +154,7 @@ impl std::fmt::Display for TokenType {
             Self::RightBracket => "}",
             Self::Ident => "Ident",
             Self::Int => "int",
+            Self::Float => "float",
             Self::String => "str",
             Self::Space => " ",
             Self::Comment => "Comment",
@@ -163,6 +182,14 @@ impl std::fmt::Display for TokenType {
             Self::Empty => "empty",
             Self::Then => "then",
             Self::For => "for",
+            Self::Type => "type",
+            Self::Dot => ".",
+            Self::DotDot => "..",
+            Self::NotEqual => "!=",
+            Self::LessEqual => "<=",
+            Self::GreaterEqual => ">=",
+            Self::QuestionMark => "?",
+            Self::Bang => "!",
         };
 
         write!(f, "{}", string)
@@ -0,0 +1,1421 @@
+use super::{ast::*, Parser};
+use crate::ErrorCode;
+use codespan::{FileId, Span};
+use codespan_reporting::diagnostic::{Diagnostic, Label, Severity};
+use std::collections::{HashMap, HashSet};
+use string_interner::{StringInterner, Sym};
+
+/// The most instances of generic functions that can be created, so that functions that
+/// instantiate themselves with ever larger types can't instantiate forever
+const MAX_INSTANCES: usize = 1024;
+
+/// The declared generic, argument and return types of a function
+#[derive(Debug, Clone)]
+struct Signature {
+    generics: Vec<Type>,
+    arguments: Vec<Type>,
+    returns: Type,
+}
+
+/// The declared members and methods of a type
+#[derive(Debug, Clone)]
+struct TypeSignature {
+    members: Vec<(Sym, Type)>,
+    /// The signatures of the type's methods, and whether they take `self`. `self` isn't included
+    /// in the arguments
+    methods: HashMap<Sym, (Signature, bool)>,
+}
+
+/// Checks the types of a parsed program before it's interpreted, so that mismatched types are
+/// reported as diagnostics instead of surfacing as runtime errors.
+///
+/// Variables declared without a type have their inferred type filled in where it's known
+#[derive(Debug)]
+pub struct TypeChecker<'a> {
+    interner: &'a StringInterner<Sym>,
+    file: FileId,
+    span: Span,
+    functions: HashMap<Sym, Signature>,
+    types: HashMap<Sym, TypeSignature>,
+    /// Generic functions, which are checked once for every instance of them
+    templates: HashMap<Sym, FunctionDecl>,
+    /// The generics of every instance of a generic function that's been called
+    instances: HashSet<(Sym, Vec<Type>)>,
+    /// Instances that haven't been checked yet
+    pending: Vec<(Sym, Vec<Type>)>,
+    /// Whether the module imports anything, in which case it can call functions it doesn't declare
+    imports: bool,
+    /// The types of the current function's variables
+    locals: HashMap<Sym, Type>,
+    /// The name of the function being checked
+    function: Option<Sym>,
+    /// The return type of the function being checked
+    returns: Type,
+    /// The generic parameters of the type being checked, which accept any type
+    generics: Vec<Type>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> TypeChecker<'a> {
+    /// Creates a type checker for the source file and interner of a parser
+    #[must_use]
+    pub fn new(parser: &'a Parser<'_>) -> Self {
+        Self {
+            interner: &parser.interner,
+            file: parser.files[0],
+            span: parser.codespan.source_span(parser.files[0]),
+            functions: HashMap::new(),
+            types: HashMap::new(),
+            templates: HashMap::new(),
+            instances: HashSet::new(),
+            pending: Vec::new(),
+            imports: false,
+            locals: HashMap::new(),
+            function: None,
+            returns: Type::Unit,
+            generics: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Checks every function and method of a module, returning all of the type errors found.
+    ///
+    /// Generic functions are replaced by an instance for every set of generics they're called
+    /// with, and calls of them are given the generics of the instance they call
+    pub fn check(mut self, ast: &mut Vec<Program>) -> Result<(), Vec<Diagnostic>> {
+        // Collect every signature beforehand, since functions and types can be used before
+        // they're declared
+        for node in ast.iter() {
+            match node {
+                Program::FunctionDecl(func) => {
+                    self.generics.clear();
+                    let (signature, _takes_self) = self.signature(func, None);
+                    self.functions.insert(func.name, signature);
+
+                    if !func.generics.is_empty() {
+                        self.templates.insert(func.name, func.clone());
+                    }
+                }
+                Program::TypeDecl(decl) => self.declare_type(decl),
+                Program::Import(_) => self.imports = true,
+            }
+        }
+
+        for node in ast.iter_mut() {
+            match node {
+                Program::FunctionDecl(func) if func.generics.is_empty() => {
+                    self.function(func, None, &[]);
+                }
+                Program::FunctionDecl(_) | Program::Import(_) => {}
+                Program::TypeDecl(decl) => {
+                    for method in &mut decl.methods {
+                        let mut generics = decl.generics.clone();
+                        generics.extend(method.generics.iter().cloned());
+
+                        self.function(method, Some(decl.name), &generics);
+                    }
+                }
+            }
+        }
+
+        // Checking an instance can call for more instances
+        let mut instances = Vec::new();
+        while let Some((name, generics)) = self.pending.pop() {
+            if self.instances.len() > MAX_INSTANCES {
+                let message = format!(
+                    "More than {} instances of generic functions were needed, `{}` may be \
+                     instantiating itself forever",
+                    MAX_INSTANCES,
+                    self.resolve(name),
+                );
+                self.error(ErrorCode::InstanceLimit, message);
+
+                break;
+            }
+
+            let mut instance = self.templates[&name].instantiate(&generics);
+            self.function(&mut instance, None, &[]);
+            instances.push(Program::FunctionDecl(instance));
+        }
+
+        // Only the instances of generic functions are interpreted
+        ast.retain(|node| match node {
+            Program::FunctionDecl(func) => func.generics.is_empty(),
+            Program::TypeDecl(_) | Program::Import(_) => true,
+        });
+        ast.extend(instances);
+
+        if self.diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(self.diagnostics)
+        }
+    }
+
+    fn declare_type(&mut self, decl: &TypeDecl) {
+        self.generics.clone_from(&decl.generics);
+
+        let members = decl
+            .members
+            .iter()
+            .map(|(name, ty)| (*name, self.declared(ty, Some(decl.name))))
+            .collect();
+
+        let mut methods = HashMap::with_capacity(decl.methods.len());
+        for method in &decl.methods {
+            self.generics.clone_from(&decl.generics);
+            self.generics.extend(method.generics.iter().cloned());
+
+            methods.insert(method.name, self.signature(method, Some(decl.name)));
+        }
+
+        self.types
+            .insert(decl.name, TypeSignature { members, methods });
+    }
+
+    /// The signature of a function or method, and whether it takes `self`
+    fn signature(&self, func: &FunctionDecl, owner: Option<Sym>) -> (Signature, bool) {
+        let takes_self = owner.is_some()
+            && func
+                .arguments
+                .first()
+                .map_or(false, |(name, _ty)| self.resolve(*name) == "self");
+
+        let arguments = func
+            .arguments
+            .iter()
+            .skip(takes_self as usize)
+            .map(|(_name, ty)| self.declared(ty, owner))
+            .collect();
+
+        let signature = Signature {
+            generics: func.generics.clone(),
+            arguments,
+            returns: self.declared(&func.returns, owner),
+        };
+
+        (signature, takes_self)
+    }
+
+    fn function(&mut self, func: &mut FunctionDecl, owner: Option<Sym>, generics: &[Type]) {
+        self.generics = generics.to_vec();
+
+        self.locals.clear();
+        for (name, ty) in &func.arguments {
+            let ty = self.declared(ty, owner);
+            self.locals.insert(*name, ty);
+        }
+
+        self.function = Some(func.name);
+        self.returns = self.declared(&func.returns, owner);
+        self.span = func.span;
+
+        for statement in &mut func.body {
+            self.statement(statement);
+        }
+
+        if self.returns != Type::Unit && !always_returns(&func.body) {
+            let message = format!(
+                "The function `{}` returns `{}`, but can end without returning a value",
+                self.resolve(func.name),
+                self.type_name(&self.returns),
+            );
+            self.error(ErrorCode::MissingReturn, message);
+        }
+    }
+
+    fn statement(&mut self, statement: &mut Statement) {
+        let span = statement.span;
+        self.at(span, |checker| checker.statement_kind(&mut statement.kind));
+    }
+
+    fn statement_kind(&mut self, statement: &mut StatementKind) {
+        match statement {
+            StatementKind::VarDecl(var_decl) => self.var_decl(var_decl),
+
+            StatementKind::Assign(assign) => {
+                let var = self.variable(assign.var);
+                let mut value = self.expr(&mut assign.expr);
+                if let AssignType::BinaryOp(op) = assign.ty {
+                    value = self.binary_operation((op, OperandType::Normal), &var, &value);
+                }
+
+                if !assignable(&var, &value) {
+                    let message = format!(
+                        "The variable `{}` has the type `{}`, but was assigned a value of type `{}`",
+                        self.resolve(assign.var),
+                        self.type_name(&var),
+                        self.type_name(&value),
+                    );
+                    self.error(ErrorCode::MismatchedTypes, message);
+                }
+            }
+
+            StatementKind::FieldAssign(assign) => {
+                let object = self.expr(&mut assign.object);
+                let member = self.member(&object, assign.member);
+                let value = self.expr(&mut assign.expr);
+
+                if !assignable(&member, &value) {
+                    let message = format!(
+                        "The member `{}` has the type `{}`, but was assigned a value of type `{}`",
+                        self.resolve(assign.member),
+                        self.type_name(&member),
+                        self.type_name(&value),
+                    );
+                    self.error(ErrorCode::MismatchedTypes, message);
+                }
+            }
+
+            StatementKind::Return(ret) => {
+                let returned = match &mut ret.exprs[..] {
+                    [] => Type::Unit,
+                    [expr] => self.expr(expr),
+                    exprs => Type::Tuple(exprs.iter_mut().map(|expr| self.expr(expr)).collect()),
+                };
+
+                if !assignable(&self.returns, &returned) {
+                    let message = format!(
+                        "The function `{}` returns `{}`, but a value of type `{}` was returned",
+                        self.function
+                            .map_or_else(String::new, |func| self.resolve(func)),
+                        self.type_name(&self.returns),
+                        self.type_name(&returned),
+                    );
+                    self.error(ErrorCode::MismatchedTypes, message);
+                }
+            }
+
+            StatementKind::Conditional(conditional) => {
+                for branch in &mut conditional._if {
+                    self.condition(&mut branch.condition);
+                    self.body(&mut branch.body);
+                }
+                if let Some(otherwise) = &mut conditional._else {
+                    self.body(&mut otherwise.body);
+                }
+            }
+
+            StatementKind::While(while_loop) => {
+                self.condition(&mut while_loop.condition);
+                self.body(&mut while_loop.body);
+                if let Some(then) = &mut while_loop.then {
+                    self.body(&mut then.body);
+                }
+            }
+
+            StatementKind::Loop(loop_loop) => self.body(&mut loop_loop.body),
+
+            StatementKind::For(for_loop) => {
+                let element = if let ExprKind::Range(range) = &mut for_loop.range.kind {
+                    for bound in &mut [&mut range.start, &mut range.end] {
+                        let ty = self.expr(bound);
+                        if !assignable(&Type::Int, &ty) {
+                            let message = format!(
+                                "Ranges must be made of `int`s, but a value of type `{}` was given",
+                                self.type_name(&ty),
+                            );
+                            self.error(ErrorCode::MismatchedTypes, message);
+                        }
+                    }
+
+                    Type::Int
+                } else {
+                    self.expr(&mut for_loop.range);
+                    Type::Any
+                };
+
+                self.locals.insert(for_loop.element, element);
+                self.body(&mut for_loop.body);
+                if let Some(then) = &mut for_loop.then {
+                    self.body(&mut then.body);
+                }
+            }
+
+            StatementKind::Expr(expr) => {
+                self.expr(expr);
+            }
+
+            StatementKind::Continue | StatementKind::Break | StatementKind::Empty => {}
+        }
+    }
+
+    fn body(&mut self, body: &mut [Statement]) {
+        for statement in body {
+            self.statement(statement);
+        }
+    }
+
+    fn var_decl(&mut self, var_decl: &mut VarDecl) {
+        let value = self.expr(&mut var_decl.expr);
+
+        if let [name] = var_decl.names[..] {
+            if value == Type::Unit {
+                let message = format!(
+                    "The variable `{}` can't be assigned a value of type `unit`",
+                    self.resolve(name),
+                );
+                self.error(ErrorCode::MismatchedTypes, message);
+            }
+
+            let ty = if var_decl.ty == Type::Infer {
+                // Only concrete types are filled in, unknown ones are left for the interpreter
+                if value != Type::Any {
+                    var_decl.ty = value.clone();
+                }
+
+                value
+            } else {
+                let declared = self.declared(&var_decl.ty, None);
+                if !assignable(&declared, &value) {
+                    let message = format!(
+                        "The variable `{}` has the type `{}`, but was assigned a value of type `{}`",
+                        self.resolve(name),
+                        self.type_name(&declared),
+                        self.type_name(&value),
+                    );
+                    self.error(ErrorCode::MismatchedTypes, message);
+                }
+
+                declared
+            };
+
+            self.locals.insert(name, ty);
+        } else {
+            let types = match value {
+                Type::Tuple(types) if types.len() == var_decl.names.len() => types,
+                Type::Any => vec![Type::Any; var_decl.names.len()],
+                value => {
+                    let message = format!(
+                        "{} variables were declared, but a value of type `{}` was given",
+                        var_decl.names.len(),
+                        self.type_name(&value),
+                    );
+                    self.error(ErrorCode::MismatchedTypes, message);
+
+                    vec![Type::Any; var_decl.names.len()]
+                }
+            };
+
+            for (name, ty) in var_decl.names.iter().zip(types) {
+                self.locals.insert(*name, ty);
+            }
+        }
+    }
+
+    fn condition(&mut self, condition: &mut Expr) {
+        let ty = self.expr(condition);
+
+        if !assignable(&Type::Bool, &ty) {
+            let message = format!(
+                "Conditions must be of type `bool`, but a value of type `{}` was given",
+                self.type_name(&ty),
+            );
+            self.at(condition.span, |checker| {
+                checker.error(ErrorCode::MismatchedTypes, message)
+            });
+        }
+    }
+
+    /// Infers the type of an expression, reporting any type errors within it
+    fn expr(&mut self, expr: &mut Expr) -> Type {
+        let span = expr.span;
+        self.at(span, |checker| checker.expr_kind(&mut expr.kind))
+    }
+
+    fn expr_kind(&mut self, expr: &mut ExprKind) -> Type {
+        match expr {
+            ExprKind::Literal(Literal::String(_)) => Type::String,
+            ExprKind::Literal(Literal::Integer(_)) => Type::Int,
+            ExprKind::Literal(Literal::Float(_)) => Type::Float,
+            ExprKind::Literal(Literal::Boolean(_)) => Type::Bool,
+            // `null` can be given to any nullable type
+            ExprKind::Literal(Literal::Null) => Type::Nullable(Box::new(Type::Any)),
+
+            ExprKind::Range(range) => {
+                self.expr(&mut range.start);
+                self.expr(&mut range.end);
+
+                Type::Any
+            }
+
+            ExprKind::Comparison(comparison) => {
+                let left = self.expr(&mut comparison.left);
+                let right = self.expr(&mut comparison.right);
+
+                let comparable = match comparison.comparison {
+                    Comparator::Equal | Comparator::NotEqual => {
+                        assignable(&left, &right) || assignable(&right, &left)
+                    }
+                    _ => match (&left, &right) {
+                        (Type::Any, _)
+                        | (_, Type::Any)
+                        | (Type::Int | Type::Float, Type::Int | Type::Float) => true,
+                        (left, right) => left == right && *left == Type::String,
+                    },
+                };
+
+                if !comparable {
+                    let message = format!(
+                        "Values of types `{}` and `{}` can't be compared with `{}`",
+                        self.type_name(&left),
+                        self.type_name(&right),
+                        comparator(comparison.comparison),
+                    );
+                    self.error(ErrorCode::InvalidOperands, message);
+                }
+
+                Type::Bool
+            }
+
+            ExprKind::BinaryOperation(bin_op) => {
+                let left = self.expr(&mut bin_op.left);
+                let right = self.expr(&mut bin_op.right);
+
+                self.binary_operation(bin_op.op, &left, &right)
+            }
+
+            ExprKind::UnaryOperation(unary) => {
+                let ty = self.expr(&mut unary.expr);
+
+                match (unary.op, &ty) {
+                    (_, Type::Any)
+                    | (UnaryOp::Negate, Type::Int | Type::Float)
+                    | (UnaryOp::Not, Type::Bool | Type::Int) => ty,
+                    (op, ty) => {
+                        let message = format!(
+                            "The operator `{}` can't be used on a value of type `{}`",
+                            if op == UnaryOp::Negate { "-" } else { "not" },
+                            self.type_name(ty),
+                        );
+                        self.error(ErrorCode::InvalidOperands, message)
+                    }
+                }
+            }
+
+            ExprKind::FunctionCall(func_call) => {
+                let arguments = self.arguments(&mut func_call.arguments);
+
+                // Functions that aren't declared in a module that imports others may be imported,
+                // so they're left to the interpreter
+                match self.functions.get(&func_call.name).cloned() {
+                    Some(signature) if !signature.generics.is_empty() => {
+                        self.generic_call(func_call, &signature, &arguments)
+                    }
+                    Some(_) if !func_call.generics.is_empty() => {
+                        let message = format!(
+                            "The function `{}` is not generic",
+                            self.resolve(func_call.name)
+                        );
+                        self.error(ErrorCode::InvalidGenerics, message)
+                    }
+                    Some(signature) => {
+                        self.call(func_call.name, &signature, &arguments);
+                        signature.returns
+                    }
+                    None if self.imports => Type::Any,
+                    None => {
+                        let message = format!(
+                            "The function `{}` does not exist",
+                            self.resolve(func_call.name)
+                        );
+                        self.error(ErrorCode::UndefinedName, message)
+                    }
+                }
+            }
+
+            ExprKind::MethodCall(method_call) => self.method_call(method_call),
+
+            ExprKind::StructLiteral(literal) => self.struct_literal(literal),
+
+            ExprKind::FieldAccess(access) => {
+                let object = self.expr(&mut access.object);
+                self.member(&object, access.member)
+            }
+
+            ExprKind::Ident(ident) => self.variable(*ident),
+            ExprKind::Expr(expr) => self.expr(expr),
+        }
+    }
+
+    fn arguments(&mut self, arguments: &mut [Expr]) -> Vec<Type> {
+        arguments.iter_mut().map(|arg| self.expr(arg)).collect()
+    }
+
+    /// Checks the arguments given to a function against its signature
+    fn call(&mut self, func: Sym, signature: &Signature, arguments: &[Type]) {
+        if signature.arguments.len() != arguments.len() {
+            let message = format!(
+                "The function `{}` takes {} arguments, but {} were given",
+                self.resolve(func),
+                signature.arguments.len(),
+                arguments.len(),
+            );
+            self.error(ErrorCode::ArgumentCount, message);
+
+            return;
+        }
+
+        for (index, (expected, given)) in signature.arguments.iter().zip(arguments).enumerate() {
+            if !assignable(expected, given) {
+                let message = format!(
+                    "Argument {} of `{}` has the type `{}`, but a value of type `{}` was given",
+                    index + 1,
+                    self.resolve(func),
+                    self.type_name(expected),
+                    self.type_name(given),
+                );
+                self.error(ErrorCode::MismatchedTypes, message);
+            }
+        }
+    }
+
+    /// Checks a call of a generic function, inferring the generics that weren't given from its
+    /// arguments. The call is given the generics of the instance it calls
+    fn generic_call(
+        &mut self,
+        func_call: &mut FunctionCall,
+        signature: &Signature,
+        arguments: &[Type],
+    ) -> Type {
+        let generics = if func_call.generics.is_empty() {
+            let mut inferred = vec![None; signature.generics.len()];
+            for (expected, given) in signature.arguments.iter().zip(arguments) {
+                infer(&signature.generics, &mut inferred, expected, given);
+            }
+
+            let mut generics = Vec::with_capacity(inferred.len());
+            for (generic, inferred) in signature.generics.iter().zip(inferred) {
+                if let Some(inferred) = inferred {
+                    generics.push(inferred);
+                } else {
+                    let message = format!(
+                        "The generic `{}` of `{}` can't be inferred and must be given explicitly",
+                        self.type_name(generic),
+                        self.resolve(func_call.name),
+                    );
+                    return self.error(ErrorCode::InvalidGenerics, message);
+                }
+            }
+
+            generics
+        } else if func_call.generics.len() == signature.generics.len() {
+            func_call
+                .generics
+                .iter()
+                .map(|ty| self.declared(ty, None))
+                .collect()
+        } else {
+            let message = format!(
+                "The function `{}` takes {} generics, but {} were given",
+                self.resolve(func_call.name),
+                signature.generics.len(),
+                func_call.generics.len(),
+            );
+            return self.error(ErrorCode::InvalidGenerics, message);
+        };
+
+        let instance = Signature {
+            generics: Vec::new(),
+            arguments: signature
+                .arguments
+                .iter()
+                .map(|ty| ty.substitute(&signature.generics, &generics))
+                .collect(),
+            returns: signature.returns.substitute(&signature.generics, &generics),
+        };
+        self.call(func_call.name, &instance, arguments);
+
+        if self.instances.insert((func_call.name, generics.clone())) {
+            self.pending.push((func_call.name, generics.clone()));
+        }
+        func_call.generics = generics;
+
+        instance.returns
+    }
+
+    fn method_call(&mut self, method_call: &mut MethodCall) -> Type {
+        let (ty, on_object) = match &method_call.receiver.kind {
+            // Calling a function on the type itself calls an associated function
+            ExprKind::Ident(name)
+                if !self.locals.contains_key(name) && self.types.contains_key(name) =>
+            {
+                (Type::Custom(*name), false)
+            }
+            _ => (self.expr(&mut method_call.receiver), true),
+        };
+        let arguments = self.arguments(&mut method_call.arguments);
+
+        let type_name = match ty {
+            Type::Custom(name) if self.types.contains_key(&name) => name,
+            Type::Any => return Type::Any,
+            ty => {
+                let message = format!(
+                    "Values of type `{}` have no method `{}`",
+                    self.type_name(&ty),
+                    self.resolve(method_call.name),
+                );
+                return self.error(ErrorCode::UndefinedMember, message);
+            }
+        };
+
+        let (code, message) = match self.types[&type_name]
+            .methods
+            .get(&method_call.name)
+            .cloned()
+        {
+            Some((signature, takes_self)) if takes_self == on_object => {
+                self.call(method_call.name, &signature, &arguments);
+                return signature.returns;
+            }
+            Some((_signature, true)) => (
+                ErrorCode::MethodCallStyle,
+                format!(
+                    "`{}` is a method of `{}` and must be called on an object",
+                    self.resolve(method_call.name),
+                    self.resolve(type_name),
+                ),
+            ),
+            Some((_signature, false)) => (
+                ErrorCode::MethodCallStyle,
+                format!(
+                    "`{}` is an associated function and must be called as `{}.{}`",
+                    self.resolve(method_call.name),
+                    self.resolve(type_name),
+                    self.resolve(method_call.name),
+                ),
+            ),
+            None => (
+                ErrorCode::UndefinedMember,
+                format!(
+                    "The type `{}` has no method `{}`",
+                    self.resolve(type_name),
+                    self.resolve(method_call.name),
+                ),
+            ),
+        };
+
+        self.error(code, message)
+    }
+
+    fn struct_literal(&mut self, literal: &mut StructLiteral) -> Type {
+        let members = if let Some(ty) = self.types.get(&literal.name) {
+            ty.members.clone()
+        } else {
+            let message = format!("The type `{}` does not exist", self.resolve(literal.name));
+            return self.error(ErrorCode::UndefinedName, message);
+        };
+
+        let mut given = Vec::with_capacity(literal.fields.len());
+        for (field, expr) in &mut literal.fields {
+            let value = self.expr(expr);
+
+            let (code, message) = match members.iter().find(|(member, _ty)| member == field) {
+                Some(_) if given.contains(field) => (
+                    ErrorCode::InvalidStructLiteral,
+                    format!(
+                        "The member `{}` was given more than once",
+                        self.resolve(*field)
+                    ),
+                ),
+                Some((_member, ty)) => {
+                    given.push(*field);
+                    if assignable(ty, &value) {
+                        continue;
+                    }
+
+                    (
+                        ErrorCode::MismatchedTypes,
+                        format!(
+                            "The member `{}` has the type `{}`, but was given a value of type `{}`",
+                            self.resolve(*field),
+                            self.type_name(ty),
+                            self.type_name(&value),
+                        ),
+                    )
+                }
+                None => (
+                    ErrorCode::UndefinedMember,
+                    format!(
+                        "The type `{}` has no member `{}`",
+                        self.resolve(literal.name),
+                        self.resolve(*field),
+                    ),
+                ),
+            };
+
+            self.error(code, message);
+        }
+
+        let missing = members
+            .iter()
+            .filter(|(member, _ty)| !given.contains(member))
+            .map(|(member, _ty)| format!("`{}`", self.resolve(*member)))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            let message = format!(
+                "The members {} of `{}` were not given",
+                missing.join(", "),
+                self.resolve(literal.name),
+            );
+            self.error(ErrorCode::InvalidStructLiteral, message);
+        }
+
+        Type::Custom(literal.name)
+    }
+
+    /// The type of a member of an object
+    fn member(&mut self, object: &Type, member: Sym) -> Type {
+        let members = match object {
+            Type::Custom(name) if self.types.contains_key(name) => &self.types[name].members,
+            Type::Any => return Type::Any,
+            ty => {
+                let message = format!(
+                    "Values of type `{}` have no member `{}`",
+                    self.type_name(ty),
+                    self.resolve(member),
+                );
+                return self.error(ErrorCode::UndefinedMember, message);
+            }
+        };
+
+        if let Some((_member, ty)) = members.iter().find(|(name, _ty)| *name == member) {
+            ty.clone()
+        } else {
+            let message = format!(
+                "The type `{}` has no member `{}`",
+                self.type_name(object),
+                self.resolve(member),
+            );
+            self.error(ErrorCode::UndefinedMember, message)
+        }
+    }
+
+    fn variable(&mut self, var: Sym) -> Type {
+        if let Some(ty) = self.locals.get(&var) {
+            ty.clone()
+        } else {
+            let message = format!("The variable `{}` does not exist", self.resolve(var));
+            self.error(ErrorCode::UndefinedName, message)
+        }
+    }
+
+    /// The type that a binary operation results in
+    fn binary_operation(
+        &mut self,
+        (op, ty): (BinaryOp, OperandType),
+        left: &Type,
+        right: &Type,
+    ) -> Type {
+        let result = match (op, left, right) {
+            (_, Type::Any, _) | (_, _, Type::Any) => Some(Type::Any),
+
+            (BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Mult | BinaryOp::Div, left, right) => {
+                match (left, right) {
+                    (Type::Int, Type::Int) => Some(Type::Int),
+                    // Integers are promoted to floats when they're mixed
+                    (Type::Int | Type::Float, Type::Int | Type::Float) => Some(Type::Float),
+                    (Type::String, Type::String)
+                        if op == BinaryOp::Plus && ty == OperandType::Normal =>
+                    {
+                        Some(Type::String)
+                    }
+                    _ => None,
+                }
+            }
+
+            (BinaryOp::And | BinaryOp::Or | BinaryOp::Xor, left, right) => match (left, right) {
+                (Type::Bool, Type::Bool) => Some(Type::Bool),
+                (Type::Int, Type::Int) => Some(Type::Int),
+                _ => None,
+            },
+        };
+
+        match result {
+            // Fallible operators evaluate to `null` when they fail
+            Some(Type::Any) => Type::Any,
+            Some(result) if ty == OperandType::Fallible => Type::Nullable(Box::new(result)),
+            Some(result) => result,
+            None => {
+                let message = format!(
+                    "The operator `{}{}` can't be used on values of types `{}` and `{}`",
+                    operator(op),
+                    match ty {
+                        OperandType::Normal => "",
+                        OperandType::Fallible => "?",
+                        OperandType::Panicking => "!",
+                    },
+                    self.type_name(left),
+                    self.type_name(right),
+                );
+                self.error(ErrorCode::InvalidOperands, message)
+            }
+        }
+    }
+
+    /// Resolves a declared type, replacing `Self` with its owning type and generic parameters
+    /// with `any`
+    fn declared(&self, ty: &Type, owner: Option<Sym>) -> Type {
+        match ty {
+            Type::Custom(_) if self.generics.contains(ty) => Type::Any,
+            Type::Custom(sym) if self.resolve(*sym) == "Self" => {
+                owner.map_or(Type::Any, Type::Custom)
+            }
+            Type::Tuple(types) => {
+                Type::Tuple(types.iter().map(|ty| self.declared(ty, owner)).collect())
+            }
+            Type::Nullable(ty) => Type::Nullable(Box::new(self.declared(ty, owner))),
+            ty => ty.clone(),
+        }
+    }
+
+    fn type_name(&self, ty: &Type) -> String {
+        ty.name(self.interner)
+    }
+
+    fn resolve(&self, sym: Sym) -> String {
+        self.interner
+            .resolve(sym)
+            .unwrap_or("<unknown>")
+            .to_string()
+    }
+
+    /// Points the errors found by `check` at `span`, unless they're within something more specific
+    fn at<T>(&mut self, span: Span, check: impl FnOnce(&mut Self) -> T) -> T {
+        let parent = std::mem::replace(&mut self.span, span);
+        let result = check(self);
+        self.span = parent;
+
+        result
+    }
+
+    /// Reports a type error, returning `any` so that it doesn't cascade into more errors
+    fn error(&mut self, code: ErrorCode, message: String) -> Type {
+        error!("Type error: {}", message);
+
+        self.diagnostics.push(
+            Diagnostic::new(
+                Severity::Error,
+                message,
+                Label::new(self.file, self.span, "Type error".to_string()),
+            )
+            .with_code(code.code()),
+        );
+
+        Type::Any
+    }
+}
+
+/// Whether a value of type `found` can be used where a value of type `expected` is expected
+fn assignable(expected: &Type, found: &Type) -> bool {
+    match (expected, found) {
+        (Type::Any | Type::Infer, _) | (_, Type::Any | Type::Infer) => true,
+
+        // Nullable values can hold their contained type, but can't be used as it
+        (Type::Nullable(expected), Type::Nullable(found)) => assignable(expected, found),
+        (Type::Nullable(expected), found) => assignable(expected, found),
+
+        (Type::Tuple(expected), Type::Tuple(found)) => {
+            expected.len() == found.len()
+                && expected
+                    .iter()
+                    .zip(found)
+                    .all(|(expected, found)| assignable(expected, found))
+        }
+
+        (expected, found) => expected == found,
+    }
+}
+
+/// Infers the generics used by a given type from the type that was expected, leaving the generics
+/// that were already inferred alone
+fn infer(generics: &[Type], inferred: &mut [Option<Type>], expected: &Type, given: &Type) {
+    if let Some(index) = generics.iter().position(|generic| generic == expected) {
+        // Values of unknown types don't say anything about the generic
+        if inferred[index].is_none() && !unknown(given) {
+            inferred[index] = Some(given.clone());
+        }
+
+        return;
+    }
+
+    match (expected, given) {
+        (Type::Nullable(expected), Type::Nullable(given)) => {
+            infer(generics, inferred, expected, given);
+        }
+        (Type::Nullable(expected), given) => infer(generics, inferred, expected, given),
+        (Type::Tuple(expected), Type::Tuple(given)) => {
+            for (expected, given) in expected.iter().zip(given) {
+                infer(generics, inferred, expected, given);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn unknown(ty: &Type) -> bool {
+    match ty {
+        Type::Any | Type::Infer => true,
+        Type::Nullable(ty) => unknown(ty),
+        Type::Tuple(types) => types.iter().any(unknown),
+        _ => false,
+    }
+}
+
+/// Whether every path through a body ends in a `return`
+fn always_returns(body: &[Statement]) -> bool {
+    body.iter().any(|statement| match &statement.kind {
+        StatementKind::Return(_) => true,
+        StatementKind::Conditional(conditional) => {
+            conditional._else.as_ref().map_or(false, |otherwise| {
+                always_returns(&otherwise.body)
+                    && conditional
+                        ._if
+                        .iter()
+                        .all(|branch| always_returns(&branch.body))
+            })
+        }
+        // A loop can only be left by breaking out of it or returning
+        StatementKind::Loop(loop_loop) => !breaks(&loop_loop.body),
+        _ => false,
+    })
+}
+
+/// Whether a body breaks out of the loop it's in
+fn breaks(body: &[Statement]) -> bool {
+    body.iter().any(|statement| match &statement.kind {
+        StatementKind::Break => true,
+        StatementKind::Conditional(conditional) => {
+            conditional._if.iter().any(|branch| breaks(&branch.body))
+                || conditional
+                    ._else
+                    .as_ref()
+                    .map_or(false, |otherwise| breaks(&otherwise.body))
+        }
+        // Breaks within nested loops leave those loops instead
+        _ => false,
+    })
+}
+
+fn operator(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Plus => "+",
+        BinaryOp::Minus => "-",
+        BinaryOp::Mult => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Xor => "xor",
+        BinaryOp::Or => "or",
+        BinaryOp::And => "and",
+    }
+}
+
+fn comparator(comparison: Comparator) -> &'static str {
+    match comparison {
+        Comparator::Equal => "==",
+        Comparator::NotEqual => "!=",
+        Comparator::LessEqual => "<=",
+        Comparator::GreaterEqual => ">=",
+        Comparator::Less => "<",
+        Comparator::Greater => ">",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TYPES: &str = "
+type Point
+    x: int
+    y: nullable<int>
+
+    fn new(x: int) -> Self
+        return Point { x: x, y: null }
+    end
+
+    fn sum(self, other: Point) -> int
+        return self.x
+    end
+end
+
+fn pick(flag: bool, x: int, y: int) -> int
+    if flag
+        return x
+    end
+    return y
+end
+
+fn nothing()
+    empty
+end
+
+fn identity<T>(value: T) -> T
+    return value
+end
+
+fn describe<T>(value: T) -> str
+    return value
+end
+";
+
+    fn check(source: &str) -> std::result::Result<Vec<Program>, Vec<String>> {
+        let mut parser = Parser::new(None, source);
+        let mut ast = parser.parse().unwrap().0;
+
+        TypeChecker::new(&parser)
+            .check(&mut ast)
+            .map(|()| ast)
+            .map_err(|diagnostics| {
+                diagnostics
+                    .into_iter()
+                    .map(|diagnostic| diagnostic.message)
+                    .collect()
+            })
+    }
+
+    #[test]
+    fn well_typed() {
+        let code = format!(
+            "{}
+fn main()
+    let point = Point.new(1)
+    let maybe: nullable<int> = 5
+    maybe = null
+    point.y = maybe
+    let sum = point.sum(Point.new(2))
+    let picked = pick(true, sum, point.x)
+    let halved: nullable<int> = sum /? 2
+    let doubled: int = sum *! 2
+    nothing()
+end
+",
+            TYPES
+        );
+
+        let mut parser = Parser::new(None, &code);
+        let mut ast = parser.parse().unwrap().0;
+        TypeChecker::new(&parser).check(&mut ast).unwrap();
+
+        // Variables without a type have their inferred type filled in
+        let main = ast
+            .iter()
+            .find_map(|node| match node {
+                Program::FunctionDecl(func) if Some(func.name) == parser.interner.get("main") => {
+                    Some(func)
+                }
+                _ => None,
+            })
+            .unwrap();
+        let types = main
+            .body
+            .iter()
+            .filter_map(|statement| match &statement.kind {
+                StatementKind::VarDecl(var_decl) => Some(var_decl.ty.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert!(matches!(types[0], Type::Custom(_)));
+        assert_eq!(
+            types[1..],
+            [
+                Type::Nullable(Box::new(Type::Int)),
+                Type::Int,
+                Type::Int,
+                Type::Nullable(Box::new(Type::Int)),
+                Type::Int,
+            ]
+        );
+    }
+
+    #[test]
+    fn generic_instances() {
+        let code = format!(
+            "{}
+fn first(value, other) -> any
+    return value
+end
+
+fn main()
+    let x = identity(1)
+    let y = identity(\"text\")
+    let z = identity<int>(2)
+    let picked = first(true, 1)
+end
+",
+            TYPES
+        );
+
+        let mut parser = Parser::new(None, &code);
+        let mut ast = parser.parse().unwrap().0;
+        TypeChecker::new(&parser).check(&mut ast).unwrap();
+
+        // Generic functions are replaced by one instance for every set of generics they're called
+        // with, and the calls are given the generics of their instance
+        let mut instances = ast
+            .iter()
+            .filter_map(|node| match node {
+                Program::FunctionDecl(func) if !func.generics.is_empty() => Some((
+                    parser.interner.resolve(func.name).unwrap(),
+                    func.generics.clone(),
+                    func.arguments
+                        .iter()
+                        .map(|(_name, ty)| ty.clone())
+                        .collect(),
+                    func.returns.clone(),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<(_, _, Vec<_>, _)>>();
+        instances.sort_by_key(|(name, generics, _, _)| (*name, format!("{:?}", generics)));
+
+        assert_eq!(
+            instances,
+            vec![
+                (
+                    "first",
+                    vec![Type::Bool, Type::Int],
+                    vec![Type::Bool, Type::Int],
+                    Type::Any
+                ),
+                ("identity", vec![Type::Int], vec![Type::Int], Type::Int),
+                (
+                    "identity",
+                    vec![Type::String],
+                    vec![Type::String],
+                    Type::String
+                ),
+            ]
+        );
+
+        let main = ast
+            .iter()
+            .find_map(|node| match node {
+                Program::FunctionDecl(func) if Some(func.name) == parser.interner.get("main") => {
+                    Some(func)
+                }
+                _ => None,
+            })
+            .unwrap();
+        let calls = main
+            .body
+            .iter()
+            .filter_map(|statement| match &statement.kind {
+                StatementKind::VarDecl(VarDecl {
+                    expr:
+                        Expr {
+                            kind: ExprKind::FunctionCall(func_call),
+                            ..
+                        },
+                    ty,
+                    ..
+                }) => Some((func_call.generics.clone(), ty.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            calls,
+            vec![
+                (vec![Type::Int], Type::Int),
+                (vec![Type::String], Type::String),
+                (vec![Type::Int], Type::Int),
+                (vec![Type::Bool, Type::Int], Type::Infer),
+            ]
+        );
+    }
+
+    #[test]
+    fn type_errors() {
+        let errors = [
+            (
+                "let x: int = \"a\"",
+                "The variable `x` has the type `int`, but was assigned a value of type `str`",
+            ),
+            (
+                "let x: int = null",
+                "The variable `x` has the type `int`, but was assigned a value of type `nullable<any>`",
+            ),
+            (
+                "let maybe: nullable<int> = 1\n    pick(true, maybe, 2)",
+                "Argument 2 of `pick` has the type `int`, but a value of type `nullable<int>` was given",
+            ),
+            (
+                "pick(true, 1)",
+                "The function `pick` takes 3 arguments, but 2 were given",
+            ),
+            (
+                "let x = nothing()",
+                "The variable `x` can't be assigned a value of type `unit`",
+            ),
+            (
+                "if 1\n        empty\n    end",
+                "Conditions must be of type `bool`, but a value of type `int` was given",
+            ),
+            ("return 1", "The function `main` returns `unit`, but a value of type `int` was returned"),
+            ("let x = y", "The variable `y` does not exist"),
+            ("missing(1)", "The function `missing` does not exist"),
+            (
+                "let point = Point.new(1)\n    point.x = \"a\"",
+                "The member `x` has the type `int`, but was assigned a value of type `str`",
+            ),
+            (
+                "let point = Point.new(1)\n    point.sum(1)",
+                "Argument 1 of `sum` has the type `Point`, but a value of type `int` was given",
+            ),
+            (
+                "let point = Point { x: 1 }",
+                "The members `y` of `Point` were not given",
+            ),
+            (
+                "let point = Point.new(1)\n    let x: int = point.y",
+                "The variable `x` has the type `int`, but was assigned a value of type `nullable<int>`",
+            ),
+            ("let x = Point.sum(1)", "`sum` is a method of `Point` and must be called on an object"),
+            ("let x = 1\n    x.y = 2", "Values of type `int` have no member `y`"),
+            (
+                "let x = identity<int, str>(1)",
+                "The function `identity` takes 1 generics, but 2 were given",
+            ),
+            ("pick<int>(true, 1, 2)", "The function `pick` is not generic"),
+            (
+                "let x: int = identity<nullable<int>>(null)",
+                "The variable `x` has the type `int`, but was assigned a value of type `nullable<int>`",
+            ),
+            (
+                "let x = identity(null)",
+                "The generic `T` of `identity` can't be inferred and must be given explicitly",
+            ),
+            (
+                "describe(\"text\")\n    describe(1)",
+                "The function `describe` returns `str`, but a value of type `int` was returned",
+            ),
+            (
+                "let x: int = 10 /? 2",
+                "The variable `x` has the type `int`, but was assigned a value of type `nullable<int>`",
+            ),
+            (
+                "let x = \"a\" +! \"b\"",
+                "The operator `+!` can't be used on values of types `str` and `str`",
+            ),
+        ];
+
+        for (body, expected) in &errors {
+            let code = format!("{}\nfn main()\n    {}\nend\n", TYPES, body);
+            assert_eq!(
+                check(&code).unwrap_err(),
+                vec![expected.to_string()],
+                "{}",
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn error_spans() {
+        let errors = [
+            ("let x: int = \"a\"", "let x: int = \"a\""),
+            ("let x = 1 + y", "y"),
+            ("let x = 1 +! (2 * \"a\")", "2 * \"a\""),
+            ("if 1\n        empty\n    end", "1"),
+        ];
+
+        for (body, expected) in &errors {
+            let code = format!("{}\nfn main()\n    {}\nend\n", TYPES, body);
+            let mut parser = Parser::new(None, &code);
+            let mut ast = parser.parse().unwrap().0;
+
+            let diagnostics = TypeChecker::new(&parser).check(&mut ast).unwrap_err();
+            let span = diagnostics[0].primary_label.span;
+            assert_eq!(
+                parser.codespan.source_slice(parser.files[0], span).unwrap(),
+                *expected,
+                "{}",
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn error_codes() {
+        let errors = [
+            ("let x: int = \"a\"", ErrorCode::MismatchedTypes),
+            ("let x = \"a\" +! \"b\"", ErrorCode::InvalidOperands),
+            ("pick(true, 1)", ErrorCode::ArgumentCount),
+            ("let x = y", ErrorCode::UndefinedName),
+            ("missing(1)", ErrorCode::UndefinedName),
+            ("let x = Point.sum(1)", ErrorCode::MethodCallStyle),
+            (
+                "let point = Point { x: 1 }",
+                ErrorCode::InvalidStructLiteral,
+            ),
+        ];
+
+        for (body, expected) in &errors {
+            let code = format!("{}\nfn main()\n    {}\nend\n", TYPES, body);
+            let mut parser = Parser::new(None, &code);
+            let mut ast = parser.parse().unwrap().0;
+
+            let diagnostics = TypeChecker::new(&parser).check(&mut ast).unwrap_err();
+            assert_eq!(
+                diagnostics[0].code.as_deref(),
+                Some(expected.code()),
+                "{}",
+                body
+            );
+        }
+    }
+    #[test]
+    fn missing_returns() {
+        let returning = [
+            "return 1",
+            "if true\n        return 1\n    else\n        return 2\n    end",
+            "while true\n        empty\n    end\n    return 1",
+            "loop\n        if true\n            return 1\n        end\n    end",
+        ];
+        for body in &returning {
+            let code = format!(
+                "fn main()\n    empty\nend\n\nfn value() -> int\n    {}\nend\n",
+                body
+            );
+            assert!(check(&code).is_ok(), "{}", body);
+        }
+
+        let missing = [
+            "empty",
+            "if true\n        return 1\n    end",
+            "if true\n        return 1\n    else\n        empty\n    end",
+            "while true\n        return 1\n    end",
+            "loop\n        if true\n            break\n        end\n        return 1\n    end",
+        ];
+        for body in &missing {
+            let code = format!(
+                "fn main()\n    empty\nend\n\nfn value() -> int\n    {}\nend\n",
+                body
+            );
+            let mut parser = Parser::new(None, &code);
+            let mut ast = parser.parse().unwrap().0;
+
+            let diagnostics = TypeChecker::new(&parser).check(&mut ast).unwrap_err();
+            assert_eq!(
+                diagnostics[0].message,
+                "The function `value` returns `int`, but can end without returning a value",
+                "{}",
+                body
+            );
+            assert_eq!(
+                diagnostics[0].code.as_deref(),
+                Some(ErrorCode::MissingReturn.code()),
+                "{}",
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn imported_functions() {
+        // Modules that import others can call functions they don't declare
+        let code = "import \"other\"\n\nfn main()\n    missing(1)\nend\n";
+        assert!(check(code).is_ok());
+    }
+}
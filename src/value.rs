@@ -1,6 +1,12 @@
 use super::{AllocId, Collectable, Gc, Heap, Result, RuntimeError, RuntimeErrorTy};
 use num_bigint::{BigInt, BigUint};
-use std::fmt;
+use num_traits::{ToPrimitive, Zero};
+use std::{
+    cmp::Ordering,
+    collections::VecDeque,
+    convert::{TryFrom, TryInto},
+    fmt,
+};
 
 // TODO: Test all implemented operations
 
@@ -35,6 +41,8 @@ pub enum RuntimeValue {
     Bool(bool),
     // Pointer
     Pointer(AllocId),
+    // Objects, holding the values of their members
+    GcObject(Heap<Vec<RuntimeValue>>),
     // Vec
     // GcVec(GcVec<RuntimeValue>),
     // Null
@@ -65,6 +73,7 @@ impl RuntimeValue {
             Self::GcString(_) | Self::Str(_) => "str",
             Self::GcInt(_) => "bigint",
             Self::GcUint(_) => "biguint",
+            Self::GcObject(_) => "object",
             Self::Null => "null",
             Self::None => "NoneType",
         }
@@ -72,27 +81,50 @@ impl RuntimeValue {
 
     // TODO: Add similar-type eq
     pub fn is_equal(self, other: Self, gc: &Gc) -> Result<bool> {
+        if let Some((left, right)) = self.promote_floats(&other) {
+            return left.is_equal(right, gc);
+        }
+        if let Some((left, right)) = self.promote_ints(&other) {
+            return left.is_equal(right, gc);
+        }
+        if let Some((left, right)) = self.big_ints(&other, gc)? {
+            return Ok(left == right);
+        }
+        if let Some((left, right)) = self.big_uints(&other, gc)? {
+            return Ok(left == right);
+        }
+
         Ok(match (self, other) {
             (Self::Byte(left), Self::Byte(right)) => left == right,
             (Self::U16(left), Self::U16(right)) => left == right,
             (Self::U32(left), Self::U32(right)) => left == right,
             (Self::U64(left), Self::U64(right)) => left == right,
             (Self::U128(left), Self::U128(right)) => left == right,
-            (Self::GcUint(left), Self::GcUint(right)) => left.fetch(gc)? == right.fetch(gc)?,
 
             (Self::IByte(left), Self::IByte(right)) => left == right,
             (Self::I16(left), Self::I16(right)) => left == right,
             (Self::I32(left), Self::I32(right)) => left == right,
             (Self::I64(left), Self::I64(right)) => left == right,
             (Self::I128(left), Self::I128(right)) => left == right,
-            (Self::GcInt(left), Self::GcInt(right)) => left.fetch(gc)? == right.fetch(gc)?,
 
-            (Self::F32(_left), Self::F32(_right)) => unimplemented!("No idea how floats work"),
-            (Self::F64(_left), Self::F64(_right)) => unimplemented!("No idea how floats work"),
+            #[allow(clippy::float_cmp)]
+            (Self::F32(left), Self::F32(right)) => left == right,
+            #[allow(clippy::float_cmp)]
+            (Self::F64(left), Self::F64(right)) => left == right,
+
+            (Self::Char(left), Self::Char(right)) => left == right,
+            (
+                left @ (Self::Str(_) | Self::GcString(_)),
+                right @ (Self::Str(_) | Self::GcString(_)),
+            ) => left.to_string(gc)? == right.to_string(gc)?,
 
             (Self::Pointer(left), Self::Pointer(right)) => left == right,
+            // Objects are only equal to themselves
+            (Self::GcObject(left), Self::GcObject(right)) => left.id() == right.id(),
 
             (Self::Bool(left), Self::Bool(right)) => left == right,
+            // Lets the results of fallible operators be compared with `null`
+            (Self::Null, Self::Null) => true,
 
             (left, right) if left == Self::None || right == Self::None => {
                 return Err(RuntimeError {
@@ -102,12 +134,75 @@ impl RuntimeValue {
                         left.name(),
                         right.name()
                     ),
+                    backtrace: None,
                 });
             }
             (_, _) => false,
         })
     }
 
+    /// Orders two values of the same type, returning `None` if they have no ordering, like a `NaN` float
+    pub fn compare(self, other: Self, gc: &Gc) -> Result<Option<Ordering>> {
+        if let Some((left, right)) = self.promote_floats(&other) {
+            return left.compare(right, gc);
+        }
+        if let Some((left, right)) = self.promote_ints(&other) {
+            return left.compare(right, gc);
+        }
+        if let Some((left, right)) = self.big_ints(&other, gc)? {
+            return Ok(left.partial_cmp(&right));
+        }
+        if let Some((left, right)) = self.big_uints(&other, gc)? {
+            return Ok(left.partial_cmp(&right));
+        }
+
+        Ok(match (self, other) {
+            (Self::Byte(left), Self::Byte(right)) => left.partial_cmp(&right),
+            (Self::U16(left), Self::U16(right)) => left.partial_cmp(&right),
+            (Self::U32(left), Self::U32(right)) => left.partial_cmp(&right),
+            (Self::U64(left), Self::U64(right)) => left.partial_cmp(&right),
+            (Self::U128(left), Self::U128(right)) => left.partial_cmp(&right),
+
+            (Self::IByte(left), Self::IByte(right)) => left.partial_cmp(&right),
+            (Self::I16(left), Self::I16(right)) => left.partial_cmp(&right),
+            (Self::I32(left), Self::I32(right)) => left.partial_cmp(&right),
+            (Self::I64(left), Self::I64(right)) => left.partial_cmp(&right),
+            (Self::I128(left), Self::I128(right)) => left.partial_cmp(&right),
+
+            (Self::F32(left), Self::F32(right)) => left.partial_cmp(&right),
+            (Self::F64(left), Self::F64(right)) => left.partial_cmp(&right),
+
+            (Self::Char(left), Self::Char(right)) => left.partial_cmp(&right),
+            (
+                left @ (Self::Str(_) | Self::GcString(_)),
+                right @ (Self::Str(_) | Self::GcString(_)),
+            ) => left.to_string(gc)?.partial_cmp(&right.to_string(gc)?),
+
+            (left, right) if left == Self::None || right == Self::None => {
+                return Err(RuntimeError {
+                    ty: RuntimeErrorTy::NullVar,
+                    message: format!(
+                        "Values of types '{}' and '{}' cannot be compared",
+                        left.name(),
+                        right.name()
+                    ),
+                    backtrace: None,
+                });
+            }
+            (left, right) => {
+                return Err(RuntimeError {
+                    ty: RuntimeErrorTy::IncompatibleTypes,
+                    message: format!(
+                        "Values of types '{}' and '{}' cannot be compared",
+                        left.name(),
+                        right.name()
+                    ),
+                    backtrace: None,
+                });
+            }
+        })
+    }
+
     pub fn to_string(&self, gc: &Gc) -> Result<String> {
         Ok(match self {
             Self::Byte(int) => int.to_string(),
@@ -129,6 +224,15 @@ impl RuntimeValue {
             Self::Str(string) => (*string).to_string(),
             Self::GcInt(int) => int.fetch(gc)?.to_string(),
             Self::GcUint(int) => int.fetch(gc)?.to_string(),
+            Self::GcObject(object) => {
+                let members = object
+                    .fetch(gc)?
+                    .iter()
+                    .map(|member| member.to_string(gc))
+                    .collect::<Result<Vec<_>>>()?;
+
+                format!("{{ {} }}", members.join(", "))
+            }
             Self::Null => "null".to_string(),
             Self::None => "NoneType".to_string(),
         })
@@ -139,6 +243,7 @@ impl RuntimeValue {
             Self::GcInt(int) => int.drop(gc)?,
             Self::GcUint(uint) => uint.drop(gc)?,
             Self::GcString(string) => string.drop(gc)?,
+            Self::GcObject(object) => object.drop(gc)?,
             _ => {}
         }
 
@@ -147,20 +252,277 @@ impl RuntimeValue {
         Ok(())
     }
 
-    pub fn from_bytes(
-        _bytes: &[u8],
-        _strings: &mut std::collections::VecDeque<String>,
-    ) -> Result<Self> {
-        unimplemented!()
+    /// Gets the id of the heap object that the value refers to, if it refers to one
+    #[must_use]
+    pub fn heap_id(&self) -> Option<AllocId> {
+        match self {
+            Self::GcInt(int) => Some(int.id()),
+            Self::GcUint(uint) => Some(uint.id()),
+            Self::GcString(string) => Some(string.id()),
+            Self::GcObject(object) => Some(object.id()),
+            Self::Pointer(id) => Some(*id),
+            _ => None,
+        }
     }
 
+    /// Gets the size of the heap object that the value refers to, if it knows it
     #[must_use]
-    #[allow(clippy::unused_self)]
-    pub fn as_bytes(&self) -> (Vec<u8>, Option<String>) {
-        unimplemented!()
+    pub fn heap_size(&self) -> Option<usize> {
+        match self {
+            Self::GcInt(int) => Some(int.size()),
+            Self::GcUint(uint) => Some(uint.size()),
+            Self::GcString(string) => Some(string.size()),
+            Self::GcObject(object) => Some(object.size()),
+            _ => None,
+        }
+    }
+
+    /// Decodes a value from the bytecode values section, strings are taken from the front of the
+    /// decoded strings section
+    pub fn from_bytes(bytes: &[u8], strings: &mut VecDeque<String>) -> Result<Self> {
+        if bytes == [0x11] {
+            let string = strings.pop_front().ok_or_else(|| RuntimeError {
+                ty: RuntimeErrorTy::MissingString,
+                message: "Not enough strings were encoded".to_string(),
+                backtrace: None,
+            })?;
+
+            return Ok(Self::Str(Box::leak(string.into_boxed_str())));
+        }
+
+        Self::try_from(bytes)
+    }
+
+    /// Encodes the value for the bytecode values section, prefixed by the length of its encoding.
+    /// Strings are stored in the strings section, so their contents are returned separately
+    pub fn as_bytes(&self) -> Result<(Vec<u8>, Option<String>)> {
+        let (value, string): (Vec<u8>, _) = match self {
+            Self::Str(string) => (vec![0x11], Some((*string).to_string())),
+            Self::GcUint(_) | Self::GcInt(_) | Self::GcString(_) | Self::GcObject(_) => {
+                return Err(RuntimeError {
+                    ty: RuntimeErrorTy::BytecodeError,
+                    message: format!(
+                        "Values of type '{}' live on the heap and cannot be encoded",
+                        self.name()
+                    ),
+                    backtrace: None,
+                });
+            }
+            value => (value.clone().into(), None),
+        };
+
+        let mut bytes = Vec::with_capacity(std::mem::size_of::<u32>() + value.len());
+        bytes.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&value);
+
+        Ok((bytes, string))
+    }
+
+    /// Converts a fixed-width number into an `f64`
+    fn as_f64(&self) -> Option<f64> {
+        Some(match *self {
+            Self::Byte(int) => int as f64,
+            Self::U16(int) => int as f64,
+            Self::U32(int) => int as f64,
+            Self::U64(int) => int as f64,
+            Self::U128(int) => int as f64,
+            Self::IByte(int) => int as f64,
+            Self::I16(int) => int as f64,
+            Self::I32(int) => int as f64,
+            Self::I64(int) => int as f64,
+            Self::I128(int) => int as f64,
+            Self::F32(float) => float as f64,
+            Self::F64(float) => float,
+
+            _ => return None,
+        })
+    }
+
+    /// Converts a mixed pair of numbers where at least one is a float into the same float type,
+    /// returning `None` if they don't need promoting or can't be promoted
+    ///
+    /// An `F64` on either side promotes both to `F64`, otherwise an `F32` promotes both to `F32`
+    fn promote_floats(&self, other: &Self) -> Option<(Self, Self)> {
+        match (self, other) {
+            (Self::F32(_), Self::F32(_)) | (Self::F64(_), Self::F64(_)) => None,
+            (Self::F64(_), _) | (_, Self::F64(_)) => {
+                Some((Self::F64(self.as_f64()?), Self::F64(other.as_f64()?)))
+            }
+            (Self::F32(_), _) | (_, Self::F32(_)) => Some((
+                Self::F32(self.as_f64()? as f32),
+                Self::F32(other.as_f64()? as f32),
+            )),
+
+            _ => None,
+        }
+    }
+
+    /// The width of a fixed-width integer, from 0 for bytes up to 4 for 128 bit integers
+    fn int_width(&self) -> Option<u8> {
+        Some(match self {
+            Self::Byte(_) | Self::IByte(_) => 0,
+            Self::U16(_) | Self::I16(_) => 1,
+            Self::U32(_) | Self::I32(_) => 2,
+            Self::U64(_) | Self::I64(_) => 3,
+            Self::U128(_) | Self::I128(_) => 4,
+
+            _ => return None,
+        })
+    }
+
+    fn as_i128(&self) -> Option<i128> {
+        Some(match *self {
+            Self::IByte(int) => int as i128,
+            Self::I16(int) => int as i128,
+            Self::I32(int) => int as i128,
+            Self::I64(int) => int as i128,
+            Self::I128(int) => int,
+
+            _ => return None,
+        })
+    }
+
+    fn as_u128(&self) -> Option<u128> {
+        Some(match *self {
+            Self::Byte(int) => int as u128,
+            Self::U16(int) => int as u128,
+            Self::U32(int) => int as u128,
+            Self::U64(int) => int as u128,
+            Self::U128(int) => int,
+
+            _ => return None,
+        })
+    }
+
+    /// Converts a pair of fixed-width integers of the same signedness but different widths into the
+    /// wider of the two, returning `None` if they don't need promoting or can't be promoted
+    fn promote_ints(&self, other: &Self) -> Option<(Self, Self)> {
+        let (left_width, right_width) = (self.int_width()?, other.int_width()?);
+        if left_width == right_width {
+            return None;
+        }
+        let width = left_width.max(right_width);
+
+        if let (Some(left), Some(right)) = (self.as_i128(), other.as_i128()) {
+            let signed = |int: i128| match width {
+                0 => Self::IByte(int as i8),
+                1 => Self::I16(int as i16),
+                2 => Self::I32(int as i32),
+                3 => Self::I64(int as i64),
+                _ => Self::I128(int),
+            };
+
+            Some((signed(left), signed(right)))
+        } else if let (Some(left), Some(right)) = (self.as_u128(), other.as_u128()) {
+            let unsigned = |int: u128| match width {
+                0 => Self::Byte(int as u8),
+                1 => Self::U16(int as u16),
+                2 => Self::U32(int as u32),
+                3 => Self::U64(int as u64),
+                _ => Self::U128(int),
+            };
+
+            Some((unsigned(left), unsigned(right)))
+        } else {
+            None
+        }
+    }
+
+    /// Converts a pair of signed integers into `BigInt`s if either of them already is one
+    fn big_ints(&self, other: &Self, gc: &Gc) -> Result<Option<(BigInt, BigInt)>> {
+        let as_big = |int: &Self| -> Result<Option<BigInt>> {
+            Ok(match int {
+                Self::GcInt(int) => Some(int.fetch(gc)?),
+                int => int.as_i128().map(BigInt::from),
+            })
+        };
+
+        if let (Self::GcInt(_), _) | (_, Self::GcInt(_)) = (self, other) {
+            if let (Some(left), Some(right)) = (as_big(self)?, as_big(other)?) {
+                return Ok(Some((left, right)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Converts a pair of unsigned integers into `BigUint`s if either of them already is one
+    fn big_uints(&self, other: &Self, gc: &Gc) -> Result<Option<(BigUint, BigUint)>> {
+        let as_big = |int: &Self| -> Result<Option<BigUint>> {
+            Ok(match int {
+                Self::GcUint(int) => Some(int.fetch(gc)?),
+                int => int.as_u128().map(BigUint::from),
+            })
+        };
+
+        if let (Self::GcUint(_), _) | (_, Self::GcUint(_)) = (self, other) {
+            if let (Some(left), Some(right)) = (as_big(self)?, as_big(other)?) {
+                return Ok(Some((left, right)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Stores a `BigInt`, demoting it to the narrowest of `I32`, `I64` and `I128` that it fits in
+    pub fn from_big_int(int: BigInt, gc: &mut Gc) -> Result<Self> {
+        Ok(if let Some(int) = int.to_i32() {
+            Self::I32(int)
+        } else if let Some(int) = int.to_i64() {
+            Self::I64(int)
+        } else if let Some(int) = int.to_i128() {
+            Self::I128(int)
+        } else {
+            Self::GcInt(int.alloc(gc)?)
+        })
+    }
+
+    /// Stores a `BigUint`, demoting it to the narrowest of `U32`, `U64` and `U128` that it fits in
+    pub fn from_big_uint(int: BigUint, gc: &mut Gc) -> Result<Self> {
+        Ok(if let Some(int) = int.to_u32() {
+            Self::U32(int)
+        } else if let Some(int) = int.to_u64() {
+            Self::U64(int)
+        } else if let Some(int) = int.to_u128() {
+            Self::U128(int)
+        } else {
+            Self::GcUint(int.alloc(gc)?)
+        })
+    }
+
+    /// Whether a fixed-width number is zero, big integers never are since they only hold values
+    /// that don't fit into 128 bits
+    fn is_zero(&self) -> bool {
+        self.as_f64() == Some(0.0)
+    }
+
+    /// Divides two numbers, dividing an integer by zero evaluates to zero. Floats follow IEEE 754 and
+    /// become infinite or NaN instead
+    pub fn div_or_zero(self, other: Self, gc: &mut Gc) -> Result<Self> {
+        let integers =
+            self.int_width().is_some() || matches!(self, Self::GcInt(_) | Self::GcUint(_));
+        if integers && other.int_width().is_some() && other.is_zero() {
+            // Multiplying by the zero gives a zero of the type that the division would have had
+            return self.mult_upflowing(other, gc);
+        }
+
+        self.div_upflowing(other, gc)
     }
 
     pub fn add_upflowing(self, other: Self, gc: &mut Gc) -> Result<Self> {
+        if let Some((left, right)) = self.promote_floats(&other) {
+            return left.add_upflowing(right, gc);
+        }
+        if let Some((left, right)) = self.promote_ints(&other) {
+            return left.add_upflowing(right, gc);
+        }
+        if let Some((left, right)) = self.big_ints(&other, gc)? {
+            return Self::from_big_int(left + right, gc);
+        }
+        if let Some((left, right)) = self.big_uints(&other, gc)? {
+            return Self::from_big_uint(left + right, gc);
+        }
+
         Ok(match (&self, &other) {
             (Self::Byte(left), Self::Byte(right)) => {
                 if let Some(result) = left.checked_add(*right) {
@@ -194,10 +556,9 @@ impl RuntimeValue {
                 if let Some(result) = left.checked_add(*right) {
                     Self::U128(result)
                 } else {
-                    todo!()
+                    Self::from_big_uint(BigUint::from(*left) + BigUint::from(*right), gc)?
                 }
             }
-            (Self::GcUint(_left), Self::GcUint(_right)) => todo!(),
 
             (Self::IByte(left), Self::IByte(right)) => {
                 if let Some(result) = left.checked_add(*right) {
@@ -231,13 +592,12 @@ impl RuntimeValue {
                 if let Some(result) = left.checked_add(*right) {
                     Self::I128(result)
                 } else {
-                    todo!()
+                    Self::from_big_int(BigInt::from(*left) + BigInt::from(*right), gc)?
                 }
             }
-            (Self::GcInt(_left), Self::GcInt(_right)) => todo!(),
 
-            (Self::F32(_left), Self::F32(_right)) => unimplemented!("No idea how floats work"),
-            (Self::F64(_left), Self::F64(_right)) => unimplemented!("No idea how floats work"),
+            (Self::F32(left), Self::F32(right)) => Self::F32(left + right),
+            (Self::F64(left), Self::F64(right)) => Self::F64(left + right),
 
             (Self::Str(left), Self::Str(right)) => {
                 let unallocated = left.to_string() + right;
@@ -271,6 +631,7 @@ impl RuntimeValue {
                         left.name(),
                         right.name()
                     ),
+                    backtrace: None,
                 });
             }
             (left, right) => {
@@ -286,43 +647,89 @@ impl RuntimeValue {
                         left.name(),
                         right.name()
                     ),
+                    backtrace: None,
                 });
             }
         })
     }
 
-    pub fn bit_not(self, _gc: &mut Gc) -> Result<Self> {
+    pub fn bit_not(self, gc: &mut Gc) -> Result<Self> {
         Ok(match self {
             Self::Byte(int) => Self::Byte(!int),
             Self::U16(int) => Self::U16(!int),
             Self::U32(int) => Self::U32(!int),
             Self::U64(int) => Self::U64(!int),
             Self::U128(int) => Self::U128(!int),
-            // Self::GcUint(int) => Self::GcUint(int.bit_not(gc)?),
             Self::IByte(int) => Self::IByte(!int),
             Self::I16(int) => Self::I16(!int),
             Self::I32(int) => Self::I32(!int),
             Self::I64(int) => Self::I64(!int),
             Self::I128(int) => Self::I128(!int),
-            // Self::GcInt(int) => Self::GcInt(int.bit_not(gc)?),
-            Self::F32(_int) => unimplemented!("No idea how floats work"),
-            Self::F64(_int) => unimplemented!("No idea how floats work"),
+            Self::GcInt(int) => Self::from_big_int(!int.fetch(gc)?, gc)?,
+            Self::Bool(boolean) => Self::Bool(!boolean),
+            // Big unsigned integers have no fixed width to flip the bits of
+            val @ (Self::F32(_) | Self::F64(_) | Self::GcUint(_)) => {
+                return Err(RuntimeError {
+                    ty: RuntimeErrorTy::IncompatibleTypes,
+                    message: format!("Cannot apply the bitwise not to the type {}", val.name()),
+                    backtrace: None,
+                });
+            }
 
             val => {
                 return Err(RuntimeError {
                     ty: RuntimeErrorTy::NullVar,
                     message: format!("Cannot apply the bitwise not to the type {}", val.name()),
+                    backtrace: None,
                 });
             }
         })
     }
 }
 
+/// The error for a failed checked operation, which can only fail by dividing by zero or by overflowing
+fn big_overflow(divide_by_zero: bool, message: String) -> RuntimeError {
+    if divide_by_zero {
+        RuntimeError {
+            ty: RuntimeErrorTy::DivideByZero,
+            message: "Attempted to divide by zero".to_string(),
+            backtrace: None,
+        }
+    } else {
+        RuntimeError {
+            ty: RuntimeErrorTy::IntegerOverflow,
+            message,
+            backtrace: None,
+        }
+    }
+}
+
 macro_rules! upflowing {
-    ($ty:ty, $([$name:tt, $func:tt, $func_two:tt, $func_three:tt, $err_one:literal, $err_two:literal]),*) => {
+    ($ty:ty, $([$name:tt, $op:tt, $func:tt, $checked:ident, $func_two:tt, $func_three:tt, $err_one:literal, $err_two:literal]),*) => {
         impl $ty {
             $(
                 pub fn $name(self, other: Self, gc: &mut Gc) -> Result<Self> {
+                    if let Some((left, right)) = self.promote_floats(&other) {
+                        return left.$name(right, gc);
+                    }
+                    if let Some((left, right)) = self.promote_ints(&other) {
+                        return left.$name(right, gc);
+                    }
+                    if let Some((left, right)) = self.big_ints(&other, gc)? {
+                        let result = <BigInt as num_traits::$checked>::$func(&left, &right).ok_or_else(|| {
+                            big_overflow(right.is_zero(), format!($err_one, "bigint"))
+                        })?;
+
+                        return Self::from_big_int(result, gc);
+                    }
+                    if let Some((left, right)) = self.big_uints(&other, gc)? {
+                        let result = <BigUint as num_traits::$checked>::$func(&left, &right).ok_or_else(|| {
+                            big_overflow(right.is_zero(), format!($err_one, "biguint"))
+                        })?;
+
+                        return Self::from_big_uint(result, gc);
+                    }
+
                     Ok(match (&self, &other) {
                         (Self::Byte(left), Self::Byte(right)) => {
                             if let Some(result) = left.$func(*right) {
@@ -356,10 +763,14 @@ macro_rules! upflowing {
                             if let Some(result) = left.$func(*right) {
                                 Self::U128(result)
                             } else {
-                                todo!()
+                                let right = BigUint::from(*right);
+                                let result = <BigUint as num_traits::$checked>::$func(&BigUint::from(*left), &right).ok_or_else(|| {
+                                    big_overflow(right.is_zero(), format!($err_one, "biguint"))
+                                })?;
+
+                                Self::from_big_uint(result, gc)?
                             }
                         }
-                        // (Self::GcUint(left), Self::GcUint(right)) => Self::GcUint(left.$func_two(*right, gc)?),
 
                         (Self::IByte(left), Self::IByte(right)) => {
                             if let Some(result) = left.$func(*right) {
@@ -393,13 +804,102 @@ macro_rules! upflowing {
                             if let Some(result) = left.$func(*right) {
                                 Self::I128(result)
                             } else {
-                               todo!()
+                                let right = BigInt::from(*right);
+                                let result = <BigInt as num_traits::$checked>::$func(&BigInt::from(*left), &right).ok_or_else(|| {
+                                    big_overflow(right.is_zero(), format!($err_one, "bigint"))
+                                })?;
+
+                                Self::from_big_int(result, gc)?
+                            }
+                        }
+
+                        (Self::F32(left), Self::F32(right)) => Self::F32(left $op right),
+                        (Self::F64(left), Self::F64(right)) => Self::F64(left $op right),
+
+                        (left, right) if left == &Self::None || right == &Self::None => {
+                            return Err(RuntimeError {
+                                ty: RuntimeErrorTy::NullVar,
+                                message: format!(
+                                    $err_two,
+                                    left.name(),
+                                    right.name()
+                                ),
+                                backtrace: None,
+                            });
+                        }
+                        (left, right) => {
+                            return Err(RuntimeError {
+                                ty: RuntimeErrorTy::IncompatibleTypes,
+                                message: format!(
+                                    $err_two,
+                                    left.name(),
+                                    right.name()
+                                ),
+                                backtrace: None,
+                            });
+                        }
+                    })
+                }
+            )*
+        }
+    }
+}
+
+macro_rules! checked {
+    ($ty:ty, $([$name:ident, $panicking:ident, $op:tt, $func:ident, $checked:ident, $err_one:literal, $err_two:literal]),*) => {
+        impl $ty {
+            $(
+                /// Evaluates to `None` instead of overflowing the width of the operands or dividing by zero
+                pub fn $name(self, other: Self, gc: &mut Gc) -> Result<Option<Self>> {
+                    if let Some((left, right)) = self.promote_floats(&other) {
+                        return left.$name(right, gc);
+                    }
+                    if let Some((left, right)) = self.promote_ints(&other) {
+                        return left.$name(right, gc);
+                    }
+                    if let Some((left, right)) = self.big_ints(&other, gc)? {
+                        return <BigInt as num_traits::$checked>::$func(&left, &right)
+                            .map(|int| Self::from_big_int(int, gc))
+                            .transpose();
+                    }
+                    if let Some((left, right)) = self.big_uints(&other, gc)? {
+                        return <BigUint as num_traits::$checked>::$func(&left, &right)
+                            .map(|int| Self::from_big_uint(int, gc))
+                            .transpose();
+                    }
+
+                    Ok(match (&self, &other) {
+                        (Self::Byte(left), Self::Byte(right)) => left.$func(*right).map(Self::Byte),
+                        (Self::U16(left), Self::U16(right)) => left.$func(*right).map(Self::U16),
+                        (Self::U32(left), Self::U32(right)) => left.$func(*right).map(Self::U32),
+                        (Self::U64(left), Self::U64(right)) => left.$func(*right).map(Self::U64),
+                        (Self::U128(left), Self::U128(right)) => left.$func(*right).map(Self::U128),
+
+                        (Self::IByte(left), Self::IByte(right)) => left.$func(*right).map(Self::IByte),
+                        (Self::I16(left), Self::I16(right)) => left.$func(*right).map(Self::I16),
+                        (Self::I32(left), Self::I32(right)) => left.$func(*right).map(Self::I32),
+                        (Self::I64(left), Self::I64(right)) => left.$func(*right).map(Self::I64),
+                        (Self::I128(left), Self::I128(right)) => left.$func(*right).map(Self::I128),
+
+                        // Floats become infinite or NaN instead of overflowing or dividing by zero
+                        (Self::F32(left), Self::F32(right)) => {
+                            let result = left $op right;
+
+                            if result.is_finite() || !left.is_finite() || !right.is_finite() {
+                                Some(Self::F32(result))
+                            } else {
+                                None
                             }
                         }
-                        // (Self::GcInt(left), Self::GcInt(right)) => Self::GcInt(left.$func_two(*right, gc)?),
+                        (Self::F64(left), Self::F64(right)) => {
+                            let result = left $op right;
 
-                        (Self::F32(_left), Self::F32(_right)) => unimplemented!("No idea how floats work"),
-                        (Self::F64(_left), Self::F64(_right)) => unimplemented!("No idea how floats work"),
+                            if result.is_finite() || !left.is_finite() || !right.is_finite() {
+                                Some(Self::F64(result))
+                            } else {
+                                None
+                            }
+                        }
 
                         (left, right) if left == &Self::None || right == &Self::None => {
                             return Err(RuntimeError {
@@ -409,6 +909,7 @@ macro_rules! upflowing {
                                     left.name(),
                                     right.name()
                                 ),
+                                backtrace: None,
                             });
                         }
                         (left, right) => {
@@ -419,10 +920,19 @@ macro_rules! upflowing {
                                     left.name(),
                                     right.name()
                                 ),
+                                backtrace: None,
                             });
                         }
                     })
                 }
+
+                /// Errors instead of overflowing the width of the operands or dividing by zero
+                pub fn $panicking(self, other: Self, gc: &mut Gc) -> Result<Self> {
+                    let (name, divide_by_zero) = (self.name(), other.is_zero());
+
+                    self.$name(other, gc)?
+                        .ok_or_else(|| big_overflow(divide_by_zero, format!($err_one, name)))
+                }
             )*
         }
     }
@@ -432,24 +942,31 @@ macro_rules! binary_op {
     ($ty:ty, $([$name:tt, $op:tt, $func:tt, $err:literal]),*) => {
         impl $ty {
             $(
-                pub fn $name(self, other: Self, _gc: &mut Gc) -> Result<Self> {
+                pub fn $name(self, other: Self, gc: &mut Gc) -> Result<Self> {
+                    if let Some((left, right)) = self.promote_ints(&other) {
+                        return left.$name(right, gc);
+                    }
+                    if let Some((left, right)) = self.big_ints(&other, gc)? {
+                        return Self::from_big_int(left $op right, gc);
+                    }
+                    if let Some((left, right)) = self.big_uints(&other, gc)? {
+                        return Self::from_big_uint(left $op right, gc);
+                    }
+
                     Ok(match (self, other) {
                         (Self::Byte(left), Self::Byte(right)) => Self::Byte(left $op right),
                         (Self::U16(left), Self::U16(right)) => Self::U16(left $op right),
                         (Self::U32(left), Self::U32(right)) => Self::U32(left $op right),
                         (Self::U64(left), Self::U64(right)) => Self::U64(left $op right),
                         (Self::U128(left), Self::U128(right)) => Self::U128(left $op right),
-                        // (Self::GcUint(left), Self::GcUint(right)) => Self::GcUint(left.$func(right, gc)?),
 
                         (Self::IByte(left), Self::IByte(right)) => Self::IByte(left $op right),
                         (Self::I16(left), Self::I16(right)) => Self::I16(left $op right),
                         (Self::I32(left), Self::I32(right)) => Self::I32(left $op right),
                         (Self::I64(left), Self::I64(right)) => Self::I64(left $op right),
                         (Self::I128(left), Self::I128(right)) => Self::I128(left $op right),
-                        // (Self::GcInt(left), Self::GcInt(right)) => Self::GcInt(left.$func(right, gc)?),
 
-                        (Self::F32(_left), Self::F32(_right)) => unimplemented!("No idea how floats work"),
-                        (Self::F64(_left), Self::F64(_right)) => unimplemented!("No idea how floats work"),
+                        (Self::Bool(left), Self::Bool(right)) => Self::Bool(left $op right),
 
                         (left, right) if left == Self::None || right == Self::None => {
                             return Err(RuntimeError {
@@ -459,6 +976,7 @@ macro_rules! binary_op {
                                     left.name(),
                                     right.name()
                                 ),
+                                backtrace: None,
                             });
                         }
                         (left, right) => {
@@ -469,6 +987,7 @@ macro_rules! binary_op {
                                     left.name(),
                                     right.name()
                                 ),
+                                backtrace: None,
                             });
                         }
                     })
@@ -482,7 +1001,9 @@ upflowing!(
     RuntimeValue,
     [
         sub_upflowing,
+        -,
         checked_sub,
+        CheckedSub,
         sub,
         new_subtracting,
         "The attempted subtract is too large to fit in a '{}'",
@@ -490,7 +1011,9 @@ upflowing!(
     ],
     [
         mult_upflowing,
+        *,
         checked_mul,
+        CheckedMul,
         mult,
         new_multiplying,
         "The attempted multiply is too large to fit in a '{}'",
@@ -498,7 +1021,9 @@ upflowing!(
     ],
     [
         div_upflowing,
+        /,
         checked_div,
+        CheckedDiv,
         div,
         new_dividing,
         "The attempted divide is too large to fit in a '{}'",
@@ -506,6 +1031,46 @@ upflowing!(
     ]
 );
 
+checked!(
+    RuntimeValue,
+    [
+        add_checked,
+        add_panicking,
+        +,
+        checked_add,
+        CheckedAdd,
+        "The attempted add is too large to fit in a '{}'",
+        "Values of types '{}' and '{}' cannot be added"
+    ],
+    [
+        sub_checked,
+        sub_panicking,
+        -,
+        checked_sub,
+        CheckedSub,
+        "The attempted subtract is too large to fit in a '{}'",
+        "Values of types '{}' and '{}' cannot be subtracted"
+    ],
+    [
+        mult_checked,
+        mult_panicking,
+        *,
+        checked_mul,
+        CheckedMul,
+        "The attempted multiply is too large to fit in a '{}'",
+        "Values of types '{}' and '{}' cannot be multiplied"
+    ],
+    [
+        div_checked,
+        div_panicking,
+        /,
+        checked_div,
+        CheckedDiv,
+        "The attempted divide is too large to fit in a '{}'",
+        "Values of types '{}' and '{}' cannot be divided"
+    ]
+);
+
 binary_op!(
     RuntimeValue,
     [
@@ -570,7 +1135,7 @@ impl Into<Vec<u8>> for RuntimeValue {
             Self::F32(int) => bytes!(0x0C, int, f32),
             Self::F64(int) => bytes!(0x0D, int, f64),
 
-            Self::Pointer(int) => bytes!(0x0E, int, u16),
+            Self::Pointer(int) => bytes!(0x0E, int, usize),
 
             Self::Bool(boolean) => vec![0x0F, boolean as u8],
 
@@ -589,45 +1154,58 @@ impl Into<Vec<u8>> for RuntimeValue {
     }
 }
 
-impl From<&[u8]> for RuntimeValue {
-    fn from(bytes: &[u8]) -> Self {
-        use std::convert::TryInto;
+impl TryFrom<&[u8]> for RuntimeValue {
+    type Error = RuntimeError;
 
-        match bytes[0] {
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        let invalid = || RuntimeError {
+            ty: RuntimeErrorTy::BytecodeError,
+            message: format!("Invalid encoded value: {:?}", bytes),
+            backtrace: None,
+        };
+
+        macro_rules! from_bytes {
+            ($ty:ty) => {
+                <$ty>::from_le_bytes(
+                    bytes
+                        .get(1..)
+                        .and_then(|bytes| bytes.try_into().ok())
+                        .ok_or_else(invalid)?,
+                )
+            };
+        }
+
+        Ok(match bytes.first().ok_or_else(invalid)? {
             0x00 => Self::None,
             0x01 => Self::Null,
 
-            0x02 => Self::Byte(bytes[1]),
-            0x03 => Self::U16(u16::from_le_bytes(bytes[1..].try_into().unwrap())),
-            0x04 => Self::U32(u32::from_le_bytes(bytes[1..].try_into().unwrap())),
-            0x05 => Self::U64(u64::from_le_bytes(bytes[1..].try_into().unwrap())),
-            0x06 => Self::U128(u128::from_le_bytes(bytes[1..].try_into().unwrap())),
+            0x02 => Self::Byte(from_bytes!(u8)),
+            0x03 => Self::U16(from_bytes!(u16)),
+            0x04 => Self::U32(from_bytes!(u32)),
+            0x05 => Self::U64(from_bytes!(u64)),
+            0x06 => Self::U128(from_bytes!(u128)),
 
-            0x07 => Self::IByte(bytes[1] as i8),
-            0x08 => Self::I16(i16::from_le_bytes(bytes[1..].try_into().unwrap())),
-            0x09 => Self::I32(i32::from_le_bytes(bytes[1..].try_into().unwrap())),
-            0x0A => Self::I64(i64::from_le_bytes(bytes[1..].try_into().unwrap())),
-            0x0B => Self::I128(i128::from_le_bytes(bytes[1..].try_into().unwrap())),
+            0x07 => Self::IByte(from_bytes!(i8)),
+            0x08 => Self::I16(from_bytes!(i16)),
+            0x09 => Self::I32(from_bytes!(i32)),
+            0x0A => Self::I64(from_bytes!(i64)),
+            0x0B => Self::I128(from_bytes!(i128)),
 
-            0x0C => Self::F32(f32::from_le_bytes(bytes[1..].try_into().unwrap())),
-            0x0D => Self::F64(f64::from_le_bytes(bytes[1..].try_into().unwrap())),
+            0x0C => Self::F32(from_bytes!(f32)),
+            0x0D => Self::F64(from_bytes!(f64)),
 
-            0x0E => Self::Pointer(AllocId(usize::from_le_bytes(
-                bytes[1..].try_into().unwrap(),
-            ))),
+            0x0E => Self::Pointer(AllocId(from_bytes!(usize))),
 
-            0x0F => Self::Bool(bytes[1] > 0),
+            0x0F => Self::Bool(from_bytes!(u8) > 0),
 
-            0x10 => Self::Char(
-                std::char::from_u32(u32::from_le_bytes(bytes[1..].try_into().unwrap())).unwrap(),
-            ),
+            0x10 => Self::Char(std::char::from_u32(from_bytes!(u32)).ok_or_else(invalid)?),
             0x11 => Self::Str(Box::leak(
                 String::from_utf8(bytes[1..].to_vec())
-                    .unwrap()
+                    .map_err(|_| invalid())?
                     .into_boxed_str(),
             )),
 
-            _ => unimplemented!(),
-        }
+            _ => return Err(invalid()),
+        })
     }
 }
@@ -1,6 +1,6 @@
 use super::{
-    jit::Jit, parser::Either, Gc, Index, Instruction, Register, Result, RuntimeValue,
-    NUMBER_REGISTERS,
+    jit::Jit, parser::Either, AllocId, Backtrace, Frame, Gc, Index, Instruction, Register, Result,
+    RuntimeValue, NUMBER_CALLER_REGISTERS, NUMBER_REGISTERS,
 };
 use std::{pin::Pin, time::Instant};
 
@@ -14,9 +14,14 @@ impl From<&crate::Options> for VmOptions {
     }
 }
 
+/// The state of a function that has called another function, restored once the callee returns
+///
+/// Only the callee-saved registers are stored, the caller registers are used to pass
+/// arguments into the callee and the return value back out of it
 #[derive(Debug, Clone)]
 pub struct ReturnFrame {
-    pub registers: [RuntimeValue; NUMBER_REGISTERS - 5],
+    pub registers: [RuntimeValue; NUMBER_REGISTERS - NUMBER_CALLER_REGISTERS],
+    pub spills: Vec<RuntimeValue>,
     pub index: Index,
     // If function_index is None, then the main function is being returned to
     pub function_index: u32,
@@ -29,6 +34,10 @@ pub struct Vm {
     pub registers: [RuntimeValue; NUMBER_REGISTERS],
     /// The register snapshots for function calls
     pub return_stack: Vec<ReturnFrame>,
+    /// The value stack, used to pass excess arguments and return values between functions
+    pub stack: Vec<RuntimeValue>,
+    /// The stack slots of the current function, holding registers that have been spilled
+    pub spills: Vec<RuntimeValue>,
     /// The index of the current function
     pub current_func: u32,
     /// The current instruction index of the current function
@@ -59,6 +68,8 @@ impl Vm {
         Self {
             registers: array_init::array_init(|_| RuntimeValue::None),
             return_stack: Vec::new(),
+            stack: Vec::new(),
+            spills: Vec::new(),
             current_func: 0,
             index: Index(0),
             finished_execution: false,
@@ -79,12 +90,71 @@ impl Vm {
         //     .collect::<Vec<_>>();
 
         while !self.finished_execution {
-            functions[self.current_func as usize][*self.index as usize].execute(self)?;
+            if let Err(mut err) =
+                functions[self.current_func as usize][*self.index as usize].execute(self)
+            {
+                err.backtrace = Some(self.backtrace());
+                return Err(err);
+            }
+
+            // Between instructions every live object is held by a register or stack, so it's
+            // safe to collect
+            if self.gc.collection_requested() {
+                let roots = self.roots();
+
+                if let Err(mut err) = self.gc.collect_requested(&roots) {
+                    err.backtrace = Some(self.backtrace());
+                    return Err(err);
+                }
+            }
         }
 
         Ok(())
     }
 
+    /// Gets every heap object that the program can currently reach
+    #[must_use]
+    pub fn roots(&self) -> Vec<AllocId> {
+        let frames = self
+            .return_stack
+            .iter()
+            .flat_map(|frame| frame.registers.iter().chain(&frame.spills));
+
+        self.registers
+            .iter()
+            .chain(std::iter::once(&self.prev_op))
+            .chain(&self.stack)
+            .chain(&self.spills)
+            .chain(frames)
+            .filter_map(RuntimeValue::heap_id)
+            .collect()
+    }
+
+    /// Runs a full collection, rooted by everything the program can currently reach
+    pub fn collect(&mut self) -> Result<()> {
+        let roots = self.roots();
+        self.gc.collect(&roots)
+    }
+
+    /// Builds a backtrace of the functions currently being executed, innermost first
+    #[must_use]
+    pub fn backtrace(&self) -> Backtrace {
+        let current = Frame {
+            function: self.current_func,
+            index: self.index,
+        };
+
+        // Return frames hold the instruction after the call that made them
+        let callers = self.return_stack.iter().rev().map(|frame| Frame {
+            function: frame.function_index,
+            index: Index(frame.index.saturating_sub(1)),
+        });
+
+        Backtrace {
+            frames: std::iter::once(current).chain(callers).collect(),
+        }
+    }
+
     #[inline]
     pub fn clear(&mut self, reg: Register) {
         self.registers[*reg as usize] = RuntimeValue::None;
@@ -112,6 +182,8 @@ impl std::fmt::Debug for Vm {
         fmt.debug_struct("Vm")
             .field("registers", &self.registers)
             .field("return_stack", &self.return_stack)
+            .field("stack", &self.stack)
+            .field("spills", &self.spills)
             .field("current_func", &self.current_func)
             .field("index", &self.index)
             .field("finished_execution", &self.finished_execution)